@@ -34,11 +34,15 @@ mods! {
     }
 
     inl mod enumerations {
+        inl mod axis;
         inl mod battery_devtype;
         inl mod battery_level;
         inl mod battery_type;
         inl mod devsubtype;
         inl mod devtype;
+        inl mod direction;
+        inl mod gate_shape;
+        inl mod thumb_stick;
         inl mod vk;
     }
 
@@ -62,15 +66,41 @@ mods! {
     }
 
     inl mod structures {
+        inl mod arcade_stick_state;
         inl mod audio_device_ids;
         inl mod battery_information;
+        inl mod calibrator;
         inl mod capabilities;
+        inl mod direction_quantizer;
+        inl mod drift_detector;
         inl mod dsound_audio_device_guids;
+        inl mod flight_stick_state;
         inl mod gamepad;
+        inl mod gate_profile;
         inl mod keystroke;
         inl mod state;
+        inl mod stick;
+        inl mod stick_calibration;
         inl mod vibration;
     }
+
+    pub mod actions;
+    pub mod backend;
+    pub mod codec;
+    pub mod copilot;
+    pub mod filter;
+    pub mod gestures;
+    pub mod keymap;
+    pub mod motion;
+    pub mod nav;
+    pub mod players;
+    pub mod pointer;
+    pub mod profile;
+    pub mod remote;
+    pub mod remap;
+    pub mod rollback;
+    pub mod transform;
+    #[cfg(feature = "midi")] #[cfg_attr(doc_cfg, doc(cfg(feature = "midi")))] pub mod midi;
 }
 
 include!("functions/get_state_ex_.rs");
@@ -0,0 +1,390 @@
+//! Named, rebindable digital and analog actions, decoupling game logic from specific [`Buttons`] or [`Axis`]es.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::actions::*;
+//!
+//! let mut map = ActionMap::new();
+//! map.digital("jump",     [Input::Buttons(Buttons::A), Input::Direction(ThumbStick::Left, Direction8::Up)]);
+//! map.analog ("throttle", [Input::Axis(Axis::RightTrigger)]);
+//! map.analog ("pitch",    [Binding::from(Input::Axis(Axis::LeftThumbY)).inverted().with_sensitivity(0.5)]);
+//! let mut actions = Actions::new(map);
+//!
+//! # for _ in 0 .. 1 {
+//! actions.update(); // calls xinput::get_state for every user_index
+//! for user_index in xuser::iter() {
+//!     if actions.action_just_pressed(user_index, "jump") { println!("player {user_index} jumped") }
+//!     let _throttle = actions.action_value(user_index, "throttle");
+//! }
+//! # }
+//!
+//! // "Press a button for jump..."
+//! actions.listen(0, "jump");
+//! # for _ in 0 .. 1 {
+//! if let Some(binding) = actions.update() { println!("jump rebound to {binding:?}") }
+//! # }
+//! ```
+
+use crate::*;
+
+use std::collections::BTreeMap;
+
+
+
+/// Digital actions are pressed once their value reaches this.
+pub const PRESS_THRESHOLD : f32 = 0.5;
+
+/// Whether an action is on/off, or has a continuous value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ActionKind {
+    /// On or off (e.g. "jump".)  [`Actions::action_value`] is `0.0` or `1.0`.
+    Digital,
+
+    /// A continuous value (e.g. "throttle".)  [`Actions::action_value`] is the strongest bound input's value.
+    Analog,
+}
+
+/// A single physical input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    /// All of these buttons are held (`1.0`), or not (`0.0`.)  Specify multiple buttons for a chord.
+    Buttons(Buttons),
+
+    /// A trigger ([`Axis::LeftTrigger`] or [`Axis::RightTrigger`]) is pulled past a raw threshold (`1.0`), or not (`0.0`.)
+    Trigger(Axis, u8),
+
+    /// The value of an axis: `-1.0 ..= 1.0` for thumbsticks, `0.0 ..= 1.0` for triggers.
+    Axis(Axis),
+
+    /// A thumbstick is pushed in a direction (as quantized by [`DirectionQuantizer::default`].)
+    /// Cardinal directions also match adjacent diagonals, e.g. [`Direction8::Up`] matches [`Direction8::UpLeft`].
+    /// Value is how far the stick is pushed in that direction (`0.0 ..= 1.0`.)
+    Direction(ThumbStick, Direction8),
+}
+
+impl From<Buttons> for Input { fn from(buttons: Buttons) -> Self { Input::Buttons(buttons) } }
+impl From<Axis   > for Input { fn from(axis:    Axis   ) -> Self { Input::Axis(axis) } }
+
+impl Input {
+    /// The current value of this input.
+    pub fn value(&self, gamepad: &Gamepad) -> f32 {
+        match *self {
+            Input::Buttons(buttons)         => if buttons != Buttons::None && gamepad.buttons.all_held(buttons) { 1.0 } else { 0.0 },
+            Input::Trigger(axis, threshold) => if axis.is_trigger() && axis.read_raw(gamepad) > i32::from(threshold) { 1.0 } else { 0.0 },
+            Input::Axis(axis)               => axis.read(gamepad),
+            Input::Direction(stick, dir)    => {
+                let (x, y) = stick.read(gamepad);
+                let (held, (bx, by)) = (DirectionQuantizer::default().quantize8(Direction8::Neutral, x, y), dir.to_xy());
+                let (hx, hy) = held.to_xy();
+                if !dir.is_held() || !held.is_held() || (bx != 0 && bx != hx) || (by != 0 && by != hy) { return 0.0 }
+                let (x, y) = (f32::from(x) / 32767.0, f32::from(y) / 32767.0);
+                ((x * bx as f32 + y * by as f32) / (bx as f32).hypot(by as f32)).clamp(0.0, 1.0)
+            },
+        }
+    }
+}
+
+/// An [`Input`] bound to an action, with optional inversion and sensitivity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Binding {
+    /// The physical input.
+    pub input:          Input,
+
+    /// Negate the input's value (e.g. for "invert Y axis" options.)
+    pub invert:         bool,
+
+    /// Multiplier applied to the input's value.
+    pub sensitivity:    f32,
+}
+
+impl From<Input  > for Binding { fn from(input:   Input  ) -> Self { Self { input, invert: false, sensitivity: 1.0 } } }
+impl From<Buttons> for Binding { fn from(buttons: Buttons) -> Self { Input::from(buttons).into() } }
+impl From<Axis   > for Binding { fn from(axis:    Axis   ) -> Self { Input::from(axis).into() } }
+
+impl Binding {
+    /// Toggle [`invert`](Self::invert).
+    pub fn inverted(self) -> Self { Self { invert: !self.invert, .. self } }
+
+    /// Set [`sensitivity`](Self::sensitivity).
+    pub fn with_sensitivity(self, sensitivity: f32) -> Self { Self { sensitivity, .. self } }
+
+    /// The current value of this binding.
+    pub fn value(&self, gamepad: &Gamepad) -> f32 {
+        let value = self.input.value(gamepad) * self.sensitivity;
+        if self.invert { -value } else { value }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Action {
+    kind:       ActionKind,
+    bindings:   Vec<Binding>,
+}
+
+/// Named actions, and the [`Binding`]s for each.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActionMap {
+    actions: BTreeMap<String, Action>,
+}
+
+impl ActionMap {
+    /// An empty map.
+    pub fn new() -> Self { Self::default() }
+
+    /// Declare (or redeclare) a digital action, bound to `bindings`.
+    pub fn digital(&mut self, name: &str, bindings: impl IntoIterator<Item = impl Into<Binding>>) -> &mut Self { self.declare(name, ActionKind::Digital, bindings) }
+
+    /// Declare (or redeclare) an analog action, bound to `bindings`.
+    pub fn analog(&mut self, name: &str, bindings: impl IntoIterator<Item = impl Into<Binding>>) -> &mut Self { self.declare(name, ActionKind::Analog, bindings) }
+
+    fn declare(&mut self, name: &str, kind: ActionKind, bindings: impl IntoIterator<Item = impl Into<Binding>>) -> &mut Self {
+        self.actions.insert(name.into(), Action { kind, bindings: bindings.into_iter().map(Into::into).collect() });
+        self
+    }
+
+    /// The names of all declared actions, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> { self.actions.keys().map(String::as_str) }
+
+    /// The kind of action `name`, or [`None`] if undeclared.
+    pub fn kind(&self, name: &str) -> Option<ActionKind> { self.actions.get(name).map(|a| a.kind) }
+
+    /// The bindings of action `name` (empty if undeclared.)
+    pub fn bindings(&self, name: &str) -> &[Binding] { self.actions.get(name).map_or(&[], |a| &a.bindings[..]) }
+
+    /// Add a binding to action `name`.  Returns `false` if `name` is undeclared.
+    pub fn bind(&mut self, name: &str, binding: impl Into<Binding>) -> bool {
+        let Some(action) = self.actions.get_mut(name) else { return false };
+        action.bindings.push(binding.into());
+        true
+    }
+
+    /// Replace all bindings of action `name`.  Returns `false` if `name` is undeclared.
+    pub fn rebind(&mut self, name: &str, bindings: impl IntoIterator<Item = impl Into<Binding>>) -> bool {
+        let Some(action) = self.actions.get_mut(name) else { return false };
+        action.bindings = bindings.into_iter().map(Into::into).collect();
+        true
+    }
+
+    /// Returns `true` if digital or analog action `name` is pressed (any binding's value reaches [`PRESS_THRESHOLD`].)
+    pub fn pressed(&self, name: &str, gamepad: &Gamepad) -> bool {
+        self.bindings(name).iter().any(|b| b.value(gamepad) >= PRESS_THRESHOLD)
+    }
+
+    /// The value of action `name`: `0.0` or `1.0` for digital actions, the bound value with the greatest magnitude for analog actions.
+    /// Returns `0.0` if `name` is undeclared.
+    pub fn value(&self, name: &str, gamepad: &Gamepad) -> f32 {
+        match self.kind(name) {
+            None                        => 0.0,
+            Some(ActionKind::Digital)   => if self.pressed(name, gamepad) { 1.0 } else { 0.0 },
+            Some(ActionKind::Analog)    => self.bindings(name).iter().map(|b| b.value(gamepad)).fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a }),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Listen {
+    user_index: u32,
+    action:     String,
+}
+
+/// Tracks the [`Gamepad`] of every `user_index`, to query an [`ActionMap`] per user.
+#[derive(Clone, Debug)]
+pub struct Actions {
+    map:        ActionMap,
+    current:    [Gamepad; xuser::MAX_COUNT as usize],
+    previous:   [Gamepad; xuser::MAX_COUNT as usize],
+    listen:     Option<Listen>,
+}
+
+impl Actions {
+    /// Track actions defined by `map`.
+    pub fn new(map: ActionMap) -> Self { Self { map, current: Default::default(), previous: Default::default(), listen: None } }
+
+    /// The action map.
+    pub fn map(&self) -> &ActionMap { &self.map }
+
+    /// The action map, for modifying bindings.
+    pub fn map_mut(&mut self) -> &mut ActionMap { &mut self.map }
+
+    /// Poll [`get_state`] for every `user_index` (disconnected users read as a neutral [`Gamepad`].)
+    ///
+    /// Returns the new binding if this completed a [`listen`](Self::listen).
+    pub fn update(&mut self) -> Option<Binding> {
+        let mut bound = None;
+        for user_index in xuser::iter() {
+            let gamepad = get_state(user_index).map(|s| s.gamepad).unwrap_or_default();
+            bound = bound.or(self.update_user(user_index, &gamepad));
+        }
+        bound
+    }
+
+    /// Update a single `user_index` with a [`Gamepad`] state obtained elsewhere (e.g. from [`get_state_ex`], or filtered.)
+    ///
+    /// Returns the new binding if this completed a [`listen`](Self::listen).
+    pub fn update_user(&mut self, user_index: u32, gamepad: &Gamepad) -> Option<Binding> {
+        let i = user_index as usize;
+        if i >= self.current.len() { return None }
+        self.previous[i] = self.current[i];
+        self.current[i] = *gamepad;
+
+        let listen = self.listen.as_ref().filter(|l| l.user_index == user_index)?;
+        let kind = self.map.kind(&listen.action)?;
+        let binding = detect(kind, &self.previous[i], &self.current[i])?;
+        let listen = self.listen.take()?;
+        self.map.rebind(&listen.action, [binding]);
+        Some(binding)
+    }
+
+    /// Start listening for the next input of `user_index`, which will replace all bindings of `action`.
+    ///
+    /// Inputs already held when listening starts are ignored until released and pressed again.
+    /// Returns `false` (and doesn't listen) if `action` is undeclared.
+    pub fn listen(&mut self, user_index: u32, action: &str) -> bool {
+        if self.map.kind(action).is_none() { return false }
+        self.listen = Some(Listen { user_index, action: action.into() });
+        true
+    }
+
+    /// The `(user_index, action)` currently being listened for, if any.
+    pub fn listening(&self) -> Option<(u32, &str)> { self.listen.as_ref().map(|l| (l.user_index, l.action.as_str())) }
+
+    /// Stop listening, leaving bindings untouched.
+    pub fn cancel_listen(&mut self) { self.listen = None }
+
+    /// The most recent [`Gamepad`] of `user_index`.
+    pub fn gamepad(&self, user_index: u32) -> Gamepad { self.current.get(user_index as usize).copied().unwrap_or_default() }
+
+    /// Returns `true` if `action` is pressed by `user_index`.
+    pub fn action_pressed(&self, user_index: u32, action: &str) -> bool { self.map.pressed(action, &self.gamepad(user_index)) }
+
+    /// Returns `true` if `action` was pressed by `user_index` since the previous update.
+    pub fn action_just_pressed(&self, user_index: u32, action: &str) -> bool {
+        self.action_pressed(user_index, action) && !self.previous.get(user_index as usize).map_or(false, |g| self.map.pressed(action, g))
+    }
+
+    /// Returns `true` if `action` was released by `user_index` since the previous update.
+    pub fn action_just_released(&self, user_index: u32, action: &str) -> bool {
+        !self.action_pressed(user_index, action) && self.previous.get(user_index as usize).map_or(false, |g| self.map.pressed(action, g))
+    }
+
+    /// The value of `action` for `user_index` (see [`ActionMap::value`].)
+    pub fn action_value(&self, user_index: u32, action: &str) -> f32 { self.map.value(action, &self.gamepad(user_index)) }
+}
+
+/// Find an input newly activated between `prev` and `next`, suitable for binding to an action of `kind`.
+fn detect(kind: ActionKind, prev: &Gamepad, next: &Gamepad) -> Option<Binding> {
+    let pressed = next.buttons.into_inner() & !prev.buttons.into_inner();
+    if pressed != 0 { return Some(Input::Buttons(Buttons::from_unchecked(1 << pressed.trailing_zeros())).into()) }
+
+    let threshold = Gamepad::TRIGGER_THRESHOLD;
+    for axis in [Axis::LeftTrigger, Axis::RightTrigger] {
+        let held = |g: &Gamepad| axis.read_raw(g) > i32::from(threshold);
+        if held(next) && !held(prev) {
+            return Some(match kind { ActionKind::Digital => Input::Trigger(axis, threshold), ActionKind::Analog => Input::Axis(axis) }.into());
+        }
+    }
+
+    for stick in ThumbStick::ALL {
+        let pushed = |g: &Gamepad| Stick::from_gamepad(g, stick).magnitude > PRESS_THRESHOLD;
+        if !pushed(next) || pushed(prev) { continue }
+        let (x, y) = stick.read(next);
+        return Some(match kind {
+            ActionKind::Digital => Input::Direction(stick, DirectionQuantizer::MENU.quantize4(Direction4::Neutral, x, y).into()).into(),
+            ActionKind::Analog  => {
+                let (ax, ay) = stick.axes();
+                let (axis, v) = if x.unsigned_abs() >= y.unsigned_abs() { (ax, x) } else { (ay, y) };
+                let binding = Binding::from(axis);
+                if v < 0 { binding.inverted() } else { binding }
+            },
+        });
+    }
+
+    None
+}
+
+
+
+#[test] fn test_values() {
+    let mut map = ActionMap::new();
+    map.digital("jump", [Input::Buttons(Buttons::A), Input::Direction(ThumbStick::Left, Direction8::Up)])
+        .digital("fire", [Input::Trigger(Axis::RightTrigger, 100)])
+        .digital("super", [Buttons::LeftShoulder | Buttons::RightShoulder])
+        .analog("pitch", [Binding::from(Axis::LeftThumbY).inverted().with_sensitivity(0.5), Binding::from(Axis::RightThumbY)]);
+
+    let idle = Gamepad::default();
+    assert!(!map.pressed("jump", &idle));
+    assert!( map.pressed("jump", &Gamepad { buttons: Buttons::A, .. idle }));
+    assert!( map.pressed("jump", &Gamepad { left_thumb_x: -25000, left_thumb_y: 25000, .. idle }), "up-left matches up");
+    assert!(!map.pressed("jump", &Gamepad { left_thumb_x: -25000, .. idle }));
+    assert!(!map.pressed("jump", &Gamepad { left_thumb_y: 10000, .. idle }), "not far enough");
+    assert!(!map.pressed("fire", &Gamepad { right_trigger: 100, .. idle }));
+    assert!( map.pressed("fire", &Gamepad { right_trigger: 101, .. idle }));
+    assert!(!map.pressed("super", &Gamepad { buttons: Buttons::LeftShoulder, .. idle }));
+    assert!( map.pressed("super", &Gamepad { buttons: Buttons::LeftShoulder | Buttons::RightShoulder, .. idle }));
+    assert!(!map.pressed("undeclared", &Gamepad { buttons: Buttons::A, .. idle }));
+
+    assert_eq!(1.0,  map.value("jump",  &Gamepad { buttons: Buttons::A, .. idle }));
+    assert_eq!(-0.5, map.value("pitch", &Gamepad { left_thumb_y: 32767, .. idle }));
+    assert_eq!(0.5,  map.value("pitch", &Gamepad { left_thumb_y: i16::MIN, .. idle }));
+    assert_eq!(-1.0, map.value("pitch", &Gamepad { left_thumb_y: 32767, right_thumb_y: i16::MIN, .. idle }), "strongest binding wins");
+    assert_eq!(0.0,  map.value("undeclared", &idle));
+
+    let v = map.value("jump", &Gamepad { left_thumb_y: 32767, .. idle });
+    assert_eq!(v, 1.0);
+    let v = Input::Direction(ThumbStick::Left, Direction8::UpRight).value(&Gamepad { left_thumb_x: 23170, left_thumb_y: 23170, .. idle });
+    assert!((v - 1.0).abs() < 1e-3, "{v}");
+}
+
+#[test] fn test_per_user() {
+    let mut map = ActionMap::new();
+    map.digital("jump", [Buttons::A]);
+    let mut actions = Actions::new(map);
+    let a = Gamepad { buttons: Buttons::A, .. Gamepad::default() };
+
+    actions.update_user(1, &a);
+    assert!(!actions.action_pressed(0, "jump"));
+    assert!( actions.action_pressed(1, "jump"));
+    assert!( actions.action_just_pressed(1, "jump"));
+    assert!(!actions.action_pressed(9, "jump"));
+    actions.update_user(1, &a);
+    assert!(!actions.action_just_pressed(1, "jump"));
+    actions.update_user(1, &Gamepad::default());
+    assert!( actions.action_just_released(1, "jump"));
+    assert!( actions.map_mut().bind("jump", Buttons::B));
+    assert!(!actions.map_mut().bind("undeclared", Buttons::B));
+    assert_eq!(actions.map().bindings("jump").len(), 2);
+    assert_eq!(actions.map().names().collect::<Vec<_>>(), ["jump"]);
+}
+
+#[test] fn test_listen() {
+    let mut map = ActionMap::new();
+    map.digital("jump", [Buttons::A]).analog("steer", [Axis::LeftThumbX]).digital("menu", [Buttons::Start]);
+    let mut actions = Actions::new(map);
+    let idle = Gamepad::default();
+
+    // held buttons are ignored until pressed again
+    actions.update_user(0, &Gamepad { buttons: Buttons::X, .. idle });
+    assert!( actions.listen(0, "jump"));
+    assert!(!actions.listen(0, "undeclared"));
+    assert_eq!(Some((0, "jump")), actions.listening());
+    assert_eq!(None, actions.update_user(1, &Gamepad { buttons: Buttons::Y, .. idle }), "other user");
+    assert_eq!(None, actions.update_user(0, &Gamepad { buttons: Buttons::X, .. idle }));
+    assert_eq!(Some(Binding::from(Buttons::B)), actions.update_user(0, &Gamepad { buttons: Buttons::X | Buttons::B, .. idle }));
+    assert_eq!(None, actions.listening());
+    assert_eq!(actions.map().bindings("jump"), [Binding::from(Buttons::B)]);
+
+    actions.listen(0, "steer");
+    assert_eq!(None, actions.update_user(0, &Gamepad { right_thumb_x: -10000, .. idle }), "not far enough");
+    assert_eq!(Some(Binding::from(Axis::RightThumbX).inverted()), actions.update_user(0, &Gamepad { right_thumb_x: -30000, .. idle }));
+
+    actions.listen(0, "menu");
+    assert_eq!(Some(Binding::from(Input::Trigger(Axis::LeftTrigger, Gamepad::TRIGGER_THRESHOLD))), actions.update_user(0, &Gamepad { left_trigger: 255, .. idle }));
+    actions.listen(0, "menu");
+    assert_eq!(Some(Binding::from(Input::Direction(ThumbStick::Left, Direction8::Down))), actions.update_user(0, &Gamepad { left_thumb_x: 3000, left_thumb_y: -30000, .. idle }));
+
+    actions.listen(0, "menu");
+    actions.cancel_listen();
+    assert_eq!(None, actions.update_user(0, &Gamepad { buttons: Buttons::A, .. idle }));
+    assert_eq!(actions.map().bindings("menu").len(), 1);
+}
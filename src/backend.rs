@@ -0,0 +1,138 @@
+//! Alternative controller sources for [`get_state`] and [`set_state`] (e.g. [`remote::Server`].)
+//!
+//! Registered [`Backend`]s are consulted before XInput, most recently registered first.
+//! A backend can claim any valid `user_index` (<code>0 .. [xuser::MAX_COUNT]</code>) - even one with a real controller connected - and returns [`None`] / `false` for the rest,
+//! which fall through to the next backend, and ultimately XInput.
+//! Invalid `user_index`es are never dispatched, and still fail with [error::BAD_ARGUMENTS].
+//! Only [`get_state`], [`get_state_ex`] and [`set_state`] are dispatched; other functions always use XInput.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::backend::*;
+//! use std::sync::Arc;
+//!
+//! /// Player 4 holds A forever.
+//! struct Bot;
+//! impl Backend for Bot {
+//!     fn get_state(&self, user_index: u32) -> Option<State> {
+//!         (user_index == 3).then(|| State { packet_number: 1, gamepad: Gamepad { buttons: Buttons::A, .. Gamepad::default() } })
+//!     }
+//!     fn set_state(&self, user_index: u32, _vibration: Vibration) -> bool { user_index == 3 }
+//! }
+//!
+//! let bot = register(Arc::new(Bot));
+//! assert!(xinput::get_state(3).unwrap().buttons.any_held(Buttons::A));
+//! drop(bot); // unregister
+//! ```
+//!
+//! [`get_state_ex`]:   crate::get_state_ex
+
+#[cfg(doc)] use crate::*;
+use crate::{State, Vibration, xuser};
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering::{Acquire, Relaxed, Release}};
+
+
+
+/// A source of controller [`State`]s, and sink for [`Vibration`]s, for some `user_index`es.  See the [module documentation](self).
+///
+/// Called from whichever thread calls [`get_state`] / [`set_state`].
+/// Backends may call XInput functions themselves (no locks are held while a backend is called.)
+pub trait Backend: Send + Sync {
+    /// The current state of `user_index`, or [`None`] to leave `user_index` to the next backend.
+    fn get_state(&self, user_index: u32) -> Option<State>;
+
+    /// Vibrate `user_index`, returning `false` to leave `user_index` to the next backend instead.
+    fn set_state(&self, user_index: u32, vibration: Vibration) -> bool;
+}
+
+/// Keeps a [`Backend`] registered.  Dropping this unregisters it.
+#[derive(Debug)]
+#[must_use = "the backend is unregistered when this is dropped"]
+pub struct Registration(u64);
+
+impl Drop for Registration {
+    fn drop(&mut self) { update(|backends| backends.retain(|(id, _)| *id != self.0)) }
+}
+
+/// Register `backend` to be consulted by [`get_state`] / [`set_state`] (before any previously registered backends) until the returned [`Registration`] is dropped.
+pub fn register(backend: Arc<dyn Backend>) -> Registration {
+    static NEXT_ID : AtomicU64 = AtomicU64::new(0);
+    let id = NEXT_ID.fetch_add(1, Relaxed);
+    update(|backends| backends.insert(0, (id, backend)));
+    Registration(id)
+}
+
+type Backends = Arc<[(u64, Arc<dyn Backend>)]>;
+static BACKENDS : Mutex<Option<Backends>> = Mutex::new(None);
+static ANY      : AtomicBool = AtomicBool::new(false); // `BACKENDS.is_some()`, without locking
+
+fn update(f: impl FnOnce(&mut Vec<(u64, Arc<dyn Backend>)>)) {
+    let mut backends = BACKENDS.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut list = backends.as_deref().map_or(Vec::new(), <[_]>::to_vec);
+    f(&mut list);
+    *backends = if list.is_empty() { None } else { Some(list.into()) };
+    ANY.store(backends.is_some(), Release);
+}
+
+/// Snapshot the backends that may claim `user_index`, so none are called with the lock held.
+fn backends(user_index: u32) -> Option<Backends> {
+    if user_index >= xuser::MAX_COUNT || !ANY.load(Acquire) { return None }
+    BACKENDS.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// [`Backend::get_state`] of the first backend to claim `user_index`.
+pub(crate) fn state(user_index: u32) -> Option<State> {
+    backends(user_index)?.iter().find_map(|(_, backend)| backend.get_state(user_index))
+}
+
+/// [`Backend::set_state`] of the first backend to claim `user_index`.  Returns `false` if none did.
+pub(crate) fn vibrate(user_index: u32, vibration: Vibration) -> bool {
+    backends(user_index).map_or(false, |backends| backends.iter().any(|(_, backend)| backend.set_state(user_index, vibration)))
+}
+
+
+
+/// Serializes tests that register backends, or that expect XInput's results for <code>0 .. [xuser::MAX_COUNT]</code>.
+#[cfg(test)] pub(crate) fn test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK : Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)] struct TestBackend { user_index: u32, packet_number: u32, vibration: Mutex<Option<Vibration>> }
+
+#[cfg(test)] impl Backend for TestBackend {
+    fn get_state(&self, user_index: u32) -> Option<State> { (user_index == self.user_index).then(|| State { packet_number: self.packet_number, .. State::default() }) }
+    fn set_state(&self, user_index: u32, vibration: Vibration) -> bool {
+        if user_index != self.user_index { return false }
+        *self.vibration.lock().unwrap() = Some(vibration);
+        true
+    }
+}
+
+#[test] fn test_dispatch() {
+    let _lock = test_lock();
+    let a = Arc::new(TestBackend { user_index: 2, packet_number: 0xA, vibration: Mutex::new(None) });
+    let b = Arc::new(TestBackend { user_index: 2, packet_number: 0xB, vibration: Mutex::new(None) });
+    let ra = register(a.clone());
+    assert_eq!(0xA, crate::get_state(2).unwrap().packet_number);
+
+    let rb = register(b.clone());
+    assert_eq!(0xB, crate::get_state(2).unwrap().packet_number, "most recently registered first");
+    crate::set_state(2, Vibration { left_motor_speed: 7, right_motor_speed: 8 }).unwrap();
+    assert_eq!(Some((7, 8)), b.vibration.lock().unwrap().map(|v| (v.left_motor_speed, v.right_motor_speed)));
+    assert!(a.vibration.lock().unwrap().is_none());
+
+    drop(rb);
+    assert_eq!(0xA, crate::get_state(2).unwrap().packet_number, "unregistered");
+    drop(ra);
+    assert!(crate::get_state(2).map_or(true, |s| s.packet_number != 0xA), "unregistered");
+
+    let invalid = register(Arc::new(TestBackend { user_index: 7, packet_number: 0x7, vibration: Mutex::new(None) }));
+    let err = crate::get_state(7).expect_err("invalid user_index never dispatched to backends");
+    assert!(matches!(err.kind(), crate::error::BAD_ARGUMENTS | crate::error::CO_E_NOTINITIALIZED), "unexpected error type: {err:?}");
+    assert!(crate::set_state(7, Vibration::default()).is_err());
+    drop(invalid);
+}
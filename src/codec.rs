@@ -0,0 +1,352 @@
+//! Compact bit-packed [`Gamepad`] encoding for netcode, with optional delta compression against a reference frame.
+//!
+//! | Encoding                                  | [`Codec::LOSSLESS`]   | [`Codec::COMPACT`]    |
+//! | ----------------------------------------- | --------------------- | --------------------- |
+//! | [`encode`](Codec::encode)                 | 12 bytes              | 7 bytes               |
+//! | [`encode_delta`](Codec::encode_delta)     | 1 - 18 bytes          | 1 - 13 bytes          |
+//!
+//! A delta of an unchanged frame is a single byte, and small stick movements typically cost 2-3 bytes per changed axis.
+//!
+//! Both sides must agree on the [`Codec`]'s precision (it isn't encoded), and for delta encoding, on the reference frame.
+//! The reference should be a previously *decoded* (or [`quantize`](Codec::quantize)d) frame, so both sides see the same values.
+//!
+//! Decoding validates its input: truncated, out of range, non-canonical, or trailing data is rejected with a [`DecodeError`],
+//! so any bytes that decode successfully re-encode to exactly the same bytes.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::codec::*;
+//!
+//! let codec = Codec::COMPACT;
+//! let mut sent = Gamepad::default();
+//! let mut received = Gamepad::default();
+//! # for _ in 0 .. 1 {
+//! let state = xinput::get_state(0).unwrap_or_default();
+//!
+//! // sender
+//! let packet = codec.encode_delta(&sent, &state.gamepad);
+//! sent = codec.quantize(&state.gamepad);
+//!
+//! // receiver
+//! received = codec.decode_delta(&received, &packet).expect("malformed packet");
+//! assert_eq!(sent.buttons, received.buttons);
+//! # }
+//! ```
+
+use crate::*;
+
+use std::fmt::{self, Display, Formatter};
+
+
+
+/// Encodes and decodes [`Gamepad`]s, with triggers and thumbsticks quantized to the given precision.
+/// Buttons are always encoded losslessly (including unnamed bits.)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Codec {
+    /// Bits per trigger (clamped to `1 ..= 8`.)  `8` is lossless.
+    pub trigger_bits:   u8,
+
+    /// Bits per thumbstick axis (clamped to `1 ..= 16`.)  `16` is lossless.
+    /// Below that, thumbsticks are quantized symmetrically: `0` and `±32767` are preserved exactly, `-32768` becomes `-32767`.
+    pub stick_bits:     u8,
+}
+
+impl Default for Codec { fn default() -> Self { Self::LOSSLESS } }
+
+/// An error returned by [`Codec::decode`] or [`Codec::decode_delta`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DecodeError {
+    /// The input ended early.
+    Truncated,
+
+    /// The input contained an out of range or non-canonical value.
+    Invalid,
+
+    /// The input had extra bytes, or nonzero padding bits, after the encoded [`Gamepad`].
+    TrailingData,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.write_str(match self {
+            DecodeError::Truncated      => "truncated gamepad encoding",
+            DecodeError::Invalid        => "invalid gamepad encoding",
+            DecodeError::TrailingData   => "trailing data after gamepad encoding",
+        })
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Bits used to encode the length of a delta.
+const DELTA_LEN_BITS : u32 = 5;
+
+impl Codec {
+    /// 8 bit triggers, 16 bit thumbsticks: decoding reproduces the encoded [`Gamepad`] exactly.
+    pub const LOSSLESS : Codec = Codec { trigger_bits: 8, stick_bits: 16 };
+
+    /// 4 bit triggers, 8 bit thumbsticks: plenty for most games, especially after a dead zone.
+    pub const COMPACT : Codec = Codec { trigger_bits: 4, stick_bits: 8 };
+
+    /// Round `gamepad`'s triggers and thumbsticks to this codec's precision, as [`decode`](Self::decode)ing an [`encode`](Self::encode)d `gamepad` would.
+    pub fn quantize(&self, gamepad: &Gamepad) -> Gamepad {
+        let mut out = *gamepad;
+        for axis in Axis::ALL { axis.write_raw(&mut out, self.dequantize(axis, self.quantize_axis(axis, axis.read_raw(gamepad)))) }
+        out
+    }
+
+    /// Encode `gamepad` by itself.
+    pub fn encode(&self, gamepad: &Gamepad) -> Vec<u8> {
+        let mut w = BitWriter::default();
+        w.write(gamepad.buttons.into_inner().into(), 16);
+        for axis in Axis::ALL { w.write(self.quantize_axis(axis, axis.read_raw(gamepad)), self.bits(axis)) }
+        w.finish()
+    }
+
+    /// Decode a [`Gamepad`] [`encode`](Self::encode)d with the same precision.
+    pub fn decode(&self, bytes: &[u8]) -> Result<Gamepad, DecodeError> {
+        let mut r = BitReader::new(bytes);
+        let mut gamepad = Gamepad { buttons: Buttons::from_unchecked(r.read(16)? as u16), .. Gamepad::default() };
+        for axis in Axis::ALL {
+            let q = r.read(self.bits(axis))?;
+            if q > self.max(axis) { return Err(DecodeError::Invalid) }
+            axis.write_raw(&mut gamepad, self.dequantize(axis, q));
+        }
+        r.finish()?;
+        Ok(gamepad)
+    }
+
+    /// Encode the changes from `reference` to `gamepad`.
+    pub fn encode_delta(&self, reference: &Gamepad, gamepad: &Gamepad) -> Vec<u8> {
+        let mut w = BitWriter::default();
+        let buttons = reference.buttons.into_inner() ^ gamepad.buttons.into_inner();
+        w.write(u32::from(buttons != 0), 1);
+        if buttons != 0 { w.write(buttons.into(), 16) }
+
+        for axis in Axis::ALL {
+            let from    = self.quantize_axis(axis, axis.read_raw(reference));
+            let to      = self.quantize_axis(axis, axis.read_raw(gamepad));
+            let delta   = to as i32 - from as i32;
+            let zigzag  = ((delta << 1) ^ (delta >> 31)) as u32;
+            w.write(u32::from(zigzag != 0), 1);
+            if zigzag != 0 {
+                let len = 32 - zigzag.leading_zeros();
+                w.write(len, DELTA_LEN_BITS);
+                w.write(zigzag, len);
+            }
+        }
+        w.finish()
+    }
+
+    /// Decode a [`Gamepad`] [`encode_delta`](Self::encode_delta)d against the same `reference`, with the same precision.
+    pub fn decode_delta(&self, reference: &Gamepad, bytes: &[u8]) -> Result<Gamepad, DecodeError> {
+        let mut r = BitReader::new(bytes);
+        let mut gamepad = Gamepad { buttons: reference.buttons, .. Gamepad::default() };
+        if r.read(1)? != 0 {
+            let buttons = r.read(16)? as u16;
+            if buttons == 0 { return Err(DecodeError::Invalid) }
+            gamepad.buttons = Buttons::from_unchecked(reference.buttons.into_inner() ^ buttons);
+        }
+
+        for axis in Axis::ALL {
+            let from = self.quantize_axis(axis, axis.read_raw(reference));
+            let to = if r.read(1)? == 0 { from } else {
+                let len = r.read(DELTA_LEN_BITS)?;
+                if len == 0 || len > self.bits(axis) + 1 { return Err(DecodeError::Invalid) }
+                let zigzag = r.read(len)?;
+                if zigzag >> (len - 1) != 1 { return Err(DecodeError::Invalid) } // non-minimal length
+                let delta = (zigzag >> 1) as i32 ^ -((zigzag & 1) as i32);
+                u32::try_from(from as i32 + delta).ok().filter(|&to| to <= self.max(axis)).ok_or(DecodeError::Invalid)?
+            };
+            axis.write_raw(&mut gamepad, self.dequantize(axis, to));
+        }
+        r.finish()?;
+        Ok(gamepad)
+    }
+
+    fn trigger_bits(&self) -> u32 { u32::from(self.trigger_bits.clamp(1, 8)) }
+    fn stick_bits(&self) -> u32 { u32::from(self.stick_bits.clamp(1, 16)) }
+    fn bits(&self, axis: Axis) -> u32 { if axis.is_trigger() { self.trigger_bits() } else { self.stick_bits() } }
+
+    /// The largest valid quantized value for `axis`.
+    fn max(&self, axis: Axis) -> u32 {
+        match (axis.is_trigger(), self.stick_bits()) {
+            (true, _)   => (1 << self.trigger_bits()) - 1,
+            (false, 16) => 0xFFFF,
+            (false, b)  => (1 << b) - 2, // symmetric: -M ..= M, offset by M = 2^(b-1) - 1
+        }
+    }
+
+    /// Quantize a [`read_raw`](Axis::read_raw) value to `0 ..= self.max(axis)`.
+    fn quantize_axis(&self, axis: Axis, raw: i32) -> u32 {
+        if axis.is_trigger() {
+            let m = self.max(axis) as f64;
+            (f64::from(raw) * m / 255.0).round() as u32
+        } else if self.stick_bits() == 16 {
+            (raw + 32768) as u32
+        } else {
+            let m = (self.max(axis) / 2) as f64;
+            let q = (f64::from(raw) * m / 32767.0).round().clamp(-m, m);
+            (q + m) as u32
+        }
+    }
+
+    /// Inverse of [`quantize_axis`](Self::quantize_axis), to a [`write_raw`](Axis::write_raw) value.
+    fn dequantize(&self, axis: Axis, q: u32) -> i32 {
+        if axis.is_trigger() {
+            let m = self.max(axis) as f64;
+            (f64::from(q) * 255.0 / m).round() as i32
+        } else if self.stick_bits() == 16 {
+            q as i32 - 32768
+        } else {
+            let m = self.max(axis) / 2;
+            if m == 0 { return 0 }
+            ((f64::from(q) - f64::from(m)) * 32767.0 / f64::from(m)).round() as i32
+        }
+    }
+}
+
+
+
+#[derive(Default)] struct BitWriter {
+    bytes:  Vec<u8>,
+    bits:   u32,
+}
+
+impl BitWriter {
+    /// Write the low `bits` bits of `value`, most significant first.
+    fn write(&mut self, value: u32, bits: u32) {
+        for i in (0 .. bits).rev() {
+            if self.bits % 8 == 0 { self.bytes.push(0) }
+            let bit = (value >> i) & 1;
+            *self.bytes.last_mut().unwrap() |= (bit as u8) << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<u8> { self.bytes }
+}
+
+struct BitReader<'b> {
+    bytes:  &'b [u8],
+    bit:    usize,
+}
+
+impl<'b> BitReader<'b> {
+    fn new(bytes: &'b [u8]) -> Self { Self { bytes, bit: 0 } }
+
+    fn read(&mut self, bits: u32) -> Result<u32, DecodeError> {
+        if self.bit + bits as usize > self.bytes.len() * 8 { return Err(DecodeError::Truncated) }
+        let mut value = 0;
+        for _ in 0 .. bits {
+            let bit = (self.bytes[self.bit / 8] >> (7 - self.bit % 8)) & 1;
+            value = (value << 1) | u32::from(bit);
+            self.bit += 1;
+        }
+        Ok(value)
+    }
+
+    /// Ensure only zero padding bits remain.
+    fn finish(self) -> Result<(), DecodeError> {
+        let used = (self.bit + 7) / 8;
+        let padding = if self.bit % 8 == 0 { 0 } else { self.bytes[used - 1] & (0xFF >> (self.bit % 8)) };
+        if used != self.bytes.len() || padding != 0 { Err(DecodeError::TrailingData) } else { Ok(()) }
+    }
+}
+
+
+
+#[cfg(test)] struct Rng(u64);
+#[cfg(test)] impl Rng {
+    fn next(&mut self) -> u64 { self.0 ^= self.0 << 13; self.0 ^= self.0 >> 7; self.0 ^= self.0 << 17; self.0 }
+    fn gamepad(&mut self) -> Gamepad {
+        let mut g = Gamepad { buttons: Buttons::from_unchecked(self.next() as u16), .. Gamepad::default() };
+        for axis in Axis::ALL {
+            let edges : &[i32] = if axis.is_trigger() { &[0, 1, 254, 255] } else { &[-32768, -32767, -1, 0, 1, 32766, 32767] };
+            let raw = if self.next() % 4 == 0 { edges[self.next() as usize % edges.len()] } else if axis.is_trigger() { self.next() as u8 as i32 } else { self.next() as i16 as i32 };
+            axis.write_raw(&mut g, raw);
+        }
+        g
+    }
+}
+
+#[cfg(test)] fn fields(g: &Gamepad) -> (Buttons, [i32; 6]) { (g.buttons, Axis::ALL.map(|a| a.read_raw(g))) }
+
+#[cfg(test)] const CODECS : [Codec; 5] = [Codec::LOSSLESS, Codec::COMPACT, Codec { trigger_bits: 1, stick_bits: 1 }, Codec { trigger_bits: 7, stick_bits: 15 }, Codec { trigger_bits: 0, stick_bits: 99 }];
+
+#[test] fn test_quantize_full_ranges() {
+    for codec in CODECS {
+        for axis in [Axis::LeftThumbX, Axis::LeftTrigger] { // all sticks (and both triggers) share the same code, test_round_trip covers the rest
+            let (range, step, exact) = if axis.is_trigger() {
+                (0 ..= 255, 255.0 / codec.max(axis) as f64, vec![0, 255])
+            } else {
+                let m = codec.max(axis) / 2;
+                (-32768 ..= 32767, if m == 0 { 65535.0 } else { 32767.0 / m as f64 }, if m == 0 { vec![0] } else { vec![-32767, 0, 32767] })
+            };
+            for raw in range {
+                let mut g = Gamepad::default();
+                axis.write_raw(&mut g, raw);
+                let decoded = codec.decode(&codec.encode(&g)).unwrap();
+                let value = axis.read_raw(&decoded);
+                assert_eq!(fields(&codec.quantize(&g)), fields(&decoded), "{codec:?} {axis:?} {raw}");
+                assert_eq!(fields(&decoded), fields(&codec.quantize(&decoded)), "{codec:?} {axis:?} {raw}: quantization should be idempotent");
+                assert!(f64::from((value - raw).abs()) <= step / 2.0 + 1.5, "{codec:?} {axis:?} {raw} => {value}");
+                if codec == Codec::LOSSLESS || exact.contains(&raw) { assert_eq!(raw, value, "{codec:?} {axis:?}") }
+            }
+        }
+    }
+}
+
+#[test] fn test_round_trip() {
+    let mut rng = Rng(0x9E3779B97F4A7C15);
+    for codec in CODECS {
+        let mut reference = Gamepad::default();
+        for _ in 0 .. 2000 {
+            let g = rng.gamepad();
+            let expected = fields(&codec.quantize(&g));
+
+            let full = codec.encode(&g);
+            assert_eq!(Ok(expected), codec.decode(&full).map(|d| fields(&d)));
+            assert_eq!(full, codec.encode(&codec.quantize(&g)));
+
+            let delta = codec.encode_delta(&reference, &g);
+            let decoded = codec.decode_delta(&reference, &delta).unwrap();
+            assert_eq!(expected, fields(&decoded), "{codec:?}");
+            assert_eq!(vec![0], codec.encode_delta(&decoded, &g), "unchanged");
+            reference = decoded;
+        }
+    }
+    assert_eq!(12, Codec::LOSSLESS.encode(&Gamepad::default()).len());
+    assert_eq!(7, Codec::COMPACT.encode(&Gamepad::default()).len());
+    let nudged = Gamepad { left_thumb_x: 300, .. Gamepad::default() };
+    assert_eq!(3, Codec::LOSSLESS.encode_delta(&Gamepad { left_thumb_x: 290, .. Gamepad::default() }, &nudged).len());
+}
+
+#[test] fn test_malformed() {
+    let codec = Codec::COMPACT;
+    let g = Gamepad { buttons: Buttons::A, left_thumb_x: 1234, right_trigger: 99, .. Gamepad::default() };
+    let full = codec.encode(&g);
+    let delta = codec.encode_delta(&Gamepad::default(), &g);
+    let decode          = |bytes: &[u8]| codec.decode(bytes).map(|g| fields(&g));
+    let decode_delta    = |bytes: &[u8]| codec.decode_delta(&Gamepad::default(), bytes).map(|g| fields(&g));
+    for len in 0 .. full.len()  { assert_eq!(Err(DecodeError::Truncated), decode(&full[.. len])) }
+    for len in 0 .. delta.len() { assert_eq!(Err(DecodeError::Truncated), decode_delta(&delta[.. len])) }
+    assert_eq!(Err(DecodeError::TrailingData), decode(&[&full[..], &[0]].concat()));
+    assert_eq!(Err(DecodeError::TrailingData), decode_delta(&[&delta[..], &[0]].concat()));
+    assert_eq!(Err(DecodeError::TrailingData), decode_delta(&[0b0000_0001]), "nonzero padding");
+    assert_eq!(Err(DecodeError::Invalid), decode(&[0, 0, 0xFF, 0, 0, 0, 0]), "stick value 255 is out of range for 8 bit sticks");
+    assert_eq!(Err(DecodeError::Invalid), decode_delta(&[0b1000_0000, 0, 0]), "button change with no changed buttons");
+
+    // every decodable input re-encodes to the same bytes
+    let mut rng = Rng(12345);
+    let reference = Gamepad { left_thumb_x: -20000, left_trigger: 200, .. Gamepad::default() };
+    for codec in CODECS {
+        for _ in 0 .. 20000 {
+            let len = rng.next() as usize % 14;
+            let bytes = (0 .. len).map(|_| rng.next() as u8).collect::<Vec<_>>();
+            if let Ok(g) = codec.decode(&bytes) { assert_eq!(bytes, codec.encode(&g), "{codec:?}") }
+            if let Ok(g) = codec.decode_delta(&reference, &bytes) { assert_eq!(bytes, codec.encode_delta(&reference, &g), "{codec:?}") }
+        }
+    }
+}
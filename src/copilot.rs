@@ -0,0 +1,163 @@
+//! Copilot mode: several controllers acting as a single player, for accessibility.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::copilot::*;
+//!
+//! // player 1 on user 0, assisted by a copilot on user 1
+//! let copilot = Merge::new([0, 1]);
+//!
+//! # for _ in 0 .. 1 {
+//! let state = copilot.get_state().unwrap_or_default();
+//! if state.buttons.any_held(Buttons::A) {
+//!     // rumble both controllers
+//!     let _ = copilot.set_state(Vibration { left_motor_speed: 0x8000, right_motor_speed: 0 });
+//! }
+//! # }
+//! ```
+
+use crate::*;
+
+
+
+/// How to combine the analog inputs of several controllers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// Use the thumbstick with the greatest magnitude, or the trigger pulled furthest.
+    #[default] Max,
+
+    /// Use the first controller (in [`Merge::users`] order) whose thumbstick is outside its [default dead zone](ThumbStick::default_deadzone),
+    /// or whose trigger is past [`Gamepad::TRIGGER_THRESHOLD`].  Falls back on [`Policy::Max`] if none are.
+    Priority,
+}
+
+/// Combines the [`State`]s of several controllers into one logical [`Gamepad`]:
+/// buttons are OR-ed together, and thumbsticks and triggers are chosen per [`Policy`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Merge {
+    /// The `user_index`es of the controllers to merge, highest priority first.
+    pub users:      Vec<u32>,
+
+    /// How to combine thumbsticks (each stick is chosen independently, both axes together.)
+    pub sticks:     Policy,
+
+    /// How to combine triggers (each trigger is chosen independently.)
+    pub triggers:   Policy,
+}
+
+impl Merge {
+    /// Merge `users` (highest priority first) using [`Policy::Max`].
+    pub fn new(users: impl IntoIterator<Item = u32>) -> Self { Self { users: users.into_iter().collect(), sticks: Policy::Max, triggers: Policy::Max } }
+
+    /// Merge `gamepads`, given in priority order.
+    pub fn merge<'g>(&self, gamepads: impl IntoIterator<Item = &'g Gamepad>) -> Gamepad {
+        let gamepads = gamepads.into_iter().collect::<Vec<_>>();
+        let mut out = Gamepad::default();
+        for g in gamepads.iter() { out.buttons |= g.buttons }
+
+        for stick in ThumbStick::ALL {
+            let magnitude = |g: &&Gamepad| { let (x, y) = stick.read(g); i32::from(x).pow(2) + i32::from(y).pow(2) };
+            let deadzone = i32::from(stick.default_deadzone()).pow(2);
+            let max = gamepads.iter().copied().max_by_key(magnitude);
+            let chosen = match self.sticks {
+                Policy::Max         => max,
+                Policy::Priority    => gamepads.iter().copied().find(|g| magnitude(g) > deadzone).or(max),
+            };
+            if let Some(g) = chosen { stick.write(&mut out, stick.read(g)) }
+        }
+
+        for axis in [Axis::LeftTrigger, Axis::RightTrigger] {
+            let max = gamepads.iter().map(|g| axis.read_raw(g)).max();
+            let value = match self.triggers {
+                Policy::Max         => max,
+                Policy::Priority    => gamepads.iter().map(|g| axis.read_raw(g)).find(|&v| v > Gamepad::TRIGGER_THRESHOLD.into()).or(max),
+            };
+            axis.write_raw(&mut out, value.unwrap_or(0));
+        }
+
+        out
+    }
+
+    /// [`get_state`] every controller in [`users`](Self::users), and [merge](Self::merge) the connected ones.
+    ///
+    /// The resulting [`State::packet_number`] is the (wrapping) sum of the packet numbers of the connected controllers,
+    /// so it changes whenever any of them change.  With no [`users`](Self::users), this returns an idle [`State`].
+    ///
+    /// ### Errors
+    /// *   [error::DEVICE_NOT_CONNECTED]   - None of the controllers are connected
+    /// *   Any other error from [`get_state`] for any controller
+    pub fn get_state(&self) -> Result<State, Error> {
+        let mut states = Vec::with_capacity(self.users.len());
+        let mut first_error = None;
+        for &user in self.users.iter() {
+            match get_state(user) {
+                Ok(state)                                                   => states.push(state),
+                Err(err) if err.kind() == error::DEVICE_NOT_CONNECTED      => { first_error.get_or_insert(err); },
+                Err(err)                                                    => return Err(err),
+            }
+        }
+        if states.is_empty() { if let Some(err) = first_error { return Err(err) } }
+        Ok(State {
+            packet_number:  states.iter().fold(0u32, |n, s| n.wrapping_add(s.packet_number)),
+            gamepad:        self.merge(states.iter().map(|s| &s.gamepad)),
+        })
+    }
+
+    /// [`set_state`] every controller in [`users`](Self::users), ignoring disconnected controllers.
+    ///
+    /// ### Errors
+    /// *   [error::DEVICE_NOT_CONNECTED]   - None of the controllers are connected
+    /// *   Any other error from [`set_state`] for any controller (after attempting to set the rest)
+    pub fn set_state(&self, vibration: impl Into<Vibration>) -> Result<(), Error> {
+        let vibration = vibration.into();
+        let mut connected = false;
+        let mut first_error = None;
+        for &user in self.users.iter() {
+            match set_state(user, vibration) {
+                Ok(())                                                      => connected = true,
+                Err(err) if err.kind() == error::DEVICE_NOT_CONNECTED      => { first_error.get_or_insert(err); },
+                Err(err) => {
+                    connected = true;
+                    if first_error.as_ref().map_or(true, |e: &Error| e.kind() == error::DEVICE_NOT_CONNECTED) { first_error = Some(err) }
+                },
+            }
+        }
+        match first_error {
+            Some(err) if !connected || err.kind() != error::DEVICE_NOT_CONNECTED => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+
+
+#[test] fn test_merge_buttons_and_max() {
+    let a = Gamepad { buttons: Buttons::A, left_thumb_x: 30000, right_thumb_y: -5000, left_trigger: 10, right_trigger: 200, .. Gamepad::default() };
+    let b = Gamepad { buttons: Buttons::B | Buttons::A, left_thumb_y: 20000, right_thumb_x: 8000, right_thumb_y: 8000, left_trigger: 100, .. Gamepad::default() };
+    let m = Merge::new([0, 1]).merge([&a, &b]);
+    assert_eq!(Buttons::A | Buttons::B, m.buttons);
+    assert_eq!((30000, 0), (m.left_thumb_x, m.left_thumb_y), "left stick from a, both axes together");
+    assert_eq!((8000, 8000), (m.right_thumb_x, m.right_thumb_y));
+    assert_eq!((100, 200), (m.left_trigger, m.right_trigger));
+
+    let none = Merge::new([]).merge([]);
+    assert_eq!((Buttons::None, 0, 0), (none.buttons, none.left_thumb_x, none.right_trigger));
+}
+
+#[test] fn test_merge_priority() {
+    let pilot   = Gamepad { left_thumb_x: 10000, right_thumb_x: 1000, left_trigger: 50, right_trigger: 10, .. Gamepad::default() };
+    let copilot = Gamepad { left_thumb_x: -32768, right_thumb_x: -20000, left_trigger: 255, right_trigger: 20, .. Gamepad::default() };
+    let merge = Merge { users: vec![0, 1], sticks: Policy::Priority, triggers: Policy::Priority };
+    let m = merge.merge([&pilot, &copilot]);
+    assert_eq!(10000, m.left_thumb_x, "pilot outside dead zone wins");
+    assert_eq!(-20000, m.right_thumb_x, "pilot inside dead zone yields");
+    assert_eq!(50, m.left_trigger);
+    assert_eq!(20, m.right_trigger, "neither past threshold: max");
+}
+
+#[test] fn test_no_users() {
+    let merge = Merge::default();
+    assert_eq!(Buttons::None, merge.get_state().unwrap().buttons);
+    assert!(merge.set_state(Vibration::default()).is_ok());
+}
@@ -0,0 +1,102 @@
+use crate::*;
+
+
+
+/// One of the six analog inputs of a [`Gamepad`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Axis {
+    /// [`Gamepad::left_thumb_x`]
+    LeftThumbX,
+
+    /// [`Gamepad::left_thumb_y`]
+    LeftThumbY,
+
+    /// [`Gamepad::right_thumb_x`]
+    RightThumbX,
+
+    /// [`Gamepad::right_thumb_y`]
+    RightThumbY,
+
+    /// [`Gamepad::left_trigger`]
+    LeftTrigger,
+
+    /// [`Gamepad::right_trigger`]
+    RightTrigger,
+}
+
+impl Axis {
+    /// Every axis.
+    pub const ALL : [Axis; 6] = [Axis::LeftThumbX, Axis::LeftThumbY, Axis::RightThumbX, Axis::RightThumbY, Axis::LeftTrigger, Axis::RightTrigger];
+
+    /// Returns `true` for [`Axis::LeftTrigger`] and [`Axis::RightTrigger`].
+    pub fn is_trigger(self) -> bool { matches!(self, Axis::LeftTrigger | Axis::RightTrigger) }
+
+    /// Read the raw value of this axis (`-32768 ..= 32767` for thumbsticks, `0 ..= 255` for triggers.)
+    pub fn read_raw(self, gamepad: &Gamepad) -> i32 {
+        match self {
+            Axis::LeftThumbX    => gamepad.left_thumb_x.into(),
+            Axis::LeftThumbY    => gamepad.left_thumb_y.into(),
+            Axis::RightThumbX   => gamepad.right_thumb_x.into(),
+            Axis::RightThumbY   => gamepad.right_thumb_y.into(),
+            Axis::LeftTrigger   => gamepad.left_trigger.into(),
+            Axis::RightTrigger  => gamepad.right_trigger.into(),
+        }
+    }
+
+    /// Read this axis, normalized to `-1.0 ..= 1.0` for thumbsticks, or `0.0 ..= 1.0` for triggers.
+    ///
+    /// No dead zone is applied.
+    /// Thumbstick values are scaled by `32767` when positive and `32768` when negative, so `-32768` reads as `-1.0`.
+    pub fn read(self, gamepad: &Gamepad) -> f32 {
+        let raw = self.read_raw(gamepad) as f32;
+        raw / self.scale(raw)
+    }
+
+    /// Overwrite this axis of `gamepad` with a raw value, clamped to the valid range of the axis.
+    pub fn write_raw(self, gamepad: &mut Gamepad, value: i32) {
+        let thumb   = value.clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+        let trigger = value.clamp(0, 255) as u8;
+        match self {
+            Axis::LeftThumbX    => gamepad.left_thumb_x     = thumb,
+            Axis::LeftThumbY    => gamepad.left_thumb_y     = thumb,
+            Axis::RightThumbX   => gamepad.right_thumb_x    = thumb,
+            Axis::RightThumbY   => gamepad.right_thumb_y    = thumb,
+            Axis::LeftTrigger   => gamepad.left_trigger     = trigger,
+            Axis::RightTrigger  => gamepad.right_trigger    = trigger,
+        }
+    }
+
+    /// Overwrite this axis of `gamepad` with a normalized value (see [`Axis::read`]), clamped to the valid range of the axis.
+    ///
+    /// The inverse of [`Axis::read`]: `-1.0` writes `-32768`, and every raw value survives a `read` / `write` round trip.
+    pub fn write(self, gamepad: &mut Gamepad, value: f32) {
+        self.write_raw(gamepad, (value * self.scale(value)).round() as i32);
+    }
+
+    fn scale(self, value: f32) -> f32 {
+        if self.is_trigger() { 255.0 } else if value < 0.0 { 32768.0 } else { 32767.0 }
+    }
+}
+
+#[test] fn test_read_write() {
+    let mut gamepad = Gamepad::default();
+    for axis in Axis::ALL {
+        axis.write(&mut gamepad, 1.0);
+        assert_eq!(axis.read(&gamepad), 1.0, "{axis:?}");
+        axis.write_raw(&mut gamepad, i32::MIN);
+        assert_eq!(axis.read(&gamepad), if axis.is_trigger() { 0.0 } else { -1.0 }, "{axis:?}");
+        axis.write(&mut gamepad, 0.0);
+        assert_eq!(axis.read_raw(&gamepad), 0, "{axis:?}");
+    }
+    gamepad.left_thumb_y = i16::MIN;
+    assert_eq!(Axis::LeftThumbY.read_raw(&gamepad), -32768);
+    assert_eq!(Axis::LeftThumbY.read(&gamepad), -1.0);
+    for raw in [i16::MIN, -32767, -16384, -1, 0, 1, 16384, i16::MAX] {
+        gamepad.left_thumb_y = raw;
+        let value = Axis::LeftThumbY.read(&gamepad);
+        Axis::LeftThumbY.write(&mut gamepad, value);
+        assert_eq!(gamepad.left_thumb_y, raw, "round trip");
+    }
+    Axis::LeftThumbY.write(&mut gamepad, -1.0);
+    assert_eq!(gamepad.left_thumb_y, i16::MIN);
+}
@@ -23,5 +23,24 @@ enumish! { BatteryDevType => u8; default: Gamepad == 0; Gamepad, Headset }
     pub const Headset : BatteryDevType = BatteryDevType(BATTERY_DEVTYPE_HEADSET);
 }
 
+
+
+#[test] fn test_display_from_str() {
+    assert_eq!("Headset",   BatteryDevType::Headset.to_string());
+    assert_eq!("0xff",      BatteryDevType(0xFF).to_string());
+    for ty in [BatteryDevType::Gamepad, BatteryDevType::Headset, BatteryDevType(0xFF)] {
+        assert_eq!(Ok(ty), ty.to_string().parse(), "Display {ty}");
+        assert_eq!(Ok(ty), format!("{ty:?}").parse(), "Debug {ty:?}");
+    }
+    for s in ["Headset", "BatteryDevType::Headset", "0x1", "1"] {
+        assert_eq!(Ok(BatteryDevType::Headset), s.parse(), "{s:?}");
+    }
+    for s in ["", "headset", "DevType::Headset", "0x100"] {
+        assert!(s.parse::<BatteryDevType>().is_err(), "{s:?}");
+    }
+}
+
+
+
 //#cpp2rust BATTERY_DEVTYPE_GAMEPAD = xinput::BatteryDevType::Gamepad
 //#cpp2rust BATTERY_DEVTYPE_HEADSET = xinput::BatteryDevType::Headset
@@ -18,6 +18,25 @@ enumish! { BatteryLevel => u8; default: Empty == 0; Empty, Low, Medium, Full }
     pub const Full      : BatteryLevel = BatteryLevel(BATTERY_LEVEL_FULL as _);
 }
 
+
+
+#[test] fn test_display_from_str() {
+    assert_eq!("Medium",    BatteryLevel::Medium.to_string());
+    assert_eq!("0x4",       BatteryLevel(4).to_string());
+    for level in [BatteryLevel::Empty, BatteryLevel::Low, BatteryLevel::Medium, BatteryLevel::Full, BatteryLevel(4)] {
+        assert_eq!(Ok(level), level.to_string().parse(), "Display {level}");
+        assert_eq!(Ok(level), format!("{level:?}").parse(), "Debug {level:?}");
+    }
+    for s in ["Full", "BatteryLevel::Full", "0x3", "3"] {
+        assert_eq!(Ok(BatteryLevel::Full), s.parse(), "{s:?}");
+    }
+    for s in ["", "full", "BatteryType::Full", "Low|Full", "0x100"] {
+        assert!(s.parse::<BatteryLevel>().is_err(), "{s:?}");
+    }
+}
+
+
+
 //#cpp2rust BATTERY_LEVEL_EMPTY     = xinput::BatteryLevel::Empty
 //#cpp2rust BATTERY_LEVEL_LOW       = xinput::BatteryLevel::Low
 //#cpp2rust BATTERY_LEVEL_MEDIUM    = xinput::BatteryLevel::Medium
@@ -30,6 +30,26 @@ enumish! { BatteryType => u8; default: Disconnected == 0; Disconnected, Wired, A
     pub const Unknown : BatteryType = BatteryType(BATTERY_TYPE_UNKNOWN as _); // 255
 }
 
+
+
+#[test] fn test_display_from_str() {
+    assert_eq!("NiMH",      BatteryType::NiMH.to_string());
+    assert_eq!("Unknown",   BatteryType::Unknown.to_string());
+    assert_eq!("0x42",      BatteryType(0x42).to_string());
+    for ty in [BatteryType::Disconnected, BatteryType::Wired, BatteryType::Alkaline, BatteryType::NiMH, BatteryType::Unknown, BatteryType(0x42)] {
+        assert_eq!(Ok(ty), ty.to_string().parse(), "Display {ty}");
+        assert_eq!(Ok(ty), format!("{ty:?}").parse(), "Debug {ty:?}");
+    }
+    for s in ["Alkaline", "BatteryType::Alkaline", "0x2", "2"] {
+        assert_eq!(Ok(BatteryType::Alkaline), s.parse(), "{s:?}");
+    }
+    for s in ["", "nimh", "BatteryLevel::Alkaline", "0x100", "-1"] {
+        assert!(s.parse::<BatteryType>().is_err(), "{s:?}");
+    }
+}
+
+
+
 //#cpp2rust BATTERY_TYPE_DISCONNECTED   = xinput::BatteryType::Disconnected
 //#cpp2rust BATTERY_TYPE_WIRED          = xinput::BatteryType::Wired
 //#cpp2rust BATTERY_TYPE_ALKALINE       = xinput::BatteryType::Alkaline
@@ -16,4 +16,23 @@ enumish! { DevType => u8; Gamepad }
     pub const Gamepad : DevType = DevType(XINPUT_DEVTYPE_GAMEPAD as _); // 1
 }
 
+
+
+#[test] fn test_display_from_str() {
+    assert_eq!("Gamepad",   DevType::Gamepad.to_string());
+    assert_eq!("0x42",      DevType(0x42).to_string());
+    for ty in [DevType::Gamepad, DevType(0), DevType(0x42)] {
+        assert_eq!(Ok(ty), ty.to_string().parse(), "Display {ty}");
+        assert_eq!(Ok(ty), format!("{ty:?}").parse(), "Debug {ty:?}");
+    }
+    for s in ["Gamepad", "DevType::Gamepad", "0x1", "1"] {
+        assert_eq!(Ok(DevType::Gamepad), s.parse(), "{s:?}");
+    }
+    for s in ["", "DevSubType::Gamepad", "Gamepad|Gamepad", "0x100", "256"] {
+        assert!(s.parse::<DevType>().is_err(), "{s:?}");
+    }
+}
+
+
+
 //#cpp2rust XINPUT_DEVTYPE_GAMEPAD = xinput::DevType::Gamepad
@@ -0,0 +1,238 @@
+use crate::*;
+
+
+
+/// An 8-way direction (or neutral), as read from a D-pad, arcade lever, or hat switch.
+///
+/// Discriminants follow [numpad notation](https://glossary.infil.net/?t=Numpad%20Notation) as used by fighting game communities:
+///
+/// | `7` ↖ | `8` ↑ | `9` ↗ |
+/// |:-----:|:-----:|:-----:|
+/// | `4` ← | `5` · | `6` → |
+/// | `1` ↙ | `2` ↓ | `3` ↘ |
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default)]
+#[repr(u8)] pub enum Direction8 {
+    /// `1` &mdash; down and left
+    DownLeft    = 1,
+    /// `2` &mdash; down
+    Down        = 2,
+    /// `3` &mdash; down and right
+    DownRight   = 3,
+    /// `4` &mdash; left
+    Left        = 4,
+    /// `5` &mdash; centered / no direction held
+    #[default] Neutral = 5,
+    /// `6` &mdash; right
+    Right       = 6,
+    /// `7` &mdash; up and left
+    UpLeft      = 7,
+    /// `8` &mdash; up
+    Up          = 8,
+    /// `9` &mdash; up and right
+    UpRight     = 9,
+}
+
+impl Direction8 {
+    /// Every direction, in numpad order (`1 ..= 9`.)
+    pub const ALL : [Direction8; 9] = [
+        Direction8::DownLeft, Direction8::Down,    Direction8::DownRight,
+        Direction8::Left,     Direction8::Neutral, Direction8::Right,
+        Direction8::UpLeft,   Direction8::Up,      Direction8::UpRight,
+    ];
+
+    /// Build a direction from signed horizontal (`+1` = right) and vertical (`+1` = up) components.
+    /// Only the sign of each component matters.
+    pub fn from_xy(x: i32, y: i32) -> Self {
+        Self::ALL[(y.signum() + 1) as usize * 3 + (x.signum() + 1) as usize]
+    }
+
+    /// Returns the horizontal (`-1` = left, `+1` = right) and vertical (`-1` = down, `+1` = up) components of this direction.
+    pub fn to_xy(self) -> (i32, i32) {
+        let n = self as i32 - 1;
+        (n % 3 - 1, n / 3 - 1)
+    }
+
+    /// Read the D-pad of `buttons` as a direction.
+    ///
+    /// Opposing directions held simultaneously (e.g. [`Buttons::DPadLeft`] + [`Buttons::DPadRight`]) cancel out,
+    /// which matches the "neutral" SOCD cleaning most arcade sticks perform in hardware.
+    pub fn from_dpad(buttons: Buttons) -> Self {
+        let x = buttons.any_held(Buttons::DPadRight) as i32 - buttons.any_held(Buttons::DPadLeft) as i32;
+        let y = buttons.any_held(Buttons::DPadUp   ) as i32 - buttons.any_held(Buttons::DPadDown) as i32;
+        Self::from_xy(x, y)
+    }
+
+    /// Convert this direction back into D-pad [`Buttons`].
+    pub fn to_dpad(self) -> Buttons {
+        let (x, y) = self.to_xy();
+        let mut buttons = Buttons::None;
+        if x < 0 { buttons |= Buttons::DPadLeft  }
+        if x > 0 { buttons |= Buttons::DPadRight }
+        if y < 0 { buttons |= Buttons::DPadDown  }
+        if y > 0 { buttons |= Buttons::DPadUp    }
+        buttons
+    }
+
+    /// Returns the numpad notation digit (`1 ..= 9`) of this direction.
+    pub fn numpad(self) -> u8 { self as u8 }
+
+    /// Parse a numpad notation digit (`1 ..= 9`) into a direction.
+    pub fn from_numpad(digit: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(digit).checked_sub(1)?).copied()
+    }
+
+    /// Returns `true` unless this is [`Direction8::Neutral`].
+    pub fn is_held(self) -> bool { self != Direction8::Neutral }
+
+    /// Mirror left/right (e.g. for a fighting game character facing left.)
+    pub fn mirror_x(self) -> Self {
+        let (x, y) = self.to_xy();
+        Self::from_xy(-x, y)
+    }
+
+    /// Convert to a thumbstick [`VK`] (e.g. [`VK::PadLThumbUpLeft`]), or [`VK::None`] if [`Direction8::Neutral`].
+    pub fn to_vk(self, stick: ThumbStick) -> VK {
+        use Direction8::*;
+        match (stick, self) {
+            (_,                 Neutral)    => VK::None,
+            (ThumbStick::Left,  Up)         => VK::PadLThumbUp,
+            (ThumbStick::Left,  Down)       => VK::PadLThumbDown,
+            (ThumbStick::Left,  Left)       => VK::PadLThumbLeft,
+            (ThumbStick::Left,  Right)      => VK::PadLThumbRight,
+            (ThumbStick::Left,  UpLeft)     => VK::PadLThumbUpLeft,
+            (ThumbStick::Left,  UpRight)    => VK::PadLThumbUpRight,
+            (ThumbStick::Left,  DownLeft)   => VK::PadLThumbDownLeft,
+            (ThumbStick::Left,  DownRight)  => VK::PadLThumbDownRight,
+            (ThumbStick::Right, Up)         => VK::PadRThumbUp,
+            (ThumbStick::Right, Down)       => VK::PadRThumbDown,
+            (ThumbStick::Right, Left)       => VK::PadRThumbLeft,
+            (ThumbStick::Right, Right)      => VK::PadRThumbRight,
+            (ThumbStick::Right, UpLeft)     => VK::PadRThumbUpLeft,
+            (ThumbStick::Right, UpRight)    => VK::PadRThumbUpRight,
+            (ThumbStick::Right, DownLeft)   => VK::PadRThumbDownLeft,
+            (ThumbStick::Right, DownRight)  => VK::PadRThumbDownRight,
+        }
+    }
+
+    /// Convert a thumbstick [`VK`] (e.g. [`VK::PadRThumbDown`]) into the stick and direction it represents.
+    /// Returns [`None`] for all other [`VK`]s, including D-pad ones.
+    pub fn from_vk(vk: VK) -> Option<(ThumbStick, Self)> {
+        ThumbStick::ALL.iter().flat_map(|&stick| Self::ALL.iter().map(move |&dir| (stick, dir)))
+            .find(|&(stick, dir)| dir.is_held() && dir.to_vk(stick) == vk)
+    }
+
+    /// Returns the angle of this direction in radians, counterclockwise from [`Direction8::Right`], or [`None`] if [`Direction8::Neutral`].
+    pub fn angle(self) -> Option<f32> {
+        let (x, y) = self.to_xy();
+        self.is_held().then(|| (y as f32).atan2(x as f32))
+    }
+}
+
+
+
+/// A 4-way direction (or neutral), e.g. for menu navigation.
+///
+/// Discriminants follow the same numpad notation as [`Direction8`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default)]
+#[repr(u8)] pub enum Direction4 {
+    /// `2` &mdash; down
+    Down        = 2,
+    /// `4` &mdash; left
+    Left        = 4,
+    /// `5` &mdash; centered / no direction held
+    #[default] Neutral = 5,
+    /// `6` &mdash; right
+    Right       = 6,
+    /// `8` &mdash; up
+    Up          = 8,
+}
+
+impl Direction4 {
+    /// Every direction, in numpad order.
+    pub const ALL : [Direction4; 5] = [Direction4::Down, Direction4::Left, Direction4::Neutral, Direction4::Right, Direction4::Up];
+
+    /// Returns the horizontal (`-1` = left, `+1` = right) and vertical (`-1` = down, `+1` = up) components of this direction.
+    pub fn to_xy(self) -> (i32, i32) { Direction8::from(self).to_xy() }
+
+    /// Read the D-pad of `buttons` as a direction.  Diagonals, and opposing directions, are [`Direction4::Neutral`].
+    pub fn from_dpad(buttons: Buttons) -> Self { Direction8::from_dpad(buttons).try_into().unwrap_or_default() }
+
+    /// Convert this direction back into D-pad [`Buttons`].
+    pub fn to_dpad(self) -> Buttons { Direction8::from(self).to_dpad() }
+
+    /// Returns `true` unless this is [`Direction4::Neutral`].
+    pub fn is_held(self) -> bool { self != Direction4::Neutral }
+
+    /// Convert to a thumbstick [`VK`] (e.g. [`VK::PadLThumbUp`]), or [`VK::None`] if [`Direction4::Neutral`].
+    pub fn to_vk(self, stick: ThumbStick) -> VK { Direction8::from(self).to_vk(stick) }
+
+    /// Convert a cardinal thumbstick [`VK`] (e.g. [`VK::PadRThumbDown`]) into the stick and direction it represents.
+    /// Returns [`None`] for all other [`VK`]s, including diagonal and D-pad ones.
+    pub fn from_vk(vk: VK) -> Option<(ThumbStick, Self)> {
+        let (stick, dir) = Direction8::from_vk(vk)?;
+        Some((stick, dir.try_into().ok()?))
+    }
+
+    /// Returns the angle of this direction in radians, counterclockwise from [`Direction4::Right`], or [`None`] if [`Direction4::Neutral`].
+    pub fn angle(self) -> Option<f32> { Direction8::from(self).angle() }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(dir: Direction4) -> Self { Direction8::from_numpad(dir as u8).unwrap_or_default() }
+}
+
+impl TryFrom<Direction8> for Direction4 {
+    type Error = Direction8;
+
+    /// Fails for diagonals, returning the original [`Direction8`].
+    fn try_from(dir: Direction8) -> Result<Self, Direction8> {
+        Direction4::ALL.iter().copied().find(|&d| d as u8 == dir as u8).ok_or(dir)
+    }
+}
+
+
+
+#[test] fn test_xy_round_trip() {
+    for dir in Direction8::ALL {
+        let (x, y) = dir.to_xy();
+        assert_eq!(dir, Direction8::from_xy(x, y));
+        assert_eq!(Some(dir), Direction8::from_numpad(dir.numpad()));
+        assert_eq!(dir, Direction8::from_dpad(dir.to_dpad()));
+    }
+    assert_eq!(None, Direction8::from_numpad(0));
+    assert_eq!(None, Direction8::from_numpad(10));
+}
+
+#[test] fn test_dpad() {
+    assert_eq!(Direction8::Neutral,     Direction8::from_dpad(Buttons::None));
+    assert_eq!(Direction8::UpRight,     Direction8::from_dpad(Buttons::DPadUp | Buttons::DPadRight | Buttons::A));
+    assert_eq!(Direction8::Down,        Direction8::from_dpad(Buttons::DPadDown | Buttons::DPadLeft | Buttons::DPadRight));
+    assert_eq!(Direction8::Neutral,     Direction8::from_dpad(Buttons::DPadUp | Buttons::DPadDown));
+    assert_eq!(Direction8::DownLeft,    Direction8::DownRight.mirror_x());
+}
+
+#[test] fn test_direction4() {
+    for dir in Direction4::ALL {
+        assert_eq!(Ok(dir), Direction4::try_from(Direction8::from(dir)));
+        assert_eq!(dir, Direction4::from_dpad(dir.to_dpad()));
+    }
+    assert_eq!(Err(Direction8::UpLeft), Direction4::try_from(Direction8::UpLeft));
+    assert_eq!(Direction4::Neutral, Direction4::from_dpad(Buttons::DPadUp | Buttons::DPadLeft));
+}
+
+#[test] fn test_vk() {
+    assert_eq!(VK::None,                Direction8::Neutral.to_vk(ThumbStick::Left));
+    assert_eq!(VK::PadLThumbDownLeft,   Direction8::DownLeft.to_vk(ThumbStick::Left));
+    assert_eq!(VK::PadRThumbUp,         Direction4::Up.to_vk(ThumbStick::Right));
+    for stick in ThumbStick::ALL {
+        for dir in Direction8::ALL.into_iter().filter(|d| d.is_held()) {
+            assert_eq!(Some((stick, dir)), Direction8::from_vk(dir.to_vk(stick)));
+        }
+    }
+    assert_eq!(None,                                        Direction8::from_vk(VK::PadDPadUp));
+    assert_eq!(None,                                        Direction8::from_vk(VK::None));
+    assert_eq!(None,                                        Direction4::from_vk(VK::PadRThumbUpLeft));
+    assert_eq!(Some((ThumbStick::Right, Direction4::Left)), Direction4::from_vk(VK::PadRThumbLeft));
+}
@@ -0,0 +1,49 @@
+use std::f32::consts::FRAC_PI_8;
+
+
+
+/// The shape of the physical gate limiting a thumbstick's range of motion, as seen in raw [`Gamepad`](crate::Gamepad) coordinates.
+///
+/// | Shape                     | Reach at 0° | Reach at 22.5°    | Reach at 45°  | Typical diagonal      |
+/// | ------------------------- |:-----------:|:-----------------:|:-------------:| --------------------- |
+/// | [`GateShape::Circle`]     | 1.0         | 1.0               | 1.0           | `(23170, 23170)`      |
+/// | [`GateShape::Octagon`]    | 1.0         | ≈0.924            | 1.0           | `(23170, 23170)`      |
+/// | [`GateShape::Square`]     | 1.0         | ≈1.082            | ≈1.414        | `(32767, 32767)`      |
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GateShape {
+    /// A round gate (first party Xbox controllers.)
+    Circle,
+
+    /// An octagonal gate with notches in the 8 cardinal and diagonal directions.
+    Octagon,
+
+    /// A square gate, or a round gate whose coordinates are stretched to a square (common with third party controllers.)
+    Square,
+}
+
+impl GateShape {
+    /// Every shape.
+    pub const ALL : [GateShape; 3] = [GateShape::Circle, GateShape::Octagon, GateShape::Square];
+
+    /// Normalized distance from center to the edge of this gate, in the direction of `angle` (radians, counterclockwise from right.)
+    pub fn reach(self, angle: f32) -> f32 {
+        match self {
+            GateShape::Circle   => 1.0,
+            GateShape::Octagon  => FRAC_PI_8.cos() / (angle.rem_euclid(2.0 * FRAC_PI_8) - FRAC_PI_8).cos(),
+            GateShape::Square   => 1.0 / angle.cos().abs().max(angle.sin().abs()),
+        }
+    }
+}
+
+
+
+#[test] fn test_reach() {
+    use std::f32::consts::*;
+    for shape in GateShape::ALL {
+        for k in 0 .. 4 { assert!((shape.reach(k as f32 * FRAC_PI_2) - 1.0).abs() < 1e-5, "{shape:?}"); }
+    }
+    assert!((GateShape::Square .reach(FRAC_PI_4) - SQRT_2).abs() < 1e-5);
+    assert!((GateShape::Octagon.reach(-FRAC_PI_4) - 1.0).abs() < 1e-5);
+    assert!((GateShape::Octagon.reach(FRAC_PI_8) - 0.92388).abs() < 1e-5);
+    assert!((GateShape::Circle .reach(1.234) - 1.0).abs() < 1e-5);
+}
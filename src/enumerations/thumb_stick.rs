@@ -0,0 +1,67 @@
+use crate::*;
+
+
+
+/// Selects the left or right thumbstick of a [`Gamepad`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ThumbStick {
+    /// [`Gamepad::left_thumb_x`], [`Gamepad::left_thumb_y`]
+    Left,
+
+    /// [`Gamepad::right_thumb_x`], [`Gamepad::right_thumb_y`]
+    Right,
+}
+
+impl ThumbStick {
+    /// Both thumbsticks.
+    pub const ALL : [ThumbStick; 2] = [ThumbStick::Left, ThumbStick::Right];
+
+    /// Read the raw `(x, y)` coordinates of this thumbstick.
+    pub fn read(self, gamepad: &Gamepad) -> (i16, i16) {
+        match self {
+            ThumbStick::Left    => (gamepad.left_thumb_x,  gamepad.left_thumb_y ),
+            ThumbStick::Right   => (gamepad.right_thumb_x, gamepad.right_thumb_y),
+        }
+    }
+
+    /// Overwrite the raw `(x, y)` coordinates of this thumbstick.
+    pub fn write(self, gamepad: &mut Gamepad, (x, y): (i16, i16)) {
+        match self {
+            ThumbStick::Left    => { gamepad.left_thumb_x  = x; gamepad.left_thumb_y  = y; },
+            ThumbStick::Right   => { gamepad.right_thumb_x = x; gamepad.right_thumb_y = y; },
+        }
+    }
+
+    /// The X and Y [`Axis`] of this thumbstick.
+    pub fn axes(self) -> (Axis, Axis) {
+        match self {
+            ThumbStick::Left    => (Axis::LeftThumbX,  Axis::LeftThumbY ),
+            ThumbStick::Right   => (Axis::RightThumbX, Axis::RightThumbY),
+        }
+    }
+
+    /// [`Gamepad::LEFT_THUMB_DEADZONE`] or [`Gamepad::RIGHT_THUMB_DEADZONE`].
+    pub fn default_deadzone(self) -> i16 {
+        match self {
+            ThumbStick::Left    => Gamepad::LEFT_THUMB_DEADZONE,
+            ThumbStick::Right   => Gamepad::RIGHT_THUMB_DEADZONE,
+        }
+    }
+
+    /// [`Buttons::LeftThumb`] or [`Buttons::RightThumb`] (pressing the stick in.)
+    pub fn button(self) -> Buttons {
+        match self {
+            ThumbStick::Left    => Buttons::LeftThumb,
+            ThumbStick::Right   => Buttons::RightThumb,
+        }
+    }
+}
+
+#[test] fn test_read_write() {
+    let mut gamepad = Gamepad::default();
+    ThumbStick::Left .write(&mut gamepad, (1, 2));
+    ThumbStick::Right.write(&mut gamepad, (3, 4));
+    assert_eq!((1, 2), ThumbStick::Left .read(&gamepad));
+    assert_eq!((3, 4), ThumbStick::Right.read(&gamepad));
+    assert_eq!(3, ThumbStick::Right.axes().0.read_raw(&gamepad));
+}
@@ -164,6 +164,25 @@ enumish! {
     pub const PadRThumbDownLeft : VK = VK(VK_PAD_RTHUMB_DOWNLEFT);
 }
 
+#[test] fn test_display_from_str() {
+    assert_eq!("PadA",      VK::PadA.to_string());
+    assert_eq!("0x1234",    VK(0x1234).to_string());
+    for vk in [VK::None, VK::PadA, VK::PadRThumbDownLeft, VK(0x1234)] {
+        assert_eq!(Ok(vk), vk.to_string().parse(), "Display {vk}");
+        assert_eq!(Ok(vk), format!("{vk:?}").parse(), "Debug {vk:?}");
+    }
+    for s in ["PadB", " VK::PadB ", "0x5801", "22529"] {
+        assert_eq!(Ok(VK::PadB), s.parse(), "{s:?}");
+    }
+    for s in ["", "padb", "PadA|PadB", "0x10000", "DevSubType::PadB"] {
+        assert!(s.parse::<VK>().is_err(), "{s:?}");
+    }
+    assert_eq!(Ok(crate::DevSubType::Wheel), "Wheel".parse());
+    assert_eq!(Ok(crate::Caps::Wireless | crate::Caps::VoiceSupported), "VoiceSupported|Wireless".parse());
+}
+
+
+
 //#cpp2rust VK_PAD_A                    = xinput::VK::PadA
 //#cpp2rust VK_PAD_B                    = xinput::VK::PadB
 //#cpp2rust VK_PAD_X                    = xinput::VK::PadX
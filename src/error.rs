@@ -1,5 +1,6 @@
 //! [`Error`], [`Kind`].  Error codes are *rarely* `HRESULT`s (`XInputUap.dll` can return [`CO_E_NOTINITIALIZED`].)<br>
-//! [`BAD_ARGUMENTS`], [`BUFFER_TOO_SMALL`], [`DEVICE_NOT_CONNECTED`], [`INVALID_FUNCTION`]
+//! [`BAD_ARGUMENTS`], [`BUFFER_TOO_SMALL`], [`DEVICE_NOT_CONNECTED`], [`INVALID_FUNCTION`]<br>
+//! [`ParseError`]
 
 #[cfg(doc)] use crate::*;
 use crate::error_macros::FnContext;
@@ -37,6 +38,23 @@ impl<O, E: PartialEq<Error>> PartialEq<Result<O, E>> for Error { fn eq(&self, ot
 
 
 
+/// An error parsing flags (e.g. [`Buttons`]) or an enum-like type (e.g. [`VK`]) with [`str::parse`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    type_name:  &'static str,
+    input:      String,
+}
+
+impl ParseError {
+    pub(crate) fn new(type_name: &'static str, input: &str) -> Self { Self { type_name, input: input.into() } }
+}
+
+impl Display for ParseError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "invalid {}: {:?}", self.type_name, self.input) } }
+
+impl std::error::Error for ParseError {}
+
+
+
 /// ≈ <code>[winresult]::[ErrorCode]</code> corresponding to <code>[winresult]::[ERROR]::*</code>, but 32-bit, and oriented around XInput error codes.<br>
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)] pub struct Kind(u32);
@@ -0,0 +1,313 @@
+//! Smoothing filters for jittery thumbsticks and triggers.
+//!
+//! All filters are timestamp aware, so they behave consistently even if [`get_state`] is polled at irregular intervals.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use std::time::Instant;
+//!
+//! let mut smooth = filter::GamepadFilter::new(filter::OneEuro::default(), filter::Passthrough);
+//! # for _ in 0 .. 1 {
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! let gamepad = smooth.filter(&state, Instant::now());
+//! # }
+//! ```
+
+#[cfg(doc)] use crate::*;
+use crate::{Axis, Gamepad};
+
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+
+
+/// A filter over a stream of timestamped scalar values.
+pub trait Filter {
+    /// Filter the next `value`, sampled at `timestamp`.
+    fn filter(&mut self, value: f32, timestamp: Instant) -> f32;
+
+    /// Forget all previous samples.
+    fn reset(&mut self);
+}
+
+
+
+/// A filter that doesn't filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Passthrough;
+
+impl Filter for Passthrough {
+    fn filter(&mut self, value: f32, _timestamp: Instant) -> f32 { value }
+    fn reset(&mut self) {}
+}
+
+
+
+/// An [exponential moving average](https://en.wikipedia.org/wiki/Exponential_smoothing), with a time constant instead of a fixed per-sample weight.
+///
+/// Cheap and effective at removing noise, at the cost of a constant lag of roughly [`time_constant`](Self::time_constant).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ema {
+    /// Time for the output to move ~63% of the way to a new constant input.
+    pub time_constant:  Duration,
+
+    prev:               Option<(f32, Instant)>,
+}
+
+impl Ema {
+    /// Create a new filter with the given time constant.
+    pub fn new(time_constant: Duration) -> Self { Self { time_constant, prev: None } }
+}
+
+impl Default for Ema { fn default() -> Self { Self::new(Duration::from_millis(30)) } }
+
+impl Filter for Ema {
+    fn filter(&mut self, value: f32, timestamp: Instant) -> f32 {
+        let out = match self.prev {
+            None => value,
+            Some((prev, prev_time)) => {
+                let dt  = timestamp.saturating_duration_since(prev_time).as_secs_f32();
+                let tau = self.time_constant.as_secs_f32();
+                let alpha = if tau <= 0.0 { 1.0 } else { 1.0 - (-dt / tau).exp() };
+                prev + alpha * (value - prev)
+            },
+        };
+        self.prev = Some((out, timestamp));
+        out
+    }
+
+    fn reset(&mut self) { self.prev = None }
+}
+
+
+
+/// The median of recent samples.  Excellent at rejecting single-sample spikes, at the cost of some lag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Median {
+    /// The maximum number of samples to take the median of.
+    pub samples:    usize,
+
+    /// Ignore samples older than this (so a slow poll rate doesn't result in ancient samples dominating.)
+    pub max_age:    Duration,
+
+    window:         VecDeque<(f32, Instant)>,
+}
+
+impl Median {
+    /// Create a new filter taking the median of up to `samples` samples no older than `max_age`.
+    pub fn new(samples: usize, max_age: Duration) -> Self { Self { samples, max_age, window: VecDeque::new() } }
+}
+
+impl Default for Median { fn default() -> Self { Self::new(5, Duration::from_millis(100)) } }
+
+impl Filter for Median {
+    fn filter(&mut self, value: f32, timestamp: Instant) -> f32 {
+        self.window.push_back((value, timestamp));
+        while self.window.len() > self.samples.max(1) { self.window.pop_front(); }
+        while let Some(&(_, t)) = self.window.front() {
+            if timestamp.saturating_duration_since(t) <= self.max_age { break }
+            self.window.pop_front();
+        }
+
+        let mut sorted = self.window.iter().map(|&(v, _)| v).collect::<Vec<_>>();
+        sorted.sort_by(f32::total_cmp);
+        let mid = sorted.len() / 2;
+        if sorted.len() % 2 == 1 { sorted[mid] } else { (sorted[mid-1] + sorted[mid]) / 2.0 }
+    }
+
+    fn reset(&mut self) { self.window.clear() }
+}
+
+
+
+/// The [1€ filter](https://gery.casiez.net/1euro/): an adaptive low-pass filter with little lag during fast motion, and strong smoothing when still.
+///
+/// Tuning: set [`beta`](Self::beta) to `0`, lower [`min_cutoff`](Self::min_cutoff) until jitter at rest is acceptable,
+/// then raise [`beta`](Self::beta) until lag during fast motion is acceptable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OneEuro {
+    /// Minimum cutoff frequency, in Hz.  Lower values remove more jitter at rest.
+    pub min_cutoff:         f32,
+
+    /// Cutoff slope.  Higher values reduce lag during fast motion.
+    pub beta:               f32,
+
+    /// Cutoff frequency for the derivative estimate, in Hz.
+    pub derivative_cutoff:  f32,
+
+    prev:                   Option<(f32, f32, Instant)>, // (value, derivative, timestamp)
+}
+
+impl OneEuro {
+    /// Create a new filter.
+    pub fn new(min_cutoff: f32, beta: f32, derivative_cutoff: f32) -> Self { Self { min_cutoff, beta, derivative_cutoff, prev: None } }
+
+    fn alpha(cutoff: f32, dt: f32) -> f32 {
+        let tau = 1.0 / (TAU * cutoff);
+        1.0 / (1.0 + tau / dt)
+    }
+}
+
+impl Default for OneEuro { fn default() -> Self { Self::new(1.0, 10.0, 1.0) } }
+
+impl Filter for OneEuro {
+    fn filter(&mut self, value: f32, timestamp: Instant) -> f32 {
+        let (out, dx) = match self.prev {
+            None => (value, 0.0),
+            Some((prev, prev_dx, prev_time)) => {
+                let dt = timestamp.saturating_duration_since(prev_time).as_secs_f32();
+                if dt <= 0.0 { return prev }
+                let dx      = prev_dx + Self::alpha(self.derivative_cutoff, dt) * ((value - prev) / dt - prev_dx);
+                let cutoff  = self.min_cutoff + self.beta * dx.abs();
+                (prev + Self::alpha(cutoff, dt) * (value - prev), dx)
+            },
+        };
+        self.prev = Some((out, dx, timestamp));
+        out
+    }
+
+    fn reset(&mut self) { self.prev = None }
+}
+
+
+
+/// Applies independent filters to each thumbstick axis and trigger of successive [`Gamepad`]s.
+///
+/// [`Gamepad::buttons`] pass through unmodified.
+#[derive(Clone, Debug)]
+pub struct GamepadFilter<S: Filter, T: Filter = S> {
+    sticks:     [S; 4],
+    triggers:   [T; 2],
+}
+
+impl<S: Filter + Clone, T: Filter + Clone> GamepadFilter<S, T> {
+    /// Use copies of `stick` for each thumbstick axis, and copies of `trigger` for each trigger.
+    pub fn new(stick: S, trigger: T) -> Self {
+        Self {
+            sticks:     [stick.clone(), stick.clone(), stick.clone(), stick],
+            triggers:   [trigger.clone(), trigger],
+        }
+    }
+}
+
+impl<S: Filter, T: Filter> GamepadFilter<S, T> {
+    const STICK_AXES    : [Axis; 4] = [Axis::LeftThumbX, Axis::LeftThumbY, Axis::RightThumbX, Axis::RightThumbY];
+    const TRIGGER_AXES  : [Axis; 2] = [Axis::LeftTrigger, Axis::RightTrigger];
+
+    /// Filter the next `gamepad` state, sampled at `timestamp`.
+    ///
+    /// Axes are read with [`Axis::read`] and written back with [`Axis::write`], which round trip every raw value (so a [`Passthrough`] filter is the identity.)
+    pub fn filter(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Gamepad {
+        let mut out = *gamepad;
+        for (axis, f) in Self::STICK_AXES  .iter().zip(self.sticks  .iter_mut()) { filter_axis(*axis, f, &mut out, timestamp) }
+        for (axis, f) in Self::TRIGGER_AXES.iter().zip(self.triggers.iter_mut()) { filter_axis(*axis, f, &mut out, timestamp) }
+        out
+    }
+
+    /// Forget all previous samples (e.g. after the controller reconnects.)
+    pub fn reset(&mut self) {
+        self.sticks  .iter_mut().for_each(Filter::reset);
+        self.triggers.iter_mut().for_each(Filter::reset);
+    }
+}
+
+fn filter_axis(axis: Axis, f: &mut impl Filter, gamepad: &mut Gamepad, timestamp: Instant) {
+    let filtered = f.filter(axis.read(gamepad), timestamp);
+    axis.write(gamepad, filtered);
+}
+
+
+
+#[test] fn test_ema_irregular_timing() {
+    let t0 = Instant::now();
+    let ms = |ms| t0 + Duration::from_millis(ms);
+
+    // One step of 20ms should match two steps of 10ms
+    let mut a = Ema::new(Duration::from_millis(30));
+    let mut b = a;
+    a.filter(0.0, ms(0));
+    b.filter(0.0, ms(0));
+    let a = a.filter(1.0, ms(20));
+    b.filter(1.0, ms(10));
+    let b = b.filter(1.0, ms(20));
+    assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    assert!(a > 0.0 && a < 1.0);
+
+    let mut ema = Ema::new(Duration::from_millis(30));
+    assert_eq!(ema.filter(0.5, ms(0)), 0.5, "first sample passes through");
+    assert_eq!(ema.filter(1.0, ms(0)), 0.5, "no time elapsed");
+    assert!(ema.filter(1.0, ms(10_000)) > 0.999);
+}
+
+#[test] fn test_median() {
+    let t0 = Instant::now();
+    let ms = |ms| t0 + Duration::from_millis(ms);
+    let mut m = Median::new(3, Duration::from_millis(100));
+    assert_eq!(m.filter(0.0, ms(0)),  0.0);
+    assert_eq!(m.filter(0.0, ms(1)),  0.0);
+    assert_eq!(m.filter(1.0, ms(2)),  0.0, "spike rejected");
+    assert_eq!(m.filter(0.2, ms(3)),  0.2);
+    assert_eq!(m.filter(0.8, ms(500)), 0.8, "old samples expired");
+    m.reset();
+    assert_eq!(m.filter(0.4, ms(501)), 0.4);
+    assert_eq!(m.filter(0.6, ms(502)), 0.5);
+}
+
+#[test] fn test_one_euro() {
+    let t0 = Instant::now();
+    let ms = |ms| t0 + Duration::from_millis(ms);
+
+    // jitter at rest is strongly attenuated
+    let mut f = OneEuro::new(1.0, 0.0, 1.0);
+    let mut max = 0f32;
+    for i in 0 .. 100 { max = max.max(f.filter(if i % 2 == 0 { 0.01 } else { -0.01 }, ms(i * 8)).abs()); }
+    assert!(max <= 0.01);
+    let last = f.filter(0.01, ms(800));
+    assert!(last.abs() < 0.005, "{last}");
+
+    // fast motion is tracked quickly with beta
+    let mut slow = OneEuro::new(1.0, 0.0, 1.0);
+    let mut fast = OneEuro::new(1.0, 10.0, 1.0);
+    for i in 0 ..= 10 {
+        let v = i as f32 / 10.0;
+        slow.filter(v, ms(i * 8));
+        fast.filter(v, ms(i * 8));
+    }
+    let (slow, fast) = (slow.filter(1.0, ms(88)), fast.filter(1.0, ms(88)));
+    assert!(fast > slow, "{fast} <= {slow}");
+}
+
+#[test] fn test_gamepad_filter() {
+    let t0 = Instant::now();
+    let mut f = GamepadFilter::new(Ema::new(Duration::from_millis(30)), Passthrough);
+    let zero = Gamepad::default();
+    let full = Gamepad { buttons: crate::Buttons::A, left_trigger: 200, left_thumb_x: 32767, right_thumb_y: -32768, .. zero };
+    assert_eq!(f.filter(&zero, t0).left_thumb_x, 0);
+    let out = f.filter(&full, t0 + Duration::from_millis(10));
+    assert_eq!(out.buttons, crate::Buttons::A);
+    assert_eq!(out.left_trigger, 200, "triggers pass through");
+    assert!(out.left_thumb_x > 0 && out.left_thumb_x < 32767);
+    assert!(out.right_thumb_y < 0 && out.right_thumb_y > -32767);
+    f.reset();
+    assert_eq!(f.filter(&full, t0 + Duration::from_millis(20)).left_thumb_x, 32767);
+}
+
+#[test] fn test_gamepad_filter_passthrough_is_identity() {
+    let t0 = Instant::now();
+    let mut f = GamepadFilter::new(Passthrough, Passthrough);
+    for (i, x) in [i16::MIN, -32767, -1, 0, 1, i16::MAX].into_iter().enumerate() {
+        let gamepad = Gamepad { left_thumb_x: x, left_thumb_y: x, right_thumb_x: x, right_thumb_y: x, left_trigger: i as u8 * 51, right_trigger: 255, .. Gamepad::default() };
+        let out = f.filter(&gamepad, t0 + Duration::from_millis(i as u64));
+        assert_eq!(bytemuck::bytes_of(&gamepad), bytemuck::bytes_of(&out), "{x}");
+    }
+
+    // filtered values that move still reach -32768
+    let mut f = GamepadFilter::new(Ema::new(Duration::from_millis(10)), Passthrough);
+    let min = Gamepad { left_thumb_x: i16::MIN, .. Gamepad::default() };
+    f.filter(&Gamepad::default(), t0);
+    let x = f.filter(&min, t0 + Duration::from_millis(10)).left_thumb_x;
+    assert!(i16::MIN < x && x < 0, "{x}");
+    assert_eq!(i16::MIN, f.filter(&min, t0 + Duration::from_secs(10)).left_thumb_x);
+}
@@ -177,6 +177,28 @@ impl Buttons {
 
 
 
+#[test] fn test_display_from_str() {
+    let ab = Buttons::A | Buttons::B;
+    let unnamed = Buttons(0x0800) | Buttons::Y;
+    assert_eq!("A|B",       ab.to_string());
+    assert_eq!("Y|0x800",   unnamed.to_string());
+    assert_eq!("None",      Buttons::None.to_string());
+
+    for buttons in [ab, unnamed, Buttons::None, Buttons::Guide, Buttons(0x0800), Buttons(0xFFFF)] {
+        assert_eq!(Ok(buttons), buttons.to_string().parse(), "Display {buttons}");
+        assert_eq!(Ok(buttons), format!("{buttons:?}").parse(), "Debug {buttons:?}");
+    }
+    for s in ["A|B", " B | A ", "Buttons::A | Buttons::B", "Buttons::{A|B}", "0x3000", "A|0x2000", "12288"] {
+        assert_eq!(Ok(ab), s.parse(), "{s:?}");
+    }
+    for s in ["", "a", "A|", "A||B", "A+B", "Caps::A", "0x10000", "0xZ", "-1"] {
+        assert_eq!(Err(crate::error::ParseError::new("Buttons", s)), s.parse::<Buttons>(), "{s:?}");
+    }
+    assert_eq!("invalid Buttons: \"A+B\"", "A+B".parse::<Buttons>().unwrap_err().to_string());
+}
+
+
+
 //#cpp2rust XINPUT_GAMEPAD_DPAD_UP          = xinput::Buttons::DPadUp
 //#cpp2rust XINPUT_GAMEPAD_DPAD_DOWN        = xinput::Buttons::DPadDown
 //#cpp2rust XINPUT_GAMEPAD_DPAD_LEFT        = xinput::Buttons::DPadLeft
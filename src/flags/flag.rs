@@ -22,4 +22,23 @@ flags! { Flag => u32; None, Gamepad }
     pub const Gamepad : Flag = Flag(XINPUT_FLAG_GAMEPAD);
 }
 
+
+
+#[test] fn test_display_from_str() {
+    assert_eq!("Gamepad",   Flag::Gamepad.to_string());
+    assert_eq!("None",      Flag::None.to_string());
+    for flag in [Flag::None, Flag::Gamepad, Flag(0x8000_0000) | Flag::Gamepad] {
+        assert_eq!(Ok(flag), flag.to_string().parse(), "Display {flag}");
+        assert_eq!(Ok(flag), format!("{flag:?}").parse(), "Debug {flag:?}");
+    }
+    for s in ["Gamepad", "Flag::Gamepad", "0x1", "1"] {
+        assert_eq!(Ok(Flag::Gamepad), s.parse(), "{s:?}");
+    }
+    for s in ["", "gamepad", "Caps::Gamepad", "0x100000000"] {
+        assert!(s.parse::<Flag>().is_err(), "{s:?}");
+    }
+}
+
+
+
 //#cpp2rust XINPUT_FLAG_GAMEPAD     = xinput::Flag::Gamepad
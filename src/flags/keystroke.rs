@@ -10,7 +10,7 @@ use winapi::um::xinput::*;
 #[repr(transparent)] pub struct KeystrokeFlags(u16);
 use KeystrokeFlags as Keystroke;
 
-flags! { Keystroke => u16; None, KeyDown, KeyUp, Repeat }
+flags! { Keystroke => u16; type_name: "KeystrokeFlags"; None, KeyDown, KeyUp, Repeat }
 
 #[allow(non_upper_case_globals)] impl KeystrokeFlags {
     /// No flags set
@@ -40,6 +40,25 @@ flags! { Keystroke => u16; None, KeyDown, KeyUp, Repeat }
     pub const Repeat : Keystroke = Keystroke(XINPUT_KEYSTROKE_REPEAT);
 }
 
+
+
+#[test] fn test_display_from_str() {
+    let down_repeat = KeystrokeFlags::KeyDown | KeystrokeFlags::Repeat;
+    assert_eq!("KeyDown|Repeat", down_repeat.to_string());
+    for flags in [down_repeat, KeystrokeFlags::None, KeystrokeFlags::KeyUp, KeystrokeFlags(0x8000) | KeystrokeFlags::KeyUp] {
+        assert_eq!(Ok(flags), flags.to_string().parse(), "Display {flags}");
+        assert_eq!(Ok(flags), format!("{flags:?}").parse(), "Debug {flags:?}");
+    }
+    for s in ["KeyDown|Repeat", "KeystrokeFlags::KeyDown | KeystrokeFlags::Repeat", "KeystrokeFlags::{KeyDown|Repeat}", "Keystroke::KeyDown | Keystroke::Repeat", "0x5", "KeyDown|0x4"] {
+        assert_eq!(Ok(down_repeat), s.parse(), "{s:?}");
+    }
+    for s in ["", "keydown", "KeyDown+Repeat", "KeystrokeFlagsKeyDown", "Buttons::KeyDown", "0x10000"] {
+        assert_eq!(Err(crate::error::ParseError::new("KeystrokeFlags", s)), s.parse::<KeystrokeFlags>(), "{s:?}");
+    }
+}
+
+
+
 //#cpp2rust XINPUT_KEYSTROKE_KEYDOWN    = xinput::Keystroke::KeyDown
 //#cpp2rust XINPUT_KEYSTROKE_KEYUP      = xinput::Keystroke::KeyUp
 //#cpp2rust XINPUT_KEYSTROKE_REPEAT     = xinput::Keystroke::Repeat
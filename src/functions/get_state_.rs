@@ -8,6 +8,7 @@ use bytemuck::Zeroable;
 /// XInputGetState
 ///
 /// Retrieves the current state of the specified controller.
+/// Registered [`backend`]s are consulted before XInput.
 ///
 /// ### Arguments
 /// *   `user_index`    &mdash; The controller to get the state of (<code>0 .. [xuser::MAX_COUNT]</code>.)
@@ -41,8 +42,15 @@ use bytemuck::Zeroable;
 /// *   [error::INVALID_FUNCTION]       - API unavailable: XInput not loaded
 pub fn get_state(user_index: impl TryInto<u32>) -> Result<State, Error> {
     fn_context!(xinput::get_state => XInputGetState);
-    #[allow(non_snake_case)] let XInputGetState = imports::XInputGetState.load(core::sync::atomic::Ordering::Relaxed);
     let user_index = user_index.try_into().map_err(|_| fn_param_error!(user_index, error::BAD_ARGUMENTS))?;
+    if let Some(state) = backend::state(user_index) { return Ok(state) }
+    xinput_get_state(user_index)
+}
+
+/// [`get_state`], bypassing registered [`backend`]s (e.g. to forward only local controllers.)
+pub(crate) fn xinput_get_state(user_index: u32) -> Result<State, Error> {
+    fn_context!(xinput::get_state => XInputGetState);
+    #[allow(non_snake_case)] let XInputGetState = imports::XInputGetState.load(core::sync::atomic::Ordering::Relaxed);
 
     let mut state = State::zeroed();
     // SAFETY: ✔️
@@ -56,6 +64,7 @@ pub fn get_state(user_index: impl TryInto<u32>) -> Result<State, Error> {
 }
 
 #[test] fn test_valid_params() {
+    let _lock = backend::test_lock();
     for user_index in 0 .. 4 {
         if let Err(err) = get_state(user_index) {
             assert!(matches!(err.kind(), error::DEVICE_NOT_CONNECTED | error::CO_E_NOTINITIALIZED), "unexpected error type: {err:?}");
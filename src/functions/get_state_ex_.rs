@@ -5,6 +5,7 @@
 /// ⚠️ **NOTE** ⚠️ This undocumented function is reserved for system software to access [Buttons::Guide].
 ///
 /// Silently falls back on [`XInputGetState`](https://learn.microsoft.com/en-us/windows/win32/api/xinput/nf-xinput-xinputgetstate) if `XInputGetStateEx` is unavailable.
+/// Registered [`backend`]s are consulted before XInput.
 ///
 /// ### Arguments
 /// *   `user_index`    &mdash; The controller to get the state of (<code>0 .. [xuser::MAX_COUNT]</code>.)
@@ -44,6 +45,7 @@ pub fn get_state_ex(user_index: impl TryInto<u32>) -> Result<State, Error> {
     fn_context!(xinput::get_state_ex => XInputGetStateEx);
     #[allow(non_snake_case)] let XInputGetStateEx = imports::_XInputGetStateEx.load(core::sync::atomic::Ordering::Relaxed);
     let user_index = user_index.try_into().map_err(|_| fn_param_error!(user_index, error::BAD_ARGUMENTS))?;
+    if let Some(state) = backend::state(user_index) { return Ok(state) }
 
     let mut state = State::default();
     // SAFETY: ✔️
@@ -61,6 +63,7 @@ pub fn get_state_ex(user_index: impl TryInto<u32>) -> Result<State, Error> {
     use super::*;
 
     #[test] fn valid_params() {
+        let _lock = backend::test_lock();
         for user_index in 0 .. 4 {
             if let Err(err) = get_state_ex(user_index) {
                 assert!(matches!(err.kind(), error::DEVICE_NOT_CONNECTED | error::CO_E_NOTINITIALIZED), "unexpected error type: {err:?}");
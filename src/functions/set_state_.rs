@@ -6,6 +6,7 @@ use crate::*;
 /// XInputSetState
 ///
 /// Control the vibration of a controller.
+/// Registered [`backend`]s are consulted before XInput.
 ///
 /// ### Arguments
 /// *   `user_index`    &mdash; The controller to vibrate (<code>0 .. [xuser::MAX_COUNT]</code>.)
@@ -55,9 +56,16 @@ use crate::*;
 ///     *   Third party middleware such as [WWise's Motion plugin](https://www.youtube.com/watch?v=I-2aR7McfKw) may think it should control vibration via your sound editor.
 pub fn set_state(user_index: impl TryInto<u32>, vibration: impl Into<Vibration>) -> Result<(), Error> {
     fn_context!(xinput::set_state => XInputSetState);
-    #[allow(non_snake_case)] let XInputSetState = imports::XInputSetState.load(core::sync::atomic::Ordering::Relaxed);
     let user_index = user_index.try_into().map_err(|_| fn_param_error!(user_index, error::BAD_ARGUMENTS))?;
-    let mut vibration = vibration.into();
+    let vibration = vibration.into();
+    if backend::vibrate(user_index, vibration) { return Ok(()) }
+    xinput_set_state(user_index, vibration)
+}
+
+/// [`set_state`], bypassing registered [`backend`]s (e.g. to vibrate only local controllers.)
+pub(crate) fn xinput_set_state(user_index: u32, mut vibration: Vibration) -> Result<(), Error> {
+    fn_context!(xinput::set_state => XInputSetState);
+    #[allow(non_snake_case)] let XInputSetState = imports::XInputSetState.load(core::sync::atomic::Ordering::Relaxed);
 
     // SAFETY: ✔️
     //  * fuzzed        in `fuzz-xinput.rs`
@@ -69,6 +77,7 @@ pub fn set_state(user_index: impl TryInto<u32>, vibration: impl Into<Vibration>)
 }
 
 #[test] fn test_valid_params() {
+    let _lock = backend::test_lock();
     let v = Vibration::default();
     for user_index in 0 .. 4 {
         if let Err(err) = set_state(user_index, v) {
@@ -0,0 +1,523 @@
+//! Recognize button chords, taps, double taps, holds and long presses ([`Recognizer`]),
+//! and thumbstick flicks, rotations and shakes ([`StickRecognizer`]), from successive [`Gamepad`] states.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::gestures::*;
+//! use std::time::Instant;
+//!
+//! let mut gestures = Recognizer::new(Timings::default());
+//! gestures.add_chord(Buttons::LeftShoulder | Buttons::RightShoulder);
+//!
+//! # for _ in 0 .. 1 {
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! for event in gestures.update(&state, Instant::now()) {
+//!     match event.gesture {
+//!         Gesture::Chord(_)               => println!("open accessibility menu"),
+//!         Gesture::Tap(Buttons::A)        => println!("select"),
+//!         Gesture::LongPress(Buttons::A)  => println!("show details"),
+//!         _                               => {},
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::*;
+
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
+use std::time::{Duration, Instant};
+
+
+
+/// Durations used to distinguish gestures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timings {
+    /// Presses shorter than this are taps, presses at least this long are holds.
+    pub tap:        Duration,
+
+    /// A second tap starting within this long of the first tap's release is a double tap.
+    /// [`Gesture::Tap`] is delayed by this long, to rule out a double tap.  Set to [`Duration::ZERO`] to disable double taps.
+    pub double_tap: Duration,
+
+    /// Presses held at least this long are long presses.
+    pub long_press: Duration,
+
+    /// All buttons of a chord must be pressed within this long of each other.
+    pub chord:      Duration,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            tap:        Duration::from_millis(200),
+            double_tap: Duration::from_millis(250),
+            long_press: Duration::from_millis(800),
+            chord:      Duration::from_millis(60),
+        }
+    }
+}
+
+/// A recognized gesture.  Every variant except [`Gesture::Chord`] refers to a single button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Gesture {
+    /// All of these buttons were pressed within [`Timings::chord`] of each other.
+    /// Buttons that are part of a recognized chord don't produce other gestures until released.
+    Chord(Buttons),
+
+    /// Pressed and released within [`Timings::tap`], and not followed by a double tap.
+    Tap(Buttons),
+
+    /// Tapped, then pressed again within [`Timings::double_tap`].
+    DoubleTap(Buttons),
+
+    /// Held for [`Timings::tap`] (i.e. this press won't be a tap.)
+    Hold(Buttons),
+
+    /// Held for [`Timings::long_press`].
+    LongPress(Buttons),
+}
+
+/// A [`Gesture`], and when it happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Event {
+    /// The recognized gesture.
+    pub gesture:    Gesture,
+
+    /// When the gesture completed.  This may be earlier than the [`Recognizer::update`] that reported it:
+    /// e.g. [`Gesture::Tap`]s are reported [`Timings::double_tap`] late, but timestamped with the release of the button.
+    pub timestamp:  Instant,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ButtonState {
+    pressed_at:     Option<Instant>,
+    pending_tap:    Option<Instant>,
+    double:         bool,
+    hold_sent:      bool,
+    long_sent:      bool,
+    chorded:        bool,
+}
+
+/// Recognizes [`Gesture`]s from successive [`Gamepad`] states.
+#[derive(Clone, Debug)]
+pub struct Recognizer {
+    /// Durations used to distinguish gestures.
+    pub timings:    Timings,
+
+    chords:         Vec<Buttons>,
+    buttons:        Buttons,
+    state:          [ButtonState; 16],
+}
+
+impl Recognizer {
+    /// Create a recognizer without any chords.
+    pub fn new(timings: Timings) -> Self { Self { timings, chords: Vec::new(), buttons: Buttons::None, state: Default::default() } }
+
+    /// Recognize `buttons` (two or more) as a chord.
+    pub fn add_chord(&mut self, buttons: Buttons) { self.chords.push(buttons) }
+
+    /// Feed the next `gamepad` state, sampled at `timestamp`, returning any gestures recognized, in chronological order.
+    pub fn update(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Vec<Event> {
+        let t = &self.timings;
+        let mut events = Vec::new();
+        let now  = gamepad.buttons.into_inner();
+        let prev = self.buttons.into_inner();
+        let (pressed, released) = (now & !prev, prev & !now);
+        self.buttons = gamepad.buttons;
+
+        for (bit, s) in self.state.iter_mut().enumerate() {
+            let button = Buttons::from_unchecked(1 << bit);
+            let mask = 1u16 << bit;
+
+            if let Some(release) = s.pending_tap {
+                let second_tap = pressed & mask != 0 && timestamp.saturating_duration_since(release) <= t.double_tap;
+                if second_tap || timestamp.saturating_duration_since(release) > t.double_tap {
+                    s.pending_tap = None;
+                    if !second_tap { events.push(Event { gesture: Gesture::Tap(button), timestamp: release }) }
+                }
+                if second_tap {
+                    s.double = true;
+                    events.push(Event { gesture: Gesture::DoubleTap(button), timestamp });
+                }
+            }
+
+            if pressed & mask != 0 {
+                *s = ButtonState { pressed_at: Some(timestamp), double: s.double, .. ButtonState::default() };
+            }
+        }
+
+        for &chord in self.chords.iter() {
+            let chord_bits = chord.into_inner();
+            if chord_bits == 0 || chord_bits & pressed == 0 || !gamepad.buttons.all_held(chord) { continue }
+            let times = (0 .. 16).filter(|bit| chord_bits & (1 << bit) != 0).filter_map(|bit| self.state[bit].pressed_at);
+            let (first, last) = times.fold((timestamp, timestamp), |(lo, hi), t| (lo.min(t), hi.max(t)));
+            if last.saturating_duration_since(first) > t.chord { continue }
+            for bit in (0 .. 16).filter(|bit| chord_bits & (1 << bit) != 0) { self.state[bit].chorded = true }
+            events.push(Event { gesture: Gesture::Chord(chord), timestamp });
+        }
+
+        for (bit, s) in self.state.iter_mut().enumerate() {
+            let button = Buttons::from_unchecked(1 << bit);
+            let mask = 1u16 << bit;
+            let Some(pressed_at) = s.pressed_at else { continue };
+
+            if now & mask != 0 && !s.chorded {
+                let held = timestamp.saturating_duration_since(pressed_at);
+                if held >= t.tap && !s.hold_sent {
+                    s.hold_sent = true;
+                    events.push(Event { gesture: Gesture::Hold(button), timestamp: pressed_at + t.tap });
+                }
+                if held >= t.long_press && !s.long_sent {
+                    s.long_sent = true;
+                    events.push(Event { gesture: Gesture::LongPress(button), timestamp: pressed_at + t.long_press });
+                }
+            }
+
+            if released & mask != 0 {
+                let tap = !s.chorded && !s.double && !s.hold_sent && timestamp.saturating_duration_since(pressed_at) < t.tap;
+                if tap && t.double_tap.is_zero() {
+                    events.push(Event { gesture: Gesture::Tap(button), timestamp });
+                } else if tap {
+                    s.pending_tap = Some(timestamp);
+                }
+                *s = ButtonState { pending_tap: s.pending_tap, .. ButtonState::default() };
+            }
+        }
+
+        events.sort_by_key(|e| e.timestamp);
+        events
+    }
+
+    /// Forget all held buttons and pending taps.
+    pub fn reset(&mut self) {
+        self.buttons = Buttons::None;
+        self.state = Default::default();
+    }
+}
+
+
+
+/// Thresholds used to recognize [`StickGesture`]s.  Magnitudes are normalized such that `32767` = `1.0` (see [`Stick`].)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StickThresholds {
+    /// The stick is considered centered below this magnitude.
+    pub deadzone:           f32,
+
+    /// A flick must reach this magnitude...
+    pub flick_magnitude:    f32,
+
+    /// ...within this long of leaving the [`deadzone`](Self::deadzone).
+    pub flick_time:         Duration,
+
+    /// Rotation is only tracked at or above this magnitude.  Returning to the [`deadzone`](Self::deadzone) resets rotation.
+    pub rotation_magnitude: f32,
+
+    /// Each swing of a shake must reach this magnitude, and swing back through half of it.
+    pub shake_magnitude:    f32,
+
+    /// Number of swings (direction reversals) that make a shake...
+    pub shake_swings:       u32,
+
+    /// ...all within this long.
+    pub shake_time:         Duration,
+}
+
+impl Default for StickThresholds {
+    fn default() -> Self {
+        Self {
+            deadzone:           0.25,
+            flick_magnitude:    0.9,
+            flick_time:         Duration::from_millis(100),
+            rotation_magnitude: 0.5,
+            shake_magnitude:    0.6,
+            shake_swings:       4,
+            shake_time:         Duration::from_millis(1000),
+        }
+    }
+}
+
+/// A recognized thumbstick gesture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StickGesture {
+    /// The stick was pushed from center to [`StickThresholds::flick_magnitude`] within [`StickThresholds::flick_time`].
+    Flick {
+        /// The (8-way) direction of the flick.
+        direction:  Direction8,
+
+        /// The angle of the flick, in radians, counterclockwise from right.
+        angle:      f32,
+
+        /// Average speed leaving the deadzone, in normalized magnitude per second.
+        speed:      f32,
+    },
+
+    /// The stick completed a full turn while pushed at least [`StickThresholds::rotation_magnitude`].
+    Rotation {
+        /// `true` if clockwise.
+        clockwise:  bool,
+
+        /// Number of consecutive full turns in this direction, including this one.
+        count:      u32,
+    },
+
+    /// The stick swung back and forth [`StickThresholds::shake_swings`] times within [`StickThresholds::shake_time`].
+    Shake,
+}
+
+/// A [`StickGesture`], and when it happened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StickEvent {
+    /// The stick that made the gesture.
+    pub stick:      ThumbStick,
+
+    /// The recognized gesture.
+    pub gesture:    StickGesture,
+
+    /// When the gesture completed.
+    pub timestamp:  Instant,
+}
+
+/// Recognizes [`StickGesture`]s of a single thumbstick from successive, timestamped [`Gamepad`] states.
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// use xinput::gestures::*;
+/// use std::time::Instant;
+///
+/// let mut right = StickRecognizer::new(ThumbStick::Right, StickThresholds::default());
+///
+/// # for _ in 0 .. 1 {
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// for event in right.update(&state, Instant::now()) {
+///     match event.gesture {
+///         StickGesture::Flick { direction, .. }   => println!("select weapon {}", direction.numpad()),
+///         StickGesture::Shake                     => println!("struggle!"),
+///         _                                       => {},
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct StickRecognizer {
+    /// The stick being recognized.
+    pub stick:          ThumbStick,
+
+    /// Thresholds used to recognize gestures.
+    pub thresholds:     StickThresholds,
+
+    prev:               Option<(Stick, Instant)>,
+    left_center:        Option<(Instant, f32)>,
+    turn:               f32,
+    turns:              (bool, u32),
+    swing:              Option<f32>,
+    swing_armed:        bool,
+    swings:             VecDeque<Instant>,
+}
+
+impl StickRecognizer {
+    /// Start recognizing gestures of `stick`.
+    pub fn new(stick: ThumbStick, thresholds: StickThresholds) -> Self {
+        Self { stick, thresholds, prev: None, left_center: None, turn: 0.0, turns: (false, 0), swing: None, swing_armed: true, swings: VecDeque::new() }
+    }
+
+    /// Progress of the current rotation in turns (positive = counterclockwise), for e.g. rotary dials.
+    /// Completed turns are subtracted as [`StickGesture::Rotation`] events are emitted, so this stays within `-1.0 ..= 1.0`.
+    pub fn rotation(&self) -> f32 { self.turn / TAU }
+
+    /// Feed the next `gamepad` state, sampled at `timestamp`, returning any gestures recognized.
+    pub fn update(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Vec<StickEvent> {
+        let t = &self.thresholds;
+        let mut gestures = Vec::new();
+        let stick = Stick::from_gamepad(gamepad, self.stick);
+        let Some((prev, prev_timestamp)) = self.prev.replace((stick, timestamp)) else { return Vec::new() };
+
+        // flicks
+        if stick.magnitude < t.deadzone {
+            self.left_center = None;
+        } else if prev.magnitude < t.deadzone {
+            self.left_center = Some((prev_timestamp, prev.magnitude));
+        }
+        if let Some((left, from)) = self.left_center {
+            let elapsed = timestamp.saturating_duration_since(left);
+            if stick.magnitude >= t.flick_magnitude && elapsed <= t.flick_time {
+                let (x, y) = stick.to_i16();
+                let direction = DirectionQuantizer { activation_radius: 0.0, release_radius: 0.0, hysteresis: 0.0 }.quantize8(Direction8::Neutral, x, y);
+                let speed = (stick.magnitude - from) / elapsed.as_secs_f32().max(1e-3);
+                gestures.push(StickGesture::Flick { direction, angle: stick.angle, speed });
+            }
+            if stick.magnitude >= t.flick_magnitude || elapsed > t.flick_time { self.left_center = None }
+        }
+
+        // rotations
+        if stick.magnitude < t.deadzone {
+            self.turn  = 0.0;
+            self.turns = (false, 0);
+        } else if stick.magnitude >= t.rotation_magnitude && prev.magnitude >= t.rotation_magnitude {
+            self.turn += (stick.angle - prev.angle + PI).rem_euclid(TAU) - PI;
+            while self.turn.abs() >= TAU {
+                let clockwise = self.turn < 0.0;
+                self.turn -= TAU.copysign(self.turn);
+                self.turns = (clockwise, if self.turns.0 == clockwise { self.turns.1 + 1 } else { 1 });
+                gestures.push(StickGesture::Rotation { clockwise, count: self.turns.1 });
+            }
+        }
+
+        // shakes
+        while self.swings.front().map_or(false, |&s| timestamp.saturating_duration_since(s) > t.shake_time) { self.swings.pop_front(); }
+        if stick.magnitude < t.shake_magnitude / 2.0 {
+            self.swing_armed = true;
+        } else if stick.magnitude >= t.shake_magnitude && self.swing_armed {
+            self.swing_armed = false;
+            let reversed = self.swing.map_or(false, |a| ((stick.angle - a + PI).rem_euclid(TAU) - PI).abs() > PI * 0.75);
+            self.swing = Some(stick.angle);
+            if reversed {
+                self.swings.push_back(timestamp);
+                if self.swings.len() >= t.shake_swings as usize {
+                    self.swings.clear();
+                    gestures.push(StickGesture::Shake);
+                }
+            }
+        }
+
+        gestures.into_iter().map(|gesture| StickEvent { stick: self.stick, gesture, timestamp }).collect()
+    }
+
+    /// Forget all previous samples.
+    pub fn reset(&mut self) { *self = Self::new(self.stick, self.thresholds) }
+}
+
+
+
+#[cfg(test)] struct Script { r: Recognizer, t0: Instant }
+#[cfg(test)] impl Script {
+    fn new() -> Self {
+        let mut r = Recognizer::new(Timings::default());
+        r.add_chord(Buttons::LeftShoulder | Buttons::RightShoulder);
+        Self { r, t0: Instant::now() }
+    }
+    fn at(&mut self, ms: u64, buttons: Buttons) -> Vec<(Gesture, u64)> {
+        let t0 = self.t0;
+        self.r.update(&Gamepad { buttons, .. Gamepad::default() }, t0 + Duration::from_millis(ms)).into_iter().map(|e| (e.gesture, e.timestamp.duration_since(t0).as_millis() as u64)).collect()
+    }
+}
+
+#[test] fn test_tap_vs_hold() {
+    let mut s = Script::new();
+    let none = Vec::<(Gesture, u64)>::new();
+    assert_eq!(none, s.at(0,   Buttons::A));
+    assert_eq!(none, s.at(100, Buttons::None));
+    assert_eq!(none, s.at(300, Buttons::None), "waiting to rule out double tap");
+    assert_eq!(vec![(Gesture::Tap(Buttons::A), 100)], s.at(400, Buttons::None));
+
+    assert_eq!(none, s.at(1000, Buttons::B));
+    assert_eq!(vec![(Gesture::Hold(Buttons::B), 1200)], s.at(1250, Buttons::B));
+    assert_eq!(vec![(Gesture::LongPress(Buttons::B), 1800)], s.at(1900, Buttons::B));
+    assert_eq!(none, s.at(2000, Buttons::None));
+    assert_eq!(none, s.at(3000, Buttons::None), "holds aren't taps");
+}
+
+#[test] fn test_double_tap() {
+    let mut s = Script::new();
+    let none = Vec::<(Gesture, u64)>::new();
+    s.at(0, Buttons::X);
+    s.at(50, Buttons::None);
+    assert_eq!(vec![(Gesture::DoubleTap(Buttons::X), 200)], s.at(200, Buttons::X));
+    assert_eq!(none, s.at(250, Buttons::None));
+    assert_eq!(none, s.at(1000, Buttons::None), "the second tap isn't a tap");
+
+    // too slow for a double tap
+    s.at(2000, Buttons::X);
+    s.at(2050, Buttons::None);
+    assert_eq!(vec![(Gesture::Tap(Buttons::X), 2050)], s.at(2400, Buttons::X));
+    s.at(2450, Buttons::None);
+    assert_eq!(vec![(Gesture::Tap(Buttons::X), 2450)], s.at(3000, Buttons::None));
+
+    // disabled
+    s.r.timings.double_tap = Duration::ZERO;
+    s.at(4000, Buttons::Y);
+    assert_eq!(vec![(Gesture::Tap(Buttons::Y), 4050)], s.at(4050, Buttons::None));
+}
+
+#[test] fn test_chord() {
+    let mut s = Script::new();
+    let none = Vec::<(Gesture, u64)>::new();
+    let chord = Buttons::LeftShoulder | Buttons::RightShoulder;
+    assert_eq!(none, s.at(0, Buttons::LeftShoulder));
+    assert_eq!(vec![(Gesture::Chord(chord), 40)], s.at(40, chord));
+    assert_eq!(none, s.at(2000, chord), "no hold or long press for chorded buttons");
+    assert_eq!(none, s.at(2050, Buttons::None));
+    assert_eq!(none, s.at(3000, Buttons::None));
+
+    // too slow for a chord
+    s.at(4000, Buttons::LeftShoulder);
+    let events = s.at(4150, chord);
+    assert!(!events.iter().any(|(g, _)| matches!(g, Gesture::Chord(_))), "{events:?}");
+    s.at(4180, Buttons::LeftShoulder);
+    assert_eq!(vec![(Gesture::Hold(Buttons::LeftShoulder), 4200)], s.at(4200, Buttons::LeftShoulder));
+    assert_eq!(vec![(Gesture::Tap(Buttons::RightShoulder), 4180)], s.at(4500, Buttons::LeftShoulder));
+
+    s.r.reset();
+    assert_eq!(none, s.at(5000, Buttons::None));
+}
+
+#[cfg(test)] fn stick_script(r: &mut StickRecognizer, samples: &[(u64, f32, f32)]) -> Vec<(StickGesture, u64)> {
+    let t0 = Instant::now();
+    let mut events = Vec::new();
+    for &(ms, magnitude, degrees) in samples {
+        let mut gamepad = Gamepad::default();
+        Stick { magnitude, angle: degrees.to_radians() }.write(&mut gamepad, r.stick);
+        events.extend(r.update(&gamepad, t0 + Duration::from_millis(ms)).into_iter().map(|e| (e.gesture, ms)));
+    }
+    events
+}
+
+#[test] fn test_flick() {
+    let mut r = StickRecognizer::new(ThumbStick::Right, StickThresholds::default());
+    let events = stick_script(&mut r, &[(0, 0.0, 0.0), (16, 0.5, 95.0), (33, 1.0, 95.0), (50, 1.0, 95.0), (66, 0.0, 0.0)]);
+    let [(StickGesture::Flick { direction, speed, .. }, 33)] = events[..] else { panic!("{events:?}") };
+    assert_eq!(Direction8::Up, direction);
+    assert!((29.0 .. 31.0).contains(&speed), "{speed}");
+
+    let events = stick_script(&mut r, &[(0, 0.0, 0.0), (100, 0.5, 0.0), (200, 0.8, 0.0), (300, 1.0, 0.0), (400, 0.0, 0.0)]);
+    assert_eq!(Vec::<(StickGesture, u64)>::new(), events, "too slow");
+
+    let events = stick_script(&mut r, &[(0, 0.0, 0.0), (16, 1.0, -135.0)]);
+    assert!(matches!(events[..], [(StickGesture::Flick { direction: Direction8::DownLeft, .. }, 16)]), "{events:?}");
+
+    // small flicks still get a direction from their angle
+    let mut r = StickRecognizer::new(ThumbStick::Left, StickThresholds { deadzone: 0.1, flick_magnitude: 0.3, .. StickThresholds::default() });
+    for (angle, expected) in [(45.0, Direction8::UpRight), (80.0, Direction8::Up), (-120.0, Direction8::DownLeft), (-110.0, Direction8::Down), (180.0, Direction8::Left)] {
+        let events = stick_script(&mut r, &[(0, 0.0, 0.0), (16, 0.35, angle), (32, 0.0, 0.0)]);
+        assert!(matches!(events[..], [(StickGesture::Flick { direction, .. }, 16)] if direction == expected), "{angle}: {events:?}");
+    }
+}
+
+#[test] fn test_rotation() {
+    let mut r = StickRecognizer::new(ThumbStick::Left, StickThresholds::default());
+    // which sample completes each turn depends on f32 rounding, so only check the turns themselves
+    let ccw = (0 ..= 76).map(|i| (i * 10, 1.0, i as f32 * 10.0)).collect::<Vec<_>>();
+    let events = stick_script(&mut r, &ccw);
+    let rotations = events.iter().filter(|e| matches!(e.0, StickGesture::Rotation { .. })).map(|e| e.0).collect::<Vec<_>>();
+    assert_eq!(vec![StickGesture::Rotation { clockwise: false, count: 1 }, StickGesture::Rotation { clockwise: false, count: 2 }], rotations, "{events:?}");
+    assert!(!events.iter().any(|e| e.0 == StickGesture::Shake), "rotating isn't shaking");
+
+    let cw = std::iter::once((990, 0.0, 0.0)).chain((0 ..= 40).map(|i| (1000 + i * 10, 1.0, 90.0 - i as f32 * 10.0))).collect::<Vec<_>>();
+    let events = stick_script(&mut r, &cw);
+    assert_eq!(vec![StickGesture::Rotation { clockwise: true, count: 1 }], events.iter().filter(|e| matches!(e.0, StickGesture::Rotation { .. })).map(|e| e.0).collect::<Vec<_>>());
+    assert!(r.rotation() < -0.1);
+    r.reset();
+    assert_eq!(0.0, r.rotation());
+}
+
+#[test] fn test_shake() {
+    let mut r = StickRecognizer::new(ThumbStick::Left, StickThresholds::default());
+    let swings = |t0: u64, dt: u64| (0 .. 10).map(move |i| (t0 + i * dt, if i % 2 == 0 { 0.0 } else { 1.0 }, if i % 4 == 1 { 0.0 } else { 180.0 })).collect::<Vec<_>>();
+    let events = stick_script(&mut r, &swings(0, 50));
+    assert_eq!(vec![(StickGesture::Shake, 450)], events.into_iter().filter(|e| e.0 == StickGesture::Shake).collect::<Vec<_>>());
+    r.reset();
+    let events = stick_script(&mut r, &swings(0, 400));
+    assert!(!events.iter().any(|e| e.0 == StickGesture::Shake), "too slow: {events:?}");
+}
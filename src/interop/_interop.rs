@@ -1,5 +1,7 @@
 mod cpp;
 
+#[cfg(feature = "serde")] #[path = "serde-1.rs"] mod serde_1;
+
 #[cfg(feature = "winapi-0-3")] #[path = "winapi-0.3.rs"] mod winapi_0_3;
 #[cfg(feature = "winapi-0-2")] #[path = "winapi-0.2.rs"] mod winapi_0_2;
 #[cfg(feature = "winapi-0-1")] #[path = "winapi-0.1.rs"] mod winapi_0_1;
@@ -0,0 +1,369 @@
+use crate::*;
+use winapi::shared::guiddef::GUID;
+
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::ser::{self, Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+use std::ffi::OsStr;
+use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+
+
+/// Flags and enumish types: named values in human readable formats, raw integers otherwise.
+trait Named : Copy + 'static {
+    const TYPE  : &'static str;
+    const NAMES : &'static [(&'static str, Self)];
+    fn to_u64(self) -> u64;
+    fn from_u64(value: u64) -> Option<Self>;
+    fn serialize_raw<S: Serializer>(self, serializer: S) -> Result<S::Ok, S::Error>;
+    fn deserialize_raw<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+macro_rules! named {
+    ( $( $named:ty => $raw:ty ),* $(,)? ) => {$(
+        impl Named for $named {
+            const TYPE  : &'static str = stringify!($named);
+            const NAMES : &'static [(&'static str, Self)] = <$named>::NAMES;
+            fn to_u64(self) -> u64 { self.into_inner().into() }
+            fn from_u64(value: u64) -> Option<Self> { <$raw>::try_from(value).ok().map(Self::from_unchecked) }
+            fn serialize_raw<S: Serializer>(self, serializer: S) -> Result<S::Ok, S::Error> { self.into_inner().serialize(serializer) }
+            fn deserialize_raw<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> { <$raw>::deserialize(deserializer).map(Self::from_unchecked) }
+        }
+    )*};
+}
+
+macro_rules! enums {
+    ( $( $enumish:ty => $raw:ty ),* $(,)? ) => {$(
+        named! { $enumish => $raw }
+
+        impl Serialize for $enumish {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match Self::NAMES.iter().find(|(_, value)| *value == *self) {
+                    Some((name, _)) if serializer.is_human_readable()   => serializer.serialize_str(name),
+                    _                                                   => self.serialize_raw(serializer),
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $enumish {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() { NameOrValue(PhantomData).deserialize(deserializer) } else { Self::deserialize_raw(deserializer) }
+            }
+        }
+    )*};
+}
+
+macro_rules! flags {
+    ( $( $flags:ty => $raw:ty ),* $(,)? ) => {$(
+        named! { $flags => $raw }
+
+        impl Serialize for $flags {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if !serializer.is_human_readable() { return self.serialize_raw(serializer) }
+                let mut unknown = self.into_inner();
+                let mut names = Vec::new();
+                for &(name, flag) in Self::NAMES {
+                    let flag = flag.into_inner();
+                    if flag != 0 && unknown & flag == flag { names.push(name); unknown &= !flag; }
+                }
+                let mut seq = serializer.serialize_seq(Some(names.len() + usize::from(unknown != 0)))?;
+                for name in names { seq.serialize_element(name)? }
+                if unknown != 0 { seq.serialize_element(&unknown)? }
+                seq.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $flags {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() { deserializer.deserialize_any(FlagList(PhantomData)) } else { Self::deserialize_raw(deserializer) }
+            }
+        }
+    )*};
+}
+
+macro_rules! structs {
+    ( $( $struct:ident { $( $field:ident ),* $(,)? } )* ) => {$(
+        impl Serialize for $struct {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut s = serializer.serialize_struct(stringify!($struct), [$(stringify!($field)),*].len())?;
+                $( s.serialize_field(stringify!($field), &self.$field)?; )*
+                s.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $struct {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                const FIELDS : &[&str] = &[$(stringify!($field)),*];
+                struct StructVisitor;
+                impl<'de> Visitor<'de> for StructVisitor {
+                    type Value = $struct;
+                    fn expecting(&self, f: &mut Formatter) -> fmt::Result { f.write_str(concat!("struct ", stringify!($struct))) }
+
+                    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<$struct, A::Error> {
+                        let mut index = 0;
+                        $( let $field = element(&mut seq, &mut index, &self)?; )*
+                        Ok($struct { $($field),* })
+                    }
+
+                    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<$struct, A::Error> {
+                        $( let mut $field = None; )*
+                        while let Some(key) = map.next_key_seed(Field(FIELDS))? {
+                            $(
+                                if key == Some(stringify!($field)) {
+                                    if $field.is_some() { return Err(de::Error::duplicate_field(stringify!($field))) }
+                                    $field = Some(map.next_value()?);
+                                    continue;
+                                }
+                            )*
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                        Ok($struct { $( $field: $field.ok_or_else(|| de::Error::missing_field(stringify!($field)))? ),* })
+                    }
+                }
+                deserializer.deserialize_struct(stringify!($struct), FIELDS, StructVisitor)
+            }
+        }
+    )*};
+}
+
+
+
+enums! {
+    BatteryDevType  => u8,
+    BatteryLevel    => u8,
+    BatteryType     => u8,
+    DevSubType      => u8,
+    DevType         => u8,
+    VK              => u16,
+}
+
+flags! {
+    Buttons         => u16,
+    Caps            => u16,
+    KeystrokeFlags  => u16,
+}
+
+structs! {
+    BatteryInformation      { battery_type, battery_level }
+    Capabilities            { ty, sub_type, flags, gamepad, vibration }
+    DSoundAudioDeviceGuids  { dsound_render_guid, dsound_capture_guid }
+    Gamepad                 { buttons, left_trigger, right_trigger, left_thumb_x, left_thumb_y, right_thumb_x, right_thumb_y }
+    Keystroke               { virtual_key, unicode, flags, user_index, hid_code }
+    State                   { packet_number, gamepad }
+    Vibration               { left_motor_speed, right_motor_speed }
+}
+
+/// [`AudioDeviceIds`] with its device ids as [`String`]s.
+mod strings {
+    use super::*;
+
+    pub struct AudioDeviceIds {
+        pub render_device_id:   Option<String>,
+        pub capture_device_id:  Option<String>,
+    }
+
+    structs! { AudioDeviceIds { render_device_id, capture_device_id } }
+}
+
+impl Serialize for AudioDeviceIds {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let id = |id: &Option<Arc<OsStr>>| id.as_deref().map(|id| id.to_str().map(String::from).ok_or_else(|| ser::Error::custom(format_args!("audio device id {id:?} is not valid unicode")))).transpose();
+        strings::AudioDeviceIds { render_device_id: id(&self.render_device_id)?, capture_device_id: id(&self.capture_device_id)? }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AudioDeviceIds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ids = strings::AudioDeviceIds::deserialize(deserializer)?;
+        let id = |id: Option<String>| id.map(|id| Arc::from(OsStr::new(&id)));
+        Ok(AudioDeviceIds { render_device_id: id(ids.render_device_id), capture_device_id: id(ids.capture_device_id) })
+    }
+}
+
+/// `"{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}"` in human readable formats, 16 raw bytes otherwise.
+impl Serialize for DSoundAudioDeviceGuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() { serializer.collect_str(self) } else { serializer.serialize_bytes(bytemuck::bytes_of(self)) }
+    }
+}
+
+impl<'de> Deserialize<'de> for DSoundAudioDeviceGuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct GuidVisitor;
+        impl<'de> Visitor<'de> for GuidVisitor {
+            type Value = DSoundAudioDeviceGuid;
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result { f.write_str("a GUID") }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                parse_guid(v).ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                let bytes = <[u8; 16]>::try_from(v).map_err(|_| E::invalid_length(v.len(), &self))?;
+                Ok(bytemuck::cast(bytes))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0u8; 16];
+                let mut index = 0;
+                for byte in bytes.iter_mut() { *byte = element(&mut seq, &mut index, &self)? }
+                Ok(bytemuck::cast(bytes))
+            }
+        }
+        if deserializer.is_human_readable() { deserializer.deserialize_str(GuidVisitor) } else { deserializer.deserialize_bytes(GuidVisitor) }
+    }
+}
+
+fn parse_guid(s: &str) -> Option<DSoundAudioDeviceGuid> {
+    let s = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')).unwrap_or(s);
+    let parts = s.split('-').collect::<Vec<_>>();
+    if parts.len() != 5 || parts.iter().zip([8, 4, 4, 4, 12]).any(|(p, len)| p.len() != len || !p.bytes().all(|b| b.is_ascii_hexdigit())) { return None }
+    let hex = |p: &str| u64::from_str_radix(p, 16).ok();
+    let data4 = (hex(parts[3])? << 48) | hex(parts[4])?;
+    Some(DSoundAudioDeviceGuid(GUID { Data1: hex(parts[0])? as u32, Data2: hex(parts[1])? as u16, Data3: hex(parts[2])? as u16, Data4: data4.to_be_bytes() }))
+}
+
+
+
+/// The next element of a struct-like sequence, or an [`invalid_length`](de::Error::invalid_length) error.
+fn element<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(seq: &mut A, index: &mut usize, expected: &dyn de::Expected) -> Result<T, A::Error> {
+    let value = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(*index, expected))?;
+    *index += 1;
+    Ok(value)
+}
+
+/// Deserializes a struct field name, as one of the given names ([`None`] if unknown.)
+struct Field(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for Field {
+    type Value = Option<&'static str>;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> { deserializer.deserialize_identifier(self) }
+}
+
+impl<'de> Visitor<'de> for Field {
+    type Value = Option<&'static str>;
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result { f.write_str("a field name") }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> { Ok(self.0.iter().copied().find(|f| *f == v)) }
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> { Ok(self.0.iter().copied().find(|f| f.as_bytes() == v)) }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> { Ok(usize::try_from(v).ok().and_then(|i| self.0.get(i).copied())) }
+}
+
+/// Deserializes a [`Named`] value from its name or its raw integer value.
+struct NameOrValue<T>(PhantomData<T>);
+
+impl<'de, T: Named> DeserializeSeed<'de> for NameOrValue<T> {
+    type Value = T;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> { deserializer.deserialize_any(self) }
+}
+
+impl<'de, T: Named> Visitor<'de> for NameOrValue<T> {
+    type Value = T;
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result { write!(f, "a {} name or integer value", T::TYPE) }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::NAMES.iter().find(|(name, _)| *name == v).map(|&(_, value)| value).ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> { T::from_u64(v).ok_or_else(|| E::invalid_value(Unexpected::Unsigned(v), &self)) }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> { u64::try_from(v).ok().and_then(T::from_u64).ok_or_else(|| E::invalid_value(Unexpected::Signed(v), &self)) }
+}
+
+/// Deserializes [`Named`] flags from a list of names and/or raw integer bits, or from a single raw integer.
+struct FlagList<T>(PhantomData<T>);
+
+impl<'de, T: Named> Visitor<'de> for FlagList<T> {
+    type Value = T;
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result { write!(f, "a list of {} names or bits", T::TYPE) }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bits = 0;
+        while let Some(flag) = seq.next_element_seed(NameOrValue::<T>(PhantomData))? { bits |= flag.to_u64() }
+        Ok(T::from_u64(bits).expect("bits of valid flags should be valid flags"))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> { NameOrValue(PhantomData).visit_u64(v) }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> { NameOrValue(PhantomData).visit_i64(v) }
+}
+
+
+
+#[cfg(test)] use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Configure, Readable, Token};
+
+/// Compares by [`Debug`](fmt::Debug) output, for types without [`PartialEq`].
+#[cfg(test)] #[derive(Debug)] struct Dbg<T>(T);
+#[cfg(test)] impl<T: fmt::Debug> PartialEq for Dbg<T> { fn eq(&self, other: &Self) -> bool { format!("{:?}", self.0) == format!("{:?}", other.0) } }
+#[cfg(test)] impl<T: Serialize> Serialize for Dbg<T> { fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> { self.0.serialize(serializer) } }
+#[cfg(test)] impl<'de, T: Deserialize<'de>> Deserialize<'de> for Dbg<T> { fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> { T::deserialize(deserializer).map(Dbg) } }
+
+#[test] fn test_flags() {
+    let buttons = Buttons::A | Buttons::DPadUp;
+    assert_tokens(&buttons.readable(), &[Token::Seq { len: Some(2) }, Token::Str("DPadUp"), Token::Str("A"), Token::SeqEnd]);
+    assert_tokens(&buttons.compact(),  &[Token::U16(0x1001)]);
+    assert_tokens(&Buttons::None.readable(), &[Token::Seq { len: Some(0) }, Token::SeqEnd]);
+
+    let unknown = Buttons::from_unchecked(0x0800 | Buttons::B.into_inner());
+    assert_tokens(&unknown.readable(), &[Token::Seq { len: Some(2) }, Token::Str("B"), Token::U16(0x0800), Token::SeqEnd]);
+    assert_tokens(&unknown.compact(),  &[Token::U16(0x2800)]);
+    assert_de_tokens(&unknown.readable(), &[Token::Seq { len: None }, Token::I64(0x0800), Token::Str("B"), Token::Str("None"), Token::SeqEnd]);
+    assert_de_tokens(&unknown.readable(), &[Token::U32(0x2800)]);
+
+    assert_tokens(&(Caps::Wireless | Caps::FfbSupported).readable(), &[Token::Seq { len: Some(2) }, Token::Str("FfbSupported"), Token::Str("Wireless"), Token::SeqEnd]);
+    assert_tokens(&(KeystrokeFlags::KeyDown | KeystrokeFlags::Repeat).readable(), &[Token::Seq { len: Some(2) }, Token::Str("KeyDown"), Token::Str("Repeat"), Token::SeqEnd]);
+
+    assert_de_tokens_error::<Readable<Buttons>>(&[Token::Seq { len: None }, Token::Str("Z")], "invalid value: string \"Z\", expected a Buttons name or integer value");
+    assert_de_tokens_error::<Readable<Buttons>>(&[Token::U32(0x10000)], "invalid value: integer `65536`, expected a Buttons name or integer value");
+}
+
+#[test] fn test_enums() {
+    assert_tokens(&VK::PadA.readable(), &[Token::Str("PadA")]);
+    assert_tokens(&VK::PadA.compact(),  &[Token::U16(0x5800)]);
+    assert_tokens(&VK::from_unchecked(0x1234).readable(), &[Token::U16(0x1234)]);
+    assert_de_tokens(&VK::PadB.readable(), &[Token::U64(0x5801)]);
+    assert_tokens(&DevSubType::Wheel.readable(), &[Token::Str("Wheel")]);
+    assert_tokens(&DevType::Gamepad.compact(), &[Token::U8(1)]);
+    assert_tokens(&BatteryLevel::Medium.readable(), &[Token::Str("Medium")]);
+    assert_tokens(&BatteryType::NiMH.readable(), &[Token::Str("NiMH")]);
+    assert_tokens(&BatteryDevType::Headset.readable(), &[Token::Str("Headset")]);
+    assert_de_tokens_error::<Readable<DevSubType>>(&[Token::I64(-1)], "invalid value: integer `-1`, expected a DevSubType name or integer value");
+}
+
+#[test] fn test_structs() {
+    let vibration = Vibration { left_motor_speed: 1, right_motor_speed: 2 };
+    let tokens = [Token::Struct { name: "Vibration", len: 2 }, Token::Str("left_motor_speed"), Token::U16(1), Token::Str("right_motor_speed"), Token::U16(2), Token::StructEnd];
+    assert_tokens(&Dbg(vibration).compact(), &tokens);
+    assert_de_tokens(&Dbg(vibration), &[Token::Seq { len: Some(2) }, Token::U16(1), Token::U16(2), Token::SeqEnd]);
+    assert_de_tokens(&Dbg(vibration), &[Token::Map { len: None }, Token::Str("right_motor_speed"), Token::U16(2), Token::Str("extra"), Token::Bool(true), Token::Str("left_motor_speed"), Token::U16(1), Token::MapEnd]);
+    assert_de_tokens_error::<Dbg<Vibration>>(&[Token::Map { len: None }, Token::Str("left_motor_speed"), Token::U16(1), Token::MapEnd], "missing field `right_motor_speed`");
+
+    let keystroke = Keystroke { virtual_key: VK::PadX, unicode: 0, flags: KeystrokeFlags::KeyUp, user_index: 3, hid_code: 0 };
+    assert_tokens(&Dbg(keystroke).readable(), &[
+        Token::Struct { name: "Keystroke", len: 5 },
+        Token::Str("virtual_key"),  Token::Str("PadX"),
+        Token::Str("unicode"),      Token::U16(0),
+        Token::Str("flags"),        Token::Seq { len: Some(1) }, Token::Str("KeyUp"), Token::SeqEnd,
+        Token::Str("user_index"),   Token::U8(3),
+        Token::Str("hid_code"),     Token::U8(0),
+        Token::StructEnd,
+    ]);
+
+    let info = BatteryInformation { battery_type: BatteryType::Wired, battery_level: BatteryLevel::Full };
+    assert_tokens(&Dbg(info).compact(), &[Token::Struct { name: "BatteryInformation", len: 2 }, Token::Str("battery_type"), Token::U8(1), Token::Str("battery_level"), Token::U8(3), Token::StructEnd]);
+
+    let ids = AudioDeviceIds { render_device_id: Some(Arc::from(OsStr::new("speakers"))), capture_device_id: None };
+    assert_tokens(&Dbg(ids), &[Token::Struct { name: "AudioDeviceIds", len: 2 }, Token::Str("render_device_id"), Token::Some, Token::Str("speakers"), Token::Str("capture_device_id"), Token::None, Token::StructEnd]);
+}
+
+#[test] fn test_guid() {
+    let guid = parse_guid("{01234567-89AB-CDEF-0123-456789ABCDEF}").unwrap();
+    assert_eq!("{01234567-89AB-CDEF-0123-456789ABCDEF}", guid.to_string());
+    assert_eq!(Some(guid), parse_guid("01234567-89ab-cdef-0123-456789abcdef"));
+    for bad in ["", "{}", "01234567-89AB-CDEF-0123-456789ABCDE", "01234567-89AB-CDEF-0123456789ABCDEF", "0123456789AB-CDEF-0123-456789ABCDEF-", "+1234567-89AB-CDEF-0123-456789ABCDEF"] {
+        assert_eq!(None, parse_guid(bad), "{bad:?}");
+    }
+
+    assert_tokens(&guid.readable(), &[Token::Str("{01234567-89AB-CDEF-0123-456789ABCDEF}")]);
+    assert_tokens(&guid.compact(),  &[Token::Bytes(&[0x67, 0x45, 0x23, 0x01, 0xAB, 0x89, 0xEF, 0xCD, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF])]);
+    assert_de_tokens_error::<Readable<DSoundAudioDeviceGuid>>(&[Token::Str("nope")], "invalid value: string \"nope\", expected a GUID");
+}
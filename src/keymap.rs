@@ -0,0 +1,273 @@
+//! Translate [`Gamepad`] input into abstract keyboard and mouse events, for controlling keyboard and mouse only applications.
+//!
+//! This module only produces [`OutputEvent`]s: injecting them (e.g. with `SendInput`) is left to the caller.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::keymap::*;
+//! use std::time::Instant;
+//!
+//! let mut keymap = KeyMapper::desktop();
+//! keymap.mappings.push(Mapping::new(Buttons::Y, Key::Virtual(0x20))); // VK_SPACE
+//!
+//! # for _ in 0 .. 1 {
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! for event in keymap.update(&state, Instant::now()) {
+//!     // e.g. translate to INPUT structures for SendInput
+//!     println!("{event:?}");
+//! }
+//! # }
+//! ```
+
+use crate::*;
+use crate::actions::{Input, PRESS_THRESHOLD};
+use crate::pointer::{MouseButton, Pointer, PointerEvent};
+
+use std::time::{Duration, Instant};
+
+
+
+/// A keyboard key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    /// A Windows virtual-key code (`VK_*`, e.g. `0x0D` for `VK_RETURN`.)
+    Virtual(u16),
+
+    /// A hardware scan code (e.g. `0x1C` for Enter), for applications that read raw keyboard input.
+    Scan(u16),
+}
+
+/// An abstract keyboard or mouse event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OutputEvent {
+    /// A key was pressed.
+    KeyDown(Key),
+
+    /// A key was released.
+    KeyUp(Key),
+
+    /// The mouse moved by whole pixels (`+dy` is down.)
+    MouseMove {
+        /// Horizontal movement, in pixels.
+        dx: i32,
+
+        /// Vertical movement, in pixels (`+` = down.)
+        dy: i32,
+    },
+
+    /// A mouse button was pressed.
+    MouseDown(MouseButton),
+
+    /// A mouse button was released.
+    MouseUp(MouseButton),
+
+    /// The mouse wheel turned, in units where `120` is one notch (`WHEEL_DELTA`.)
+    Wheel {
+        /// Positive values scroll up (or right, if `horizontal`.)
+        delta:      i32,
+
+        /// `true` for a horizontal wheel.
+        horizontal: bool,
+    },
+}
+
+/// What a [`Mapping`] produces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    /// Hold a key while the input is pressed.
+    Key(Key),
+
+    /// Hold a mouse button while the input is pressed.
+    Mouse(MouseButton),
+
+    /// Scroll continuously, proportional to the input's value.
+    Wheel {
+        /// Wheel units (`120` = one notch) per second at an input value of `1.0`.  Use a negative speed to scroll down (or left.)
+        speed:      f32,
+
+        /// `true` for a horizontal wheel.
+        horizontal: bool,
+    },
+}
+
+impl From<Key> for Output { fn from(key: Key) -> Self { Output::Key(key) } }
+impl From<MouseButton> for Output { fn from(button: MouseButton) -> Self { Output::Mouse(button) } }
+
+/// An [`Input`] mapped to an [`Output`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mapping {
+    /// What to read.
+    pub input:  Input,
+
+    /// What to produce.  Keys and mouse buttons are held while the input's value is at least [`PRESS_THRESHOLD`].
+    pub output: Output,
+}
+
+impl Mapping {
+    /// Map `input` to `output`.
+    pub fn new(input: impl Into<Input>, output: impl Into<Output>) -> Self { Self { input: input.into(), output: output.into() } }
+}
+
+/// Converts [`Gamepad`] changes into [`OutputEvent`]s according to a table of [`Mapping`]s, and an optional [`Pointer`].
+#[derive(Clone, Debug)]
+pub struct KeyMapper {
+    /// The mapping table.
+    pub mappings:   Vec<Mapping>,
+
+    /// Mouse cursor emulation, if any.  The pointer's own [`clicks`](Pointer::clicks) are reported as [`OutputEvent::MouseDown`] / [`OutputEvent::MouseUp`].
+    pub pointer:    Option<Pointer>,
+
+    held:           Vec<bool>,
+    wheel:          Vec<f32>,
+    prev:           Option<Instant>,
+}
+
+impl KeyMapper {
+    /// An empty mapping table, without a pointer.
+    pub fn new(mappings: impl Into<Vec<Mapping>>) -> Self { Self { mappings: mappings.into(), pointer: None, held: Vec::new(), wheel: Vec::new(), prev: None } }
+
+    /// A general purpose desktop layout:
+    ///
+    /// | Input             | Output    |
+    /// | ----------------- | --------- |
+    /// | Left thumbstick   | Mouse cursor ([`Pointer::new`])
+    /// | A / B             | Left / right click
+    /// | Right thumbstick  | Vertical wheel
+    /// | D-pad             | Arrow keys
+    /// | Start             | Enter
+    /// | Back              | Escape
+    /// | LB / RB           | Shift+Tab / Tab
+    pub fn desktop() -> Self {
+        const VK_TAB : u16 = 0x09; const VK_RETURN : u16 = 0x0D; const VK_SHIFT : u16 = 0x10; const VK_ESCAPE : u16 = 0x1B;
+        const VK_LEFT : u16 = 0x25; const VK_UP : u16 = 0x26; const VK_RIGHT : u16 = 0x27; const VK_DOWN : u16 = 0x28;
+        let mut keymap = Self::new([
+            Mapping::new(Input::Direction(ThumbStick::Right, Direction8::Up),   Output::Wheel { speed:  1200.0, horizontal: false }),
+            Mapping::new(Input::Direction(ThumbStick::Right, Direction8::Down), Output::Wheel { speed: -1200.0, horizontal: false }),
+            Mapping::new(Buttons::DPadLeft,     Key::Virtual(VK_LEFT)),
+            Mapping::new(Buttons::DPadUp,       Key::Virtual(VK_UP)),
+            Mapping::new(Buttons::DPadRight,    Key::Virtual(VK_RIGHT)),
+            Mapping::new(Buttons::DPadDown,     Key::Virtual(VK_DOWN)),
+            Mapping::new(Buttons::Start,        Key::Virtual(VK_RETURN)),
+            Mapping::new(Buttons::Back,         Key::Virtual(VK_ESCAPE)),
+            Mapping::new(Buttons::LeftShoulder, Key::Virtual(VK_SHIFT)),
+            Mapping::new(Buttons::LeftShoulder, Key::Virtual(VK_TAB)),
+            Mapping::new(Buttons::RightShoulder,Key::Virtual(VK_TAB)),
+        ]);
+        keymap.pointer = Some(Pointer::new(ThumbStick::Left));
+        keymap
+    }
+
+    /// Feed the next `gamepad` state, sampled at `timestamp`, returning the resulting events in order.
+    ///
+    /// If several mappings hold the same key or mouse button, it's pressed when the first of them is pressed, and released when the last of them is released.
+    pub fn update(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Vec<OutputEvent> {
+        let mut events = Vec::new();
+        let dt = self.prev.replace(timestamp).map_or(Duration::ZERO, |prev| timestamp.saturating_duration_since(prev)).as_secs_f32();
+        self.held.resize(self.mappings.len(), false);
+        self.wheel.resize(self.mappings.len(), 0.0);
+
+        if let Some(pointer) = self.pointer.as_mut() {
+            events.extend(pointer.update(gamepad, timestamp).into_iter().map(|e| match e {
+                PointerEvent::Move { dx, dy }   => OutputEvent::MouseMove { dx, dy },
+                PointerEvent::Down(b)           => OutputEvent::MouseDown(b),
+                PointerEvent::Up(b)             => OutputEvent::MouseUp(b),
+            }));
+        }
+
+        let was = self.outputs_held();
+        for (i, mapping) in self.mappings.iter().enumerate() {
+            let value = mapping.input.value(gamepad);
+            match mapping.output {
+                Output::Key(_) | Output::Mouse(_) => self.held[i] = value >= PRESS_THRESHOLD,
+                Output::Wheel { speed, horizontal } => {
+                    let wheel = self.wheel[i] + value * speed * dt;
+                    let delta = wheel.trunc();
+                    self.wheel[i] = if value == 0.0 { 0.0 } else { wheel - delta };
+                    if delta != 0.0 { events.push(OutputEvent::Wheel { delta: delta as i32, horizontal }) }
+                },
+            }
+        }
+        let now = self.outputs_held();
+        events.extend(was.iter().filter(|o| !now.contains(o)).map(|o| o.up()));
+        events.extend(now.iter().filter(|o| !was.contains(o)).map(|o| o.down()));
+        events
+    }
+
+    /// Release everything currently held (e.g. when focus changes, to avoid stuck keys), returning the release events.
+    pub fn reset(&mut self) -> Vec<OutputEvent> {
+        let mut events = self.outputs_held().iter().map(|o| o.up()).collect::<Vec<_>>();
+        if let Some(pointer) = self.pointer.as_mut() {
+            if let Some(prev) = self.prev {
+                events.extend(pointer.update(&Gamepad::default(), prev).into_iter().filter_map(|e| match e { PointerEvent::Up(b) => Some(OutputEvent::MouseUp(b)), _ => None }));
+            }
+            pointer.reset();
+        }
+        self.held.clear();
+        self.wheel.clear();
+        self.prev = None;
+        events
+    }
+
+    /// Keys and mouse buttons held by mappings, deduplicated, in mapping order.
+    fn outputs_held(&self) -> Vec<Held> {
+        let mut held = Vec::new();
+        for (mapping, _) in self.mappings.iter().zip(self.held.iter()).filter(|(_, h)| **h) {
+            let o = match mapping.output { Output::Key(k) => Held::Key(k), Output::Mouse(b) => Held::Mouse(b), Output::Wheel { .. } => continue };
+            if !held.contains(&o) { held.push(o) }
+        }
+        held
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Held { Key(Key), Mouse(MouseButton) }
+
+impl Held {
+    fn down(&self) -> OutputEvent { match *self { Held::Key(k) => OutputEvent::KeyDown(k), Held::Mouse(b) => OutputEvent::MouseDown(b) } }
+    fn up  (&self) -> OutputEvent { match *self { Held::Key(k) => OutputEvent::KeyUp(k),   Held::Mouse(b) => OutputEvent::MouseUp(b)   } }
+}
+
+
+
+#[test] fn test_keys() {
+    let t0 = Instant::now();
+    let (enter, tab) = (Key::Virtual(0x0D), Key::Scan(0x0F));
+    let mut keymap = KeyMapper::new([
+        Mapping::new(Buttons::A, enter),
+        Mapping::new(Buttons::B, tab),
+        Mapping::new(Input::Trigger(Axis::RightTrigger, 30), tab),
+        Mapping::new(Buttons::X, MouseButton::Middle),
+    ]);
+    let pad = |buttons, right_trigger| Gamepad { buttons, right_trigger, .. Gamepad::default() };
+    assert_eq!(vec![OutputEvent::KeyDown(enter)],                               keymap.update(&pad(Buttons::A, 0), t0));
+    assert_eq!(Vec::<OutputEvent>::new(),                                       keymap.update(&pad(Buttons::A, 0), t0));
+    assert_eq!(vec![OutputEvent::KeyUp(enter), OutputEvent::KeyDown(tab)],      keymap.update(&pad(Buttons::B, 0), t0));
+    assert_eq!(Vec::<OutputEvent>::new(),                                       keymap.update(&pad(Buttons::None, 255), t0), "still held by the trigger");
+    assert_eq!(vec![OutputEvent::KeyUp(tab), OutputEvent::MouseDown(MouseButton::Middle)], keymap.update(&pad(Buttons::X, 0), t0));
+    assert_eq!(vec![OutputEvent::MouseUp(MouseButton::Middle), OutputEvent::KeyDown(enter)], keymap.reset().into_iter().chain(keymap.update(&pad(Buttons::A, 0), t0)).collect::<Vec<_>>());
+    assert_eq!(vec![OutputEvent::KeyUp(enter)], keymap.reset());
+}
+
+#[test] fn test_wheel_and_pointer() {
+    let t0 = Instant::now();
+    let mut keymap = KeyMapper::desktop();
+    let mut scrolled = 0;
+    for frame in 0 ..= 100 {
+        for event in keymap.update(&Gamepad { right_thumb_y: -32768, .. Gamepad::default() }, t0 + Duration::from_millis(10 * frame)) {
+            match event {
+                OutputEvent::Wheel { delta, horizontal: false } => scrolled += delta,
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+    }
+    assert!((-1201 ..= -1199).contains(&scrolled), "{scrolled}");
+    let events = keymap.reset();
+    assert_eq!(Vec::<OutputEvent>::new(), events);
+
+    let events = keymap.update(&Gamepad { left_thumb_x: 32767, buttons: Buttons::A | Buttons::DPadUp, .. Gamepad::default() }, t0 + Duration::from_millis(1010));
+    assert!(matches!(events[..], [OutputEvent::MouseDown(MouseButton::Left), OutputEvent::KeyDown(Key::Virtual(0x26))]), "{events:?}");
+    let events = keymap.update(&Gamepad { left_thumb_x: 32767, buttons: Buttons::A | Buttons::DPadUp, .. Gamepad::default() }, t0 + Duration::from_millis(1020));
+    assert!(matches!(events[..], [OutputEvent::MouseMove { dx, dy: 0 }] if dx > 0), "{events:?}");
+    assert_eq!(vec![OutputEvent::KeyUp(Key::Virtual(0x26)), OutputEvent::MouseUp(MouseButton::Left)], keymap.reset());
+}
@@ -201,6 +201,29 @@ macro_rules! enumish {
             }
         }
 
+        impl $enumish {
+            pub(crate) const NAMES : &'static [(&'static str, $enumish)] = &[$((stringify!($ident), <$enumish>::$ident)),*];
+        }
+
+        /// Writes the name (e.g. `PadA`), or `0x`-prefixed hex for unnamed values.
+        impl std::fmt::Display for $enumish {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match Self::NAMES.iter().find(|(_, value)| value.0 == self.0) {
+                    Some((name, _)) => f.write_str(name),
+                    None            => write!(f, "{:#x}", self.0),
+                }
+            }
+        }
+
+        /// Parses a name (`PadA` or `VK::PadA`), or a raw value (`0x5800` or `22528`.)
+        impl std::str::FromStr for $enumish {
+            type Err = $crate::error::ParseError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let value = $crate::macros::parse_named(&[stringify!($enumish)], s, false, |name| Self::NAMES.iter().find(|(n, _)| *n == name).map(|(_, v)| v.0.into()))?;
+                value.try_into().map(Self).map_err(|_| $crate::error::ParseError::new(stringify!($enumish), s))
+            }
+        }
+
         enumish!( $enumish => $d3d );
     };
     ( $enumish:ty => $d3d:ty ) => {
@@ -230,9 +253,13 @@ macro_rules! enumish {
 /// ### Usage
 /// ```ignore
 /// flags! { RustyFlags => XINPUT_FLAGS; A, B, C }
+/// flags! { Alias => XINPUT_FLAGS; type_name: "RustyFlags"; A, B, C } // parse and report errors as `RustyFlags`, not just `Alias`
 /// ```
 macro_rules! flags {
     ( $flagish:ty => $d3d:ty; $($ident:ident),* $(,)? ) => {
+        flags! { $flagish => $d3d; type_name: stringify!($flagish); $($ident),* }
+    };
+    ( $flagish:ty => $d3d:ty; type_name: $type_name:expr; $($ident:ident),* $(,)? ) => {
         impl std::fmt::Debug for $flagish {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 match *self {
@@ -294,6 +321,39 @@ macro_rules! flags {
             pub const fn into_inner(self) -> $d3d { self.0 as _ }
         }
 
+        impl $flagish {
+            pub(crate) const NAMES : &'static [(&'static str, $flagish)] = &[$((stringify!($ident), <$flagish>::$ident)),*];
+        }
+
+        /// Writes `|`-separated names (e.g. `A|B`), followed by `0x`-prefixed hex for any unnamed bits.
+        impl std::fmt::Display for $flagish {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let mut unnamed = self.0;
+                let mut sep = "";
+                for (name, flag) in Self::NAMES.iter().copied() {
+                    if flag.0 != 0 && unnamed & flag.0 == flag.0 {
+                        write!(f, "{sep}{name}")?;
+                        unnamed &= !flag.0;
+                        sep = "|";
+                    }
+                }
+                match (unnamed, sep) {
+                    (0, "") => f.write_str(Self::NAMES.iter().find(|(_, flag)| flag.0 == 0).map_or("0x0", |(name, _)| name)),
+                    (0, _)  => Ok(()),
+                    _       => write!(f, "{sep}{unnamed:#x}"),
+                }
+            }
+        }
+
+        /// Parses `|`-separated names and/or raw values: `A|B`, `Buttons::A | Buttons::B`, `Buttons::{A|B}`, `0x3000`, `A|0x800`, ...
+        impl std::str::FromStr for $flagish {
+            type Err = $crate::error::ParseError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let value = $crate::macros::parse_named(&[$type_name, stringify!($flagish)], s, true, |name| Self::NAMES.iter().find(|(n, _)| *n == name).map(|(_, v)| v.0.into()))?;
+                value.try_into().map(Self).map_err(|_| $crate::error::ParseError::new($type_name, s))
+            }
+        }
+
         impl std::default::Default for $flagish {
             fn default() -> Self { Self::none() }
         }
@@ -309,6 +369,35 @@ macro_rules! flags {
     }
 }
 
+/// Parses `enumish!` / `flags!` values, as written by their `Debug` or `Display` implementations.
+///
+/// Items are names (`A`, `Buttons::A`) or raw values (`0x1000`, `4096`.)
+/// If `flags`, several items may be `|`-ed together (`A|B`, `Buttons::A | Buttons::B`, `Buttons::{A|B}`, `Buttons::none()`.)
+/// Any of `type_names` may prefix names; the first is used for errors.
+pub(crate) fn parse_named(type_names: &[&'static str], s: &str, flags: bool, name: impl Fn(&str) -> Option<u64>) -> Result<u64, crate::error::ParseError> {
+    let err = || crate::error::ParseError::new(type_names[0], s);
+    fn unprefix<'s>(type_names: &[&str], s: &'s str) -> Option<&'s str> { type_names.iter().find_map(|t| s.strip_prefix(t)) }
+    let wrapped = |open, close| unprefix(type_names, s.trim())?.strip_prefix(open)?.strip_suffix(close);
+    let items = wrapped("::{", "}").or_else(|| wrapped("(", ")")).unwrap_or(s);
+    if !flags && items.contains('|') { return Err(err()) }
+
+    let mut value = 0;
+    for item in items.split('|') {
+        let item = item.trim();
+        let item = unprefix(type_names, item).and_then(|i| i.strip_prefix("::")).unwrap_or(item);
+        value |= if let Some(v) = name(item) {
+            v
+        } else if flags && item == "none()" {
+            0
+        } else if let Some(hex) = item.strip_prefix("0x").or_else(|| item.strip_prefix("0X")) {
+            u64::from_str_radix(hex, 16).map_err(|_| err())?
+        } else {
+            item.parse().map_err(|_| err())?
+        };
+    }
+    Ok(value)
+}
+
 macro_rules! mods {
     ( $( #[$attr:meta] )* inl      mod $mod:ident ;                $($tt:tt)* ) => { $(#[$attr])* pub(crate) mod $mod;                       #[allow(unused_imports)] pub use self::$mod::*; mods!{ $($tt)* } };
     ( $( #[$attr:meta] )* inl      mod $mod:ident { $($body:tt)* } $($tt:tt)* ) => { $(#[$attr])* pub(crate) mod $mod { mods!{ $($body)* } } #[allow(unused_imports)] pub use self::$mod::*; mods!{ $($tt)* } };
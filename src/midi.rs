@@ -0,0 +1,388 @@
+//! [MIDI 1.0](https://midi.org/midi-1-0-core-specifications) output for [`DevSubType::DrumKit`] and [`DevSubType::Guitar`]-style controllers.
+//!
+//! This module only produces raw MIDI bytes (or [`Message`]s) &mdash; sending them to an actual MIDI device or file is left to the caller.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! let gamepad = 0;
+//! let caps = xinput::get_capabilities(gamepad, Flag::None).ok();
+//! let map = caps.and_then(|caps| midi::NoteMap::for_sub_type(caps.sub_type)).unwrap_or_else(midi::NoteMap::drum_kit);
+//! let mut converter = midi::Converter::new(map);
+//!
+//! let mut bytes = Vec::new();
+//! if let Ok(state) = xinput::get_state(gamepad) {
+//!     converter.update_bytes(&state, &mut bytes);
+//! }
+//! // ...send `bytes` to a MIDI output...
+//! ```
+
+use crate::*;
+
+
+
+/// A MIDI 1.0 channel voice message.
+///
+/// Channels are zero-indexed (MIDI software often displays them as `1 ..= 16`.)
+/// Out of range values are masked when converted [`Message::to_bytes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Message {
+    /// `0x9n note velocity`
+    NoteOn {
+        /// MIDI channel (`0 ..= 15`.)
+        channel:    u8,
+        /// MIDI note number (`60` = middle C.)
+        note:       u8,
+        /// How hard the note was struck (`1 ..= 127`.)
+        velocity:   u8,
+    },
+
+    /// `0x8n note velocity`
+    NoteOff {
+        /// MIDI channel (`0 ..= 15`.)
+        channel:    u8,
+        /// MIDI note number (`60` = middle C.)
+        note:       u8,
+        /// Release velocity (generally ignored.)
+        velocity:   u8,
+    },
+
+    /// `0xBn controller value`
+    ControlChange {
+        /// MIDI channel (`0 ..= 15`.)
+        channel:    u8,
+        /// Continuous controller number (e.g. `1` = modulation wheel.)
+        controller: u8,
+        /// Controller value (`0 ..= 127`.)
+        value:      u8,
+    },
+
+    /// `0xEn lsb msb`
+    PitchBend {
+        /// MIDI channel (`0 ..= 15`.)
+        channel:    u8,
+        /// 14-bit (`0 ..= 16383`) bend, with [`Message::PITCH_BEND_CENTER`] being no bend.
+        value:      u16,
+    },
+}
+
+impl Message {
+    /// Pitch bend value for no bend.
+    pub const PITCH_BEND_CENTER : u16 = 0x2000;
+
+    /// Encode this message as raw MIDI bytes.
+    pub fn to_bytes(self) -> [u8; 3] {
+        match self {
+            Message::NoteOn         { channel, note, velocity }     => [0x90 | (channel & 0xF), note & 0x7F, velocity & 0x7F],
+            Message::NoteOff        { channel, note, velocity }     => [0x80 | (channel & 0xF), note & 0x7F, velocity & 0x7F],
+            Message::ControlChange  { channel, controller, value }  => [0xB0 | (channel & 0xF), controller & 0x7F, value & 0x7F],
+            Message::PitchBend      { channel, value }              => [0xE0 | (channel & 0xF), (value & 0x7F) as u8, ((value >> 7) & 0x7F) as u8],
+        }
+    }
+}
+
+impl From<Message> for [u8; 3] { fn from(message: Message) -> Self { message.to_bytes() } }
+
+
+
+/// Reads an [`Axis`] of a [`Gamepad`] as an amount between `0.0` and `1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Control {
+    /// The axis to read.
+    pub axis: Axis,
+
+    /// The [normalized](Axis::read) axis value corresponding to `0.0` (e.g. `-1.0` for a whammy bar resting at `-32768`.)
+    pub rest: f32,
+
+    /// The [normalized](Axis::read) axis value corresponding to `1.0`.
+    pub full: f32,
+}
+
+impl Control {
+    /// Read `gamepad`, returning an amount clamped to `0.0 ..= 1.0`.
+    pub fn amount(&self, gamepad: &Gamepad) -> f32 {
+        let range = self.full - self.rest;
+        if range == 0.0 { return 0.0 }
+        ((self.axis.read(gamepad) - self.rest) / range).clamp(0.0, 1.0)
+    }
+}
+
+/// How hard a [`Note`] is struck.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Velocity {
+    /// A constant velocity (`1 ..= 127`.)
+    Fixed(u8),
+
+    /// Velocity read from an analog axis (some drum kits report per-pad velocity through the thumbstick axes.)
+    Control(Control),
+}
+
+impl Velocity {
+    fn read(&self, gamepad: &Gamepad) -> u8 {
+        match self {
+            Velocity::Fixed(v)      => (*v).clamp(1, 127), // N.B. NoteOn with velocity 0 is treated as NoteOff by most receivers
+            Velocity::Control(c)    => 1 + (c.amount(gamepad) * 126.0).round() as u8,
+        }
+    }
+}
+
+/// Play `note` while all of `buttons` are held.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    /// Buttons that must *all* be held for the note to sound.
+    pub buttons:    Buttons,
+
+    /// MIDI note number (`60` = middle C.)
+    pub note:       u8,
+
+    /// Note on velocity.
+    pub velocity:   Velocity,
+}
+
+/// Send continuous controller `controller` from an analog [`Control`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cc {
+    /// The analog input.
+    pub control:    Control,
+
+    /// MIDI continuous controller number (e.g. `1` = modulation wheel.)
+    pub controller: u8,
+}
+
+/// Configures how a [`Converter`] maps [`Gamepad`] input to MIDI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoteMap {
+    /// MIDI channel to send on (`0 ..= 15`.)
+    pub channel:        u8,
+
+    /// Notes to play.
+    pub notes:          Vec<Note>,
+
+    /// If not [`Buttons::None`], notes only start (or restart) when any of these buttons are pressed, like a guitar's strum bar.
+    /// Otherwise, notes start as soon as their buttons are held, like drum pads.
+    pub strum:          Buttons,
+
+    /// Bend pitch *down* by up to the receiver's bend range, like a whammy bar.
+    pub pitch_bend:     Option<Control>,
+
+    /// Continuous controllers (e.g. guitar tilt.)
+    pub ccs:            Vec<Cc>,
+}
+
+impl NoteMap {
+    /// A drum kit ([`DevSubType::DrumKit`]) on the General MIDI percussion channel (10, or `9` zero-indexed.)
+    ///
+    /// | Pad                           | Note  | General MIDI      |
+    /// | ----------------------------- | -----:| ----------------- |
+    /// | Red ([`Buttons::B`])          | 38    | Acoustic Snare
+    /// | Yellow ([`Buttons::Y`])       | 42    | Closed Hi-Hat
+    /// | Blue ([`Buttons::X`])         | 45    | Low Tom
+    /// | Green ([`Buttons::A`])        | 41    | Low Floor Tom
+    /// | Orange ([`Buttons::RightShoulder`]) | 49 | Crash Cymbal 1
+    /// | Kick pedal ([`Buttons::LeftShoulder`]) | 36 | Bass Drum 1
+    pub fn drum_kit() -> Self {
+        let pad = |buttons, note| Note { buttons, note, velocity: Velocity::Fixed(100) };
+        Self {
+            channel:    9,
+            notes:      vec![
+                pad(Buttons::B,             38),
+                pad(Buttons::Y,             42),
+                pad(Buttons::X,             45),
+                pad(Buttons::A,             41),
+                pad(Buttons::RightShoulder, 49),
+                pad(Buttons::LeftShoulder,  36),
+            ],
+            strum:      Buttons::None,
+            pitch_bend: None,
+            ccs:        Vec::new(),
+        }
+    }
+
+    /// A guitar ([`DevSubType::Guitar`], [`DevSubType::GuitarAlternate`]) with frets tuned to the open strings of a standard tuned guitar.
+    ///
+    /// | Input                                 | MIDI  |
+    /// | ------------------------------------- | ----- |
+    /// | Green fret ([`Buttons::A`])           | `40` (E2)
+    /// | Red fret ([`Buttons::B`])             | `45` (A2)
+    /// | Yellow fret ([`Buttons::Y`])          | `50` (D3)
+    /// | Blue fret ([`Buttons::X`])            | `55` (G3)
+    /// | Orange fret ([`Buttons::LeftShoulder`]) | `59` (B3)
+    /// | Strum bar ([`Buttons::DPadUp`] / [`Buttons::DPadDown`]) | Starts held frets
+    /// | Whammy bar ([`Gamepad::right_thumb_x`]) | Pitch bend (down)
+    /// | Tilt ([`Gamepad::right_thumb_y`])     | CC 1 (modulation wheel)
+    pub fn guitar() -> Self { Self::frets(0, [40, 45, 50, 55, 59]) }
+
+    /// A bass guitar ([`DevSubType::GuitarBass`]), as per [`NoteMap::guitar`] but an octave lower.
+    pub fn bass() -> Self { Self::frets(1, [28, 33, 38, 43, 47]) }
+
+    /// Returns the default map for `sub_type`, or [`None`] if it's not a drum kit or guitar.
+    pub fn for_sub_type(sub_type: DevSubType) -> Option<Self> {
+        match sub_type {
+            DevSubType::DrumKit                             => Some(Self::drum_kit()),
+            DevSubType::Guitar | DevSubType::GuitarAlternate => Some(Self::guitar()),
+            DevSubType::GuitarBass                          => Some(Self::bass()),
+            _                                               => None,
+        }
+    }
+
+    fn frets(channel: u8, notes: [u8; 5]) -> Self {
+        let buttons = [Buttons::A, Buttons::B, Buttons::Y, Buttons::X, Buttons::LeftShoulder];
+        Self {
+            channel,
+            notes:      buttons.iter().zip(notes).map(|(&buttons, note)| Note { buttons, note, velocity: Velocity::Fixed(100) }).collect(),
+            strum:      Buttons::DPadUp | Buttons::DPadDown,
+            pitch_bend: Some(Control { axis: Axis::RightThumbX, rest: -1.0, full: 1.0 }),
+            ccs:        vec![Cc { control: Control { axis: Axis::RightThumbY, rest: 0.0, full: 1.0 }, controller: 1 }],
+        }
+    }
+}
+
+
+
+/// Converts successive [`Gamepad`] states into MIDI [`Message`]s according to a [`NoteMap`].
+#[derive(Clone, Debug)]
+pub struct Converter {
+    map:        NoteMap,
+    buttons:    Buttons,
+    sounding:   Vec<bool>,
+    pitch_bend: Option<u16>,
+    ccs:        Vec<Option<u8>>,
+}
+
+impl Converter {
+    /// Create a new converter.  No notes are considered to be sounding yet.
+    pub fn new(map: NoteMap) -> Self {
+        let sounding    = vec![false; map.notes.len()];
+        let ccs         = vec![None; map.ccs.len()];
+        Self { map, buttons: Buttons::None, sounding, pitch_bend: None, ccs }
+    }
+
+    /// The [`NoteMap`] this converter was created with.
+    pub fn map(&self) -> &NoteMap { &self.map }
+
+    /// Process the next `gamepad` state, returning any resulting messages.
+    ///
+    /// Pitch bend and continuous controller messages are only sent when their values change (including the first update.)
+    pub fn update(&mut self, gamepad: &Gamepad) -> Vec<Message> {
+        let mut messages = Vec::new();
+        let channel = self.map.channel;
+        let held    = gamepad.buttons;
+        let pressed = Buttons::from_unchecked(held.into_inner() & !self.buttons.into_inner());
+        let strummed = self.map.strum == Buttons::None || pressed.any_held(self.map.strum);
+
+        for (note, sounding) in self.map.notes.iter().zip(self.sounding.iter_mut()) {
+            let active = note.buttons != Buttons::None && held.all_held(note.buttons);
+            let retrigger = self.map.strum != Buttons::None && strummed;
+            if *sounding && (!active || retrigger) {
+                messages.push(Message::NoteOff { channel, note: note.note, velocity: 0 });
+                *sounding = false;
+            }
+            if !*sounding && active && strummed {
+                messages.push(Message::NoteOn { channel, note: note.note, velocity: note.velocity.read(gamepad) });
+                *sounding = true;
+            }
+        }
+
+        if let Some(bend) = self.map.pitch_bend.as_ref() {
+            let value = Message::PITCH_BEND_CENTER - (bend.amount(gamepad) * f32::from(Message::PITCH_BEND_CENTER)).round() as u16;
+            if self.pitch_bend != Some(value) {
+                self.pitch_bend = Some(value);
+                messages.push(Message::PitchBend { channel, value });
+            }
+        }
+
+        for (cc, prev) in self.map.ccs.iter().zip(self.ccs.iter_mut()) {
+            let value = (cc.control.amount(gamepad) * 127.0).round() as u8;
+            if *prev != Some(value) {
+                *prev = Some(value);
+                messages.push(Message::ControlChange { channel, controller: cc.controller, value });
+            }
+        }
+
+        self.buttons = held;
+        messages
+    }
+
+    /// Process the next `gamepad` state, appending any resulting raw MIDI bytes to `out`.
+    pub fn update_bytes(&mut self, gamepad: &Gamepad, out: &mut Vec<u8>) {
+        for message in self.update(gamepad) { out.extend_from_slice(&message.to_bytes()) }
+    }
+
+    /// Stop all sounding notes (e.g. when the controller disconnects), returning the [`Message::NoteOff`]s required.
+    pub fn release_all(&mut self) -> Vec<Message> {
+        let channel = self.map.channel;
+        let mut messages = Vec::new();
+        for (note, sounding) in self.map.notes.iter().zip(self.sounding.iter_mut()) {
+            if std::mem::take(sounding) { messages.push(Message::NoteOff { channel, note: note.note, velocity: 0 }) }
+        }
+        self.buttons = Buttons::None;
+        messages
+    }
+}
+
+
+
+#[test] fn test_to_bytes() {
+    assert_eq!([0x99, 38, 100],     Message::NoteOn        { channel: 9, note: 38, velocity: 100 }.to_bytes());
+    assert_eq!([0x80, 40, 0],       Message::NoteOff       { channel: 0, note: 40, velocity: 0 }.to_bytes());
+    assert_eq!([0xB1, 1, 127],      Message::ControlChange { channel: 1, controller: 1, value: 127 }.to_bytes());
+    assert_eq!([0xE0, 0x00, 0x40],  Message::PitchBend     { channel: 0, value: Message::PITCH_BEND_CENTER }.to_bytes());
+    assert_eq!([0xE2, 0x7F, 0x7F],  Message::PitchBend     { channel: 2, value: 16383 }.to_bytes());
+}
+
+#[test] fn test_drum_kit() {
+    let mut c = Converter::new(NoteMap::drum_kit());
+    let pads = |buttons| Gamepad { buttons, .. Gamepad::default() };
+    assert_eq!(c.update(&pads(Buttons::None)), []);
+    assert_eq!(c.update(&pads(Buttons::B | Buttons::LeftShoulder)), [
+        Message::NoteOn { channel: 9, note: 38, velocity: 100 },
+        Message::NoteOn { channel: 9, note: 36, velocity: 100 },
+    ]);
+    assert_eq!(c.update(&pads(Buttons::B | Buttons::LeftShoulder)), []);
+    assert_eq!(c.update(&pads(Buttons::LeftShoulder)), [Message::NoteOff { channel: 9, note: 38, velocity: 0 }]);
+    assert_eq!(c.release_all(), [Message::NoteOff { channel: 9, note: 36, velocity: 0 }]);
+    assert_eq!(c.release_all(), []);
+
+    let mut bytes = Vec::new();
+    c.update_bytes(&pads(Buttons::Y), &mut bytes);
+    assert_eq!(bytes, [0x99, 42, 100]);
+}
+
+#[test] fn test_guitar() {
+    let mut c = Converter::new(NoteMap::guitar());
+    let rest = Gamepad { right_thumb_x: i16::MIN, .. Gamepad::default() };
+    assert_eq!(c.update(&rest), [
+        Message::PitchBend      { channel: 0, value: Message::PITCH_BEND_CENTER },
+        Message::ControlChange  { channel: 0, controller: 1, value: 0 },
+    ]);
+
+    let fret = Gamepad { buttons: Buttons::A | Buttons::Y, .. rest };
+    assert_eq!(c.update(&fret), [], "frets alone shouldn't sound without a strum");
+
+    let strum = Gamepad { buttons: Buttons::A | Buttons::Y | Buttons::DPadDown, .. rest };
+    assert_eq!(c.update(&strum), [
+        Message::NoteOn { channel: 0, note: 40, velocity: 100 },
+        Message::NoteOn { channel: 0, note: 50, velocity: 100 },
+    ]);
+    assert_eq!(c.update(&fret), []);
+    assert_eq!(c.update(&strum), [
+        Message::NoteOff { channel: 0, note: 40, velocity: 0 },
+        Message::NoteOn  { channel: 0, note: 40, velocity: 100 },
+        Message::NoteOff { channel: 0, note: 50, velocity: 0 },
+        Message::NoteOn  { channel: 0, note: 50, velocity: 100 },
+    ]);
+
+    let whammy = Gamepad { buttons: Buttons::A, right_thumb_x: i16::MAX, right_thumb_y: i16::MAX, .. rest };
+    assert_eq!(c.update(&whammy), [
+        Message::NoteOff        { channel: 0, note: 50, velocity: 0 },
+        Message::PitchBend      { channel: 0, value: 0 },
+        Message::ControlChange  { channel: 0, controller: 1, value: 127 },
+    ]);
+}
+
+#[test] fn test_for_sub_type() {
+    assert_eq!(NoteMap::for_sub_type(DevSubType::DrumKit),          Some(NoteMap::drum_kit()));
+    assert_eq!(NoteMap::for_sub_type(DevSubType::GuitarAlternate),  Some(NoteMap::guitar()));
+    assert_eq!(NoteMap::for_sub_type(DevSubType::GuitarBass),       Some(NoteMap::bass()));
+    assert_eq!(NoteMap::for_sub_type(DevSubType::Gamepad),          None);
+}
@@ -0,0 +1,360 @@
+//! Fighting game style command inputs (`236P`, `623K`, `[4]6P`, `360P`, ...) using [numpad notation](Direction8).
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::motion::*;
+//!
+//! let mut buffer = CommandBuffer::new();
+//! buffer.add("fireball",  "236P".parse().unwrap());
+//! buffer.add("uppercut",  "623P".parse().unwrap());
+//! buffer.add("super",     Motion::parse("236236P").unwrap().with_priority(1));
+//!
+//! # for _ in 0 .. 1 {
+//! // once per game frame:
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! if let Some(command) = buffer.push(&state) {
+//!     println!("{command}!");
+//! }
+//! # }
+//! ```
+
+use crate::*;
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+
+
+/// Buttons matched by `P` in [`Motion::parse`]: the punches of an [`ArcadeStickState`].
+pub const PUNCHES : Buttons = Buttons::from_unchecked(Buttons::X.into_inner() | Buttons::Y.into_inner() | Buttons::RightShoulder.into_inner() | Buttons::LeftShoulder.into_inner());
+
+/// Buttons matched by `K` in [`Motion::parse`]: the (non-trigger) kicks of an [`ArcadeStickState`].
+pub const KICKS : Buttons = Buttons::from_unchecked(Buttons::A.into_inner() | Buttons::B.into_inner());
+
+/// A single step of a [`Motion`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Press a direction (e.g. `2`.)
+    Direction(Direction8),
+
+    /// Hold a direction for at least [`Leniency::charge`] frames (e.g. `[4]`.)
+    /// Charging a cardinal direction also accepts the adjacent diagonals (e.g. `[4]` accepts `1` and `7`.)
+    Charge(Direction8),
+
+    /// Touch all four cardinal directions (`360`), in any order, within [`Leniency::motion`] frames.
+    /// Diagonals count towards both of their cardinal directions.
+    Rotation,
+}
+
+/// A command input: a sequence of [`Step`]s, followed by pressing any of a set of [`Buttons`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Motion {
+    /// The directional steps, in order.
+    pub steps:      Vec<Step>,
+
+    /// Pressing any of these buttons completes the motion.
+    pub buttons:    Buttons,
+
+    /// When several motions complete on the same frame, the highest priority wins (ties go to the motion with more steps.)
+    pub priority:   i32,
+}
+
+/// An error parsing [`Motion`] notation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MotionParseError {
+    notation:   String,
+    message:    &'static str,
+}
+
+impl Display for MotionParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { write!(f, "invalid motion {:?}: {}", self.notation, self.message) }
+}
+
+impl std::error::Error for MotionParseError {}
+
+impl Motion {
+    /// Create a motion from steps and buttons, with priority `0`.
+    pub fn new(steps: impl Into<Vec<Step>>, buttons: Buttons) -> Self { Self { steps: steps.into(), buttons, priority: 0 } }
+
+    /// Set [`priority`](Self::priority).
+    pub fn with_priority(self, priority: i32) -> Self { Self { priority, .. self } }
+
+    /// Parse numpad notation.
+    ///
+    /// | Notation      | Meaning   |
+    /// | ------------- | --------- |
+    /// | `1` ..= `9`   | [`Step::Direction`]
+    /// | `[4]`         | [`Step::Charge`]
+    /// | `360`         | [`Step::Rotation`]
+    /// | `P`           | Any of [`PUNCHES`]
+    /// | `K`           | Any of [`KICKS`]
+    /// | `A` `B` `X` `Y` | That face button
+    ///
+    /// Directions must come before buttons, and at least one button is required.  Multiple buttons (e.g. `PK`) match any of them.
+    pub fn parse(notation: &str) -> Result<Self, MotionParseError> {
+        let err = |message| MotionParseError { notation: notation.into(), message };
+        let mut steps = Vec::new();
+        let mut buttons = Buttons::None;
+        let mut rest = notation.trim();
+
+        while let Some(c) = rest.chars().next() {
+            if let Some(r) = rest.strip_prefix("360") {
+                steps.push(Step::Rotation);
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('[') {
+                let (digit, r) = r.split_once(']').ok_or_else(|| err("unterminated `[`"))?;
+                let dir = digit.parse().ok().and_then(Direction8::from_numpad).filter(|d| d.is_held()).ok_or_else(|| err("expected a direction inside `[...]`"))?;
+                steps.push(Step::Charge(dir));
+                rest = r;
+            } else if let Some(dir) = c.to_digit(10).and_then(|d| Direction8::from_numpad(d as u8)) {
+                steps.push(Step::Direction(dir));
+                rest = &rest[1..];
+            } else {
+                break;
+            }
+            if steps.last() == Some(&Step::Direction(Direction8::Neutral)) { steps.pop(); }
+        }
+
+        for c in rest.chars() {
+            buttons |= match c {
+                'P' => PUNCHES,
+                'K' => KICKS,
+                'A' => Buttons::A,
+                'B' => Buttons::B,
+                'X' => Buttons::X,
+                'Y' => Buttons::Y,
+                _   => return Err(err("unexpected character (expected a direction or one of `PKABXY`)")),
+            };
+        }
+        if buttons == Buttons::None { return Err(err("expected a button")) }
+        Ok(Self::new(steps, buttons))
+    }
+}
+
+impl FromStr for Motion {
+    type Err = MotionParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> { Self::parse(s) }
+}
+
+/// How sloppy inputs may be, in frames (i.e. calls to [`CommandBuffer::push`].)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Leniency {
+    /// Maximum frames from the first to the last (non-charge) step of a motion.
+    pub motion: usize,
+
+    /// Maximum frames between consecutive steps.
+    pub step:   usize,
+
+    /// Maximum frames between the last step and the button press.
+    pub button: usize,
+
+    /// Minimum frames a [`Step::Charge`] direction must be held.
+    pub charge: usize,
+}
+
+impl Default for Leniency { fn default() -> Self { Self { motion: 20, step: 8, button: 8, charge: 45 } } }
+
+/// A single frame of input, as recorded by a [`CommandBuffer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Frame {
+    /// The direction held, relative to the player's facing (i.e. `6` is always "forward".)
+    pub direction:  Direction8,
+
+    /// Buttons newly pressed this frame.
+    pub pressed:    Buttons,
+}
+
+/// Records [`Frame`]s of input, and matches them against named [`Motion`]s.
+#[derive(Clone, Debug)]
+pub struct CommandBuffer {
+    /// Timing windows.
+    pub leniency:       Leniency,
+
+    /// If `false`, directions are mirrored (so `4` is still "back".)
+    pub facing_right:   bool,
+
+    /// Quantizes the left thumbstick when the D-pad isn't held.
+    pub quantizer:      DirectionQuantizer,
+
+    commands:           Vec<(String, Motion)>,
+    frames:             VecDeque<Frame>,
+    held:               Buttons,
+    stick:              Direction8,
+}
+
+impl Default for CommandBuffer { fn default() -> Self { Self::new() } }
+
+impl CommandBuffer {
+    /// An empty buffer with default [`Leniency`], facing right.
+    pub fn new() -> Self {
+        Self {
+            leniency:       Leniency::default(),
+            facing_right:   true,
+            quantizer:      DirectionQuantizer::LEFT_THUMB,
+            commands:       Vec::new(),
+            frames:         VecDeque::new(),
+            held:           Buttons::None,
+            stick:          Direction8::Neutral,
+        }
+    }
+
+    /// Add a named command.
+    pub fn add(&mut self, name: &str, motion: Motion) { self.commands.push((name.into(), motion)) }
+
+    /// Recorded frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &Frame> { self.frames.iter() }
+
+    /// Forget all recorded frames (e.g. when a round ends.)
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.held = Buttons::None;
+        self.stick = Direction8::Neutral;
+    }
+
+    /// Record a frame of `gamepad` input (D-pad, or left thumbstick if the D-pad is neutral), returning the name of the command completed this frame, if any.
+    pub fn push(&mut self, gamepad: &Gamepad) -> Option<&str> {
+        self.stick = self.quantizer.quantize8(self.stick, gamepad.left_thumb_x, gamepad.left_thumb_y);
+        let dpad = Direction8::from_dpad(gamepad.buttons);
+        let direction = if dpad.is_held() { dpad } else { self.stick };
+        let direction = if self.facing_right { direction } else { direction.mirror_x() };
+        let pressed = Buttons::from_unchecked(gamepad.buttons.into_inner() & !self.held.into_inner());
+        self.held = gamepad.buttons;
+        self.push_frame(Frame { direction, pressed })
+    }
+
+    /// Record a frame of input, returning the name of the command completed this frame, if any.
+    pub fn push_frame(&mut self, frame: Frame) -> Option<&str> {
+        let l = &self.leniency;
+        let capacity = l.charge + l.motion + l.button + l.step + 1;
+        while self.frames.len() >= capacity { self.frames.pop_front(); }
+        self.frames.push_back(frame);
+
+        let frames = self.frames.make_contiguous();
+        self.commands.iter()
+            .filter(|(_, m)| matches(frames, m, l))
+            .max_by_key(|(_, m)| (m.priority, m.steps.len()))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+fn held(frame: &Frame, dir: Direction8) -> bool {
+    let ((x, y), (fx, fy)) = (dir.to_xy(), frame.direction.to_xy());
+    (x == 0 || x == fx) && (y == 0 || y == fy)
+}
+
+/// Match `motion` against `frames`, scanning backwards from the newest frame (which must press one of the motion's buttons.)
+fn matches(frames: &[Frame], motion: &Motion, l: &Leniency) -> bool {
+    let Some(last) = frames.len().checked_sub(1) else { return false };
+    if !frames[last].pressed.any_held(motion.buttons) { return false }
+
+    let mut end = last as isize;    // latest frame the next (earlier) step may occupy
+    let mut gap = l.button;         // how far before `end` that step may end
+    let mut first = None::<isize>;  // frame of the latest step, for the overall motion window
+
+    for step in motion.steps.iter().rev() {
+        let lo = (end - gap as isize).max(0);
+        let window_lo = first.map_or(0, |f| (f - l.motion as isize).max(0));
+        let found = match *step {
+            Step::Direction(dir) => (lo ..= end).rev().find(|&i| i >= window_lo && frames[i as usize].direction == dir),
+            Step::Charge(dir) => (lo ..= end).rev().find(|&i| held(&frames[i as usize], dir)).and_then(|i| {
+                let start = (0 ..= i).rev().take_while(|&j| held(&frames[j as usize], dir)).last()?;
+                (i - start + 1 >= l.charge as isize).then_some(start)
+            }),
+            Step::Rotation => (lo ..= end).rev().find(|&i| frames[i as usize].direction.is_held()).and_then(|i| {
+                let mut seen = [false; 4];
+                let stop = window_lo.max(i - l.motion as isize);
+                (stop ..= i).rev().find(|&j| {
+                    let (x, y) = frames[j as usize].direction.to_xy();
+                    if x != 0 { seen[(x < 0) as usize] = true }
+                    if y != 0 { seen[2 + (y < 0) as usize] = true }
+                    seen.iter().all(|&s| s)
+                })
+            }),
+        };
+        let Some(i) = found else { return false };
+        if !matches!(step, Step::Charge(_)) { first.get_or_insert(i); }
+        end = i - 1;
+        gap = l.step;
+    }
+    true
+}
+
+
+
+#[cfg(test)] fn pad(numpad: u8, buttons: Buttons) -> Gamepad {
+    Gamepad { buttons: Direction8::from_numpad(numpad).unwrap().to_dpad() | buttons, .. Gamepad::default() }
+}
+
+#[cfg(test)] fn run(buffer: &mut CommandBuffer, inputs: &[(u8, usize)], buttons: Buttons) -> Option<String> {
+    for &(numpad, frames) in inputs { for _ in 0 .. frames { assert_eq!(None, buffer.push(&pad(numpad, Buttons::None)).map(String::from)); } }
+    let numpad = inputs.last().map_or(5, |i| i.0);
+    let result = buffer.push(&pad(numpad, buttons)).map(String::from);
+    buffer.clear();
+    result
+}
+
+#[test] fn test_parse() {
+    assert_eq!(Ok(Motion::new([Step::Direction(Direction8::Down), Step::Direction(Direction8::DownRight), Step::Direction(Direction8::Right)], PUNCHES)), "236P".parse());
+    assert_eq!(Ok(Motion::new([Step::Charge(Direction8::Left), Step::Direction(Direction8::Right)], KICKS | Buttons::X)), Motion::parse("[4]6KX"));
+    assert_eq!(Ok(Motion::new([Step::Rotation], Buttons::Y)), Motion::parse("360Y"));
+    assert_eq!(Ok(Motion::new([], Buttons::A)), Motion::parse("5A"));
+    for bad in ["236", "236Q", "[4", "[5]6P", "[x]6P", "P6"] {
+        assert!(Motion::parse(bad).is_err(), "{bad:?} should fail to parse");
+    }
+    assert_eq!("invalid motion \"236\": expected a button", Motion::parse("236").unwrap_err().to_string());
+}
+
+#[test] fn test_quarter_circle() {
+    let mut b = CommandBuffer::new();
+    b.add("fireball", "236P".parse().unwrap());
+    b.add("uppercut", "623P".parse().unwrap());
+
+    assert_eq!(Some("fireball".into()), run(&mut b, &[(2, 2), (3, 2), (6, 2)], Buttons::X));
+    assert_eq!(Some("fireball".into()), run(&mut b, &[(2, 2), (3, 2), (6, 2), (5, 5)], Buttons::X), "button leniency");
+    assert_eq!(None,                    run(&mut b, &[(2, 2), (3, 2), (6, 2), (5, 9)], Buttons::X), "pressed too late");
+    assert_eq!(None,                    run(&mut b, &[(2, 2), (3, 2), (6, 2)], Buttons::A), "wrong button");
+    assert_eq!(None,                    run(&mut b, &[(2, 2), (5, 10), (3, 2), (6, 2)], Buttons::X), "too long between steps");
+    b.leniency.motion = 10;
+    assert_eq!(None,                    run(&mut b, &[(2, 6), (3, 7), (6, 6)], Buttons::X), "too slow overall");
+    b.leniency = Leniency::default();
+    assert_eq!(Some("uppercut".into()), run(&mut b, &[(6, 2), (3, 1), (2, 2), (3, 2)], Buttons::Y));
+
+    b.facing_right = false;
+    assert_eq!(Some("fireball".into()), run(&mut b, &[(2, 2), (1, 2), (4, 2)], Buttons::X), "mirrored");
+}
+
+#[test] fn test_priority() {
+    let mut b = CommandBuffer::new();
+    b.add("fireball",   "236P".parse().unwrap());
+    b.add("super",      "236236P".parse().unwrap());
+    b.add("low",        Motion::parse("2P").unwrap().with_priority(-1));
+    assert_eq!(Some("super".into()),    run(&mut b, &[(2, 1), (3, 1), (6, 1), (2, 1), (3, 1), (6, 1)], Buttons::X), "more steps wins ties");
+    assert_eq!(Some("fireball".into()), run(&mut b, &[(2, 1), (3, 1), (6, 1)], Buttons::X));
+    assert_eq!(Some("low".into()),      run(&mut b, &[(2, 1)], Buttons::X));
+}
+
+#[test] fn test_charge_and_rotation() {
+    let mut b = CommandBuffer::new();
+    b.add("sonic boom", "[4]6P".parse().unwrap());
+    b.add("spd",        "360P".parse().unwrap());
+    assert_eq!(Some("sonic boom".into()), run(&mut b, &[(4, 30), (1, 20), (6, 1)], Buttons::X));
+    assert_eq!(None,                      run(&mut b, &[(4, 10), (6, 1)], Buttons::X), "not charged long enough");
+    assert_eq!(None,                      run(&mut b, &[(4, 50), (5, 10), (6, 1)], Buttons::X), "charge released too early");
+    assert_eq!(Some("spd".into()),        run(&mut b, &[(6, 2), (3, 2), (2, 2), (1, 2), (4, 2), (7, 2), (8, 2)], Buttons::X));
+    assert_eq!(Some("spd".into()),        run(&mut b, &[(6, 2), (2, 2), (4, 2), (8, 2), (5, 3)], Buttons::X), "button leniency");
+    assert_eq!(None,                      run(&mut b, &[(6, 2), (2, 2), (4, 2)], Buttons::X), "only 270");
+}
+
+#[test] fn test_thumbstick() {
+    let mut b = CommandBuffer::new();
+    b.add("fireball", "236P".parse().unwrap());
+    let stick = |x: i16, y: i16, buttons| Gamepad { left_thumb_x: x, left_thumb_y: y, buttons, .. Gamepad::default() };
+    assert_eq!(None,                b.push(&stick(0, -30000, Buttons::None)));
+    assert_eq!(None,                b.push(&stick(21000, -21000, Buttons::None)));
+    assert_eq!(None,                b.push(&stick(30000, 0, Buttons::None)));
+    assert_eq!(Some("fireball"),    b.push(&stick(30000, 0, Buttons::Y)));
+    assert_eq!(None,                b.push(&stick(30000, 0, Buttons::Y)), "already held");
+    assert_eq!(5, b.frames().count());
+}
@@ -0,0 +1,268 @@
+//! Spatial UI focus navigation: move focus between rectangles with the D-pad, left thumbstick, or [`Keystroke`]s.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::nav::*;
+//! use std::time::Instant;
+//!
+//! // a 3x2 grid of buttons
+//! let buttons = (0 .. 6).map(|i| Rect::new((i % 3) as f32 * 110.0, (i / 3) as f32 * 50.0, 100.0, 40.0));
+//! let mut nav = Navigator::new(buttons);
+//! nav.wrap = Wrap::Horizontal;
+//!
+//! # for _ in 0 .. 1 {
+//! // either poll...
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! if let Some(focus) = nav.update(&state, Instant::now()) {
+//!     println!("focused button {focus}");
+//! }
+//!
+//! // ...or use keystrokes
+//! while let Ok(Some(keystroke)) = xinput::get_keystroke(0, ()) {
+//!     nav.keystroke(&keystroke);
+//! }
+//! # }
+//! ```
+
+use crate::*;
+
+use std::time::{Duration, Instant};
+
+
+
+/// A focusable rectangle, in screen coordinates (`+y` is down.)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    /// Left edge.
+    pub x:      f32,
+
+    /// Top edge.
+    pub y:      f32,
+
+    /// Width.
+    pub width:  f32,
+
+    /// Height.
+    pub height: f32,
+}
+
+impl Rect {
+    /// A rectangle with its top left corner at `(x, y)`.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self { Self { x, y, width, height } }
+
+    /// The center of the rectangle.
+    pub fn center(&self) -> (f32, f32) { (self.x + self.width / 2.0, self.y + self.height / 2.0) }
+}
+
+/// Which directions focus wraps around in, when moving past the last item.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Wrap {
+    /// Focus stops at the edges.
+    #[default] None,
+
+    /// Moving left/right past the end of a row wraps to the other end.
+    Horizontal,
+
+    /// Moving up/down past the end of a column wraps to the other end.
+    Vertical,
+
+    /// Both [`Wrap::Horizontal`] and [`Wrap::Vertical`].
+    Both,
+}
+
+impl Wrap {
+    fn wraps(self, dir: Direction4) -> bool {
+        match dir {
+            Direction4::Left | Direction4::Right    => matches!(self, Wrap::Horizontal | Wrap::Both),
+            Direction4::Up   | Direction4::Down     => matches!(self, Wrap::Vertical   | Wrap::Both),
+            Direction4::Neutral                     => false,
+        }
+    }
+}
+
+/// Repeat timing for held directions (when polling with [`Navigator::update`].)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Repeat {
+    /// How long a direction must be held before it starts repeating.
+    pub delay:      Duration,
+
+    /// Time between repeats.
+    pub interval:   Duration,
+}
+
+impl Default for Repeat { fn default() -> Self { Self { delay: Duration::from_millis(400), interval: Duration::from_millis(100) } } }
+
+/// Moves focus between [`Rect`]s.
+#[derive(Clone, Debug)]
+pub struct Navigator {
+    /// The focusable items.  [`Navigator::focus`] indexes into this.
+    pub items:      Vec<Rect>,
+
+    /// Wrap-around rules.
+    pub wrap:       Wrap,
+
+    /// Repeat timing for [`Navigator::update`].
+    pub repeat:     Repeat,
+
+    /// Quantizes the left thumbstick for [`Navigator::update`].
+    pub quantizer:  DirectionQuantizer,
+
+    focus:          Option<usize>,
+    stick:          Direction4,
+    held:           Direction4,
+    next_repeat:    Option<Instant>,
+}
+
+impl Navigator {
+    /// Navigate between `items`, with nothing focused, no wrapping, default [`Repeat`] timing, and [`DirectionQuantizer::MENU`].
+    pub fn new(items: impl IntoIterator<Item = Rect>) -> Self {
+        Self {
+            items:          items.into_iter().collect(),
+            wrap:           Wrap::None,
+            repeat:         Repeat::default(),
+            quantizer:      DirectionQuantizer::MENU,
+            focus:          None,
+            stick:          Direction4::Neutral,
+            held:           Direction4::Neutral,
+            next_repeat:    None,
+        }
+    }
+
+    /// The focused item, if any.
+    pub fn focus(&self) -> Option<usize> { self.focus.filter(|&f| f < self.items.len()) }
+
+    /// Focus `item` (or nothing.)
+    pub fn set_focus(&mut self, item: Option<usize>) { self.focus = item }
+
+    /// Move focus in `dir`, returning the newly focused item if focus changed.
+    /// If nothing is focused, the first item is focused (in any direction.)
+    pub fn navigate(&mut self, dir: Direction4) -> Option<usize> {
+        if !dir.is_held() || self.items.is_empty() { return None }
+        let Some(from) = self.focus() else {
+            self.focus = Some(0);
+            return self.focus;
+        };
+
+        // distances along, and across, the direction of travel
+        let (dx, dy) = match dir { Direction4::Left => (-1.0, 0.0), Direction4::Right => (1.0, 0.0), Direction4::Up => (0.0, -1.0), _ => (0.0, 1.0) };
+        let (fx, fy) = self.items[from].center();
+        let offsets = self.items.iter().enumerate().filter(|&(i, _)| i != from).map(|(i, r)| {
+            let (x, y) = r.center();
+            let (x, y) = (x - fx, y - fy);
+            (i, x * dx + y * dy, (x * dy - y * dx).abs())
+        }).collect::<Vec<_>>();
+
+        let ahead = offsets.iter().filter(|(_, along, across)| *along > 0.0 && *across <= *along * 2.0).min_by(|a, b| (a.1 + 2.0 * a.2).total_cmp(&(b.1 + 2.0 * b.2)));
+        let wrapped = || offsets.iter().filter(|(_, along, _)| *along < 0.0).min_by(|a, b| (4.0 * a.2 + a.1).total_cmp(&(4.0 * b.2 + b.1)));
+        let to = ahead.or_else(|| if self.wrap.wraps(dir) { wrapped() } else { None })?.0;
+        self.focus = Some(to);
+        Some(to)
+    }
+
+    /// Feed the next polled `gamepad` state (D-pad, or left thumbstick if the D-pad is neutral), sampled at `timestamp`,
+    /// returning the newly focused item if focus changed.
+    pub fn update(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Option<usize> {
+        self.stick = self.quantizer.quantize4(self.stick, gamepad.left_thumb_x, gamepad.left_thumb_y);
+        let dpad = Direction4::from_dpad(gamepad.buttons);
+        let dir = if dpad.is_held() { dpad } else { self.stick };
+
+        if dir != self.held {
+            self.held = dir;
+            self.next_repeat = Some(timestamp + self.repeat.delay);
+            return self.navigate(dir);
+        }
+        let next = self.next_repeat?;
+        if timestamp < next { return None }
+        let late = timestamp.saturating_duration_since(next) >= self.repeat.interval;
+        self.next_repeat = Some(if late { timestamp } else { next } + self.repeat.interval);
+        self.navigate(dir)
+    }
+
+    /// Feed a [`Keystroke`] (D-pad or left thumbstick key down / repeat), returning the newly focused item if focus changed.
+    /// XInput generates its own repeats, so [`repeat`](Self::repeat) isn't used.
+    pub fn keystroke(&mut self, keystroke: &Keystroke) -> Option<usize> {
+        if keystroke.flags.into_inner() & (Keystroke::KeyDown.into_inner() | Keystroke::Repeat.into_inner()) == 0 { return None }
+        let dir = match keystroke.virtual_key {
+            VK::PadDPadUp       => Direction4::Up,
+            VK::PadDPadDown     => Direction4::Down,
+            VK::PadDPadLeft     => Direction4::Left,
+            VK::PadDPadRight    => Direction4::Right,
+            vk => match Direction4::from_vk(vk) {
+                Some((ThumbStick::Left, dir))   => dir,
+                _                               => return None,
+            },
+        };
+        self.navigate(dir)
+    }
+}
+
+
+
+#[cfg(test)] fn grid() -> Navigator {
+    // 0 1 2
+    // 3 4 5
+    //  6 7     (offset half a column)
+    let mut items = (0 .. 6).map(|i| Rect::new((i % 3) as f32 * 100.0, (i / 3) as f32 * 50.0, 90.0, 40.0)).collect::<Vec<_>>();
+    items.push(Rect::new( 50.0, 100.0, 90.0, 40.0));
+    items.push(Rect::new(150.0, 100.0, 90.0, 40.0));
+    Navigator::new(items)
+}
+
+#[test] fn test_navigate() {
+    use Direction4::*;
+    let mut nav = grid();
+    assert_eq!(None, nav.focus());
+    assert_eq!(Some(0), nav.navigate(Down), "first press focuses the first item");
+    assert_eq!(None, nav.navigate(Neutral));
+    for (dir, to) in [(Right, Some(1)), (Right, Some(2)), (Right, None), (Down, Some(5)), (Down, Some(7)), (Down, None), (Left, Some(6)), (Left, Some(3)), (Up, Some(0)), (Up, None)] {
+        assert_eq!(to, nav.navigate(dir), "{dir:?}");
+    }
+    assert_eq!(Some(0), nav.focus());
+
+    nav.wrap = Wrap::Horizontal;
+    assert_eq!(Some(2), nav.navigate(Left), "wrap to the end of the row");
+    assert_eq!(Some(0), nav.navigate(Right));
+    assert_eq!(None, nav.navigate(Up), "no vertical wrap");
+    nav.wrap = Wrap::Both;
+    assert_eq!(Some(3), nav.navigate(Up), "wrap to the bottom of the column");
+    assert_eq!(Some(6), nav.navigate(Down));
+
+    nav.items.clear();
+    assert_eq!(None, nav.focus());
+    assert_eq!(None, nav.navigate(Down));
+}
+
+#[test] fn test_update_repeat() {
+    let t0 = Instant::now();
+    let at = |ms| t0 + Duration::from_millis(ms);
+    let mut nav = Navigator::new((0 .. 10).map(|i| Rect::new(i as f32 * 10.0, 0.0, 10.0, 10.0)));
+    nav.set_focus(Some(0));
+    let right = Gamepad { buttons: Buttons::DPadRight, .. Gamepad::default() };
+    let stick = Gamepad { left_thumb_x: 30000, .. Gamepad::default() };
+    let idle = Gamepad::default();
+
+    assert_eq!(Some(1), nav.update(&right, at(0)));
+    assert_eq!(None,    nav.update(&right, at(100)));
+    assert_eq!(None,    nav.update(&right, at(399)));
+    assert_eq!(Some(2), nav.update(&right, at(400)), "repeat after delay");
+    assert_eq!(None,    nav.update(&right, at(450)));
+    assert_eq!(Some(3), nav.update(&right, at(500)), "repeat interval");
+    assert_eq!(None,    nav.update(&idle,  at(510)));
+    assert_eq!(Some(4), nav.update(&stick, at(520)), "left stick");
+    assert_eq!(None,    nav.update(&stick, at(600)));
+    assert_eq!(None,    nav.update(&right, at(610)), "switching source while holding the same direction doesn't re-trigger");
+    assert_eq!(Some(5), nav.update(&right, at(920)));
+}
+
+#[test] fn test_keystroke() {
+    let mut nav = grid();
+    nav.set_focus(Some(4));
+    let key = |virtual_key, flags| Keystroke { virtual_key, flags, .. Keystroke::default() };
+    assert_eq!(Some(5), nav.keystroke(&key(VK::PadDPadRight,  Keystroke::KeyDown)));
+    assert_eq!(None,    nav.keystroke(&key(VK::PadDPadLeft,   Keystroke::KeyUp)));
+    assert_eq!(Some(4), nav.keystroke(&key(VK::PadLThumbLeft, Keystroke::KeyDown | Keystroke::Repeat)));
+    assert_eq!(None,    nav.keystroke(&key(VK::PadRThumbLeft, Keystroke::KeyDown)), "right stick");
+    assert_eq!(None,    nav.keystroke(&key(VK::PadA,          Keystroke::KeyDown)));
+    assert_eq!(Some(4), nav.focus());
+}
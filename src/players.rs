@@ -0,0 +1,247 @@
+//! "Press A to join" local multiplayer: stable logical players on top of XInput's `user_index`es.
+//!
+//! Windows assigns `user_index`es, and a controller that disconnects and reconnects may come back with a different one.
+//! [`PlayerSlots`] keeps logical players stable across reconnects: a disconnected player is reclaimed by a controller
+//! connecting on their old `user_index`, then by a controller with matching [`DeviceInfo`], then by the next controller to press the join button.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::players::*;
+//!
+//! let mut players = PlayerSlots::new(4);
+//! # for _ in 0 .. 1 {
+//! for event in players.update() {
+//!     match event {
+//!         Event::Joined       { player, .. } => println!("player {} joined", player + 1),
+//!         Event::Disconnected { player }     => println!("player {} controller disconnected", player + 1),
+//!         _                                  => {},
+//!     }
+//! }
+//! let paused = players.disconnected().next().is_some();
+//! # }
+//! ```
+
+use crate::*;
+use crate::profile::DeviceInfo;
+
+
+
+/// A connected controller, as seen by [`PlayerSlots::update_with`].
+#[derive(Clone, Copy, Debug)]
+pub struct Connection {
+    /// The controller's current state.
+    pub gamepad:    Gamepad,
+
+    /// The controller's device info, if known, for matching reconnecting controllers to disconnected players.
+    pub device:     Option<DeviceInfo>,
+}
+
+/// The state of a logical player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Slot {
+    /// Nobody has joined as this player.
+    #[default] Empty,
+
+    /// This player is playing on `user_index`.
+    Connected(u32),
+
+    /// This player's controller disconnected (from `user_index`.)  The game should probably pause.
+    Disconnected(u32),
+}
+
+/// A change reported by [`PlayerSlots::update`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// A controller pressed the join button and became a new player.
+    Joined {
+        /// The new player.
+        player:     usize,
+
+        /// The new player's controller.
+        user_index: u32,
+    },
+
+    /// A player's controller disconnected.
+    Disconnected {
+        /// The player whose controller disconnected.
+        player:     usize,
+    },
+
+    /// A controller reclaimed a disconnected player.
+    Reconnected {
+        /// The reclaimed player.
+        player:     usize,
+
+        /// The player's new controller.
+        user_index: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Player {
+    slot:       Slot,
+    device:     Option<DeviceInfo>,
+}
+
+/// Assigns controllers to logical players.  See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct PlayerSlots {
+    /// Pressing any of these buttons joins the game (or reclaims a disconnected player.)
+    pub join:   Buttons,
+
+    players:    Vec<Player>,
+    held:       Vec<Buttons>,
+    devices:    Vec<Option<DeviceInfo>>,
+}
+
+impl PlayerSlots {
+    /// Up to `max_players` players, joining with [`Buttons::A`] or [`Buttons::Start`].
+    pub fn new(max_players: usize) -> Self {
+        Self { join: Buttons::A | Buttons::Start, players: vec![Player::default(); max_players], held: Vec::new(), devices: Vec::new() }
+    }
+
+    /// The maximum number of players.
+    pub fn max_players(&self) -> usize { self.players.len() }
+
+    /// The state of `player` ([`Slot::Empty`] if out of range.)
+    pub fn slot(&self, player: usize) -> Slot { self.players.get(player).map_or(Slot::Empty, |p| p.slot) }
+
+    /// The `user_index` `player` is currently playing on, if connected.
+    pub fn user_index(&self, player: usize) -> Option<u32> { match self.slot(player) { Slot::Connected(u) => Some(u), _ => None } }
+
+    /// The player playing on `user_index`, if any.
+    pub fn player(&self, user_index: u32) -> Option<usize> { self.players.iter().position(|p| p.slot == Slot::Connected(user_index)) }
+
+    /// Players whose controllers are disconnected (e.g. for a "reconnect player 2's controller" pause screen.)
+    pub fn disconnected(&self) -> impl Iterator<Item = usize> + '_ { self.players.iter().enumerate().filter(|(_, p)| matches!(p.slot, Slot::Disconnected(_))).map(|(i, _)| i) }
+
+    /// Remove `player` from the game, returning `false` if they hadn't joined.
+    pub fn leave(&mut self, player: usize) -> bool {
+        let Some(p) = self.players.get_mut(player) else { return false };
+        let joined = p.slot != Slot::Empty;
+        *p = Player::default();
+        joined
+    }
+
+    /// Swap players `a` and `b` (e.g. so two people can trade controllers.)
+    ///
+    /// ### Panics
+    /// If `a` or `b` is out of range.
+    pub fn swap(&mut self, a: usize, b: usize) { self.players.swap(a, b) }
+
+    /// [`get_state`] every `user_index`, and [`update_with`](Self::update_with) the results.
+    /// [`get_capabilities`] is queried once per connection, for [`DeviceInfo`].
+    pub fn update(&mut self) -> Vec<Event> {
+        let users = xuser::iter().map(|user| {
+            let gamepad = get_state(user).ok()?.gamepad;
+            let cached = self.devices.get(user as usize).copied().flatten();
+            let device = cached.or_else(|| get_capabilities(user, Flag::None).ok().map(|caps| DeviceInfo::from(&caps)));
+            Some(Connection { gamepad, device })
+        }).collect::<Vec<_>>();
+        self.devices = users.iter().map(|c| c.and_then(|c| c.device)).collect();
+        self.update_with(&users)
+    }
+
+    /// Update players from `users`, indexed by `user_index` ([`None`] if disconnected.)
+    pub fn update_with(&mut self, users: &[Option<Connection>]) -> Vec<Event> {
+        let mut events = Vec::new();
+        let connected = |user: u32| users.get(user as usize).map_or(false, |c| c.is_some());
+
+        for (player, p) in self.players.iter_mut().enumerate() {
+            if let Slot::Connected(user) = p.slot {
+                if !connected(user) {
+                    p.slot = Slot::Disconnected(user);
+                    events.push(Event::Disconnected { player });
+                }
+            }
+        }
+
+        self.held.resize(users.len(), Buttons::None);
+        for (user, connection) in users.iter().enumerate() {
+            let user_index = user as u32;
+            let Some(connection) = connection else { self.held[user] = Buttons::None; continue };
+            let pressed = Buttons::from_unchecked(connection.gamepad.buttons.into_inner() & !self.held[user].into_inner());
+            self.held[user] = connection.gamepad.buttons;
+            if self.player(user_index).is_some() { continue }
+
+            let disconnected = |p: &Player| matches!(p.slot, Slot::Disconnected(_));
+            let by_slot   = self.players.iter().position(|p| p.slot == Slot::Disconnected(user_index));
+            let by_device = || self.players.iter().position(|p| disconnected(p) && p.device.is_some() && p.device == connection.device);
+            if let Some(player) = by_slot.or_else(by_device) {
+                self.players[player] = Player { slot: Slot::Connected(user_index), device: connection.device };
+                events.push(Event::Reconnected { player, user_index });
+            } else if pressed.any_held(self.join) {
+                if let Some(player) = self.players.iter().position(disconnected) {
+                    self.players[player] = Player { slot: Slot::Connected(user_index), device: connection.device };
+                    events.push(Event::Reconnected { player, user_index });
+                } else if let Some(player) = self.players.iter().position(|p| p.slot == Slot::Empty) {
+                    self.players[player] = Player { slot: Slot::Connected(user_index), device: connection.device };
+                    events.push(Event::Joined { player, user_index });
+                }
+            }
+        }
+
+        events
+    }
+}
+
+
+
+#[cfg(test)] fn connection(buttons: Buttons, sub_type: DevSubType) -> Option<Connection> {
+    Some(Connection { gamepad: Gamepad { buttons, .. Gamepad::default() }, device: Some(DeviceInfo { sub_type, vendor_id: None, product_id: None }) })
+}
+
+#[test] fn test_join_and_leave() {
+    let (pad, wheel) = (DevSubType::Gamepad, DevSubType::Wheel);
+    let mut players = PlayerSlots::new(2);
+    assert_eq!(Vec::<Event>::new(), players.update_with(&[connection(Buttons::None, pad), connection(Buttons::None, wheel)]));
+    assert_eq!(vec![Event::Joined { player: 0, user_index: 1 }], players.update_with(&[connection(Buttons::None, pad), connection(Buttons::A, wheel)]));
+    assert_eq!(Vec::<Event>::new(), players.update_with(&[connection(Buttons::None, pad), connection(Buttons::A, wheel)]), "already joined");
+    assert_eq!(vec![Event::Joined { player: 1, user_index: 0 }], players.update_with(&[connection(Buttons::Start, pad), connection(Buttons::None, wheel)]));
+    assert_eq!((Some(1), Some(1), None), (players.user_index(0), players.player(0), players.player(2)));
+
+    players.swap(0, 1);
+    assert_eq!((Slot::Connected(0), Slot::Connected(1)), (players.slot(0), players.slot(1)));
+    assert!(players.leave(0));
+    assert!(!players.leave(0));
+    assert!(!players.leave(9));
+    assert_eq!(Slot::Empty, players.slot(0));
+    assert_eq!(Vec::<Event>::new(), players.update_with(&[connection(Buttons::Start, pad), connection(Buttons::None, wheel)]), "held, not pressed");
+    assert_eq!(vec![Event::Joined { player: 0, user_index: 0 }], players.update_with(&[connection(Buttons::A, pad), connection(Buttons::None, wheel)]));
+}
+
+#[test] fn test_reconnect() {
+    let (pad, wheel) = (DevSubType::Gamepad, DevSubType::Wheel);
+    let mut players = PlayerSlots::new(4);
+    players.update_with(&[connection(Buttons::A, pad), connection(Buttons::A, wheel)]);
+    assert_eq!((Slot::Connected(0), Slot::Connected(1)), (players.slot(0), players.slot(1)));
+
+    // both disconnect
+    assert_eq!(vec![Event::Disconnected { player: 0 }, Event::Disconnected { player: 1 }], players.update_with(&[None, None]));
+    assert_eq!(vec![0, 1], players.disconnected().collect::<Vec<_>>());
+
+    // a wheel on user 0 reclaims player 0 by slot, even though player 0 had a gamepad
+    assert_eq!(vec![Event::Reconnected { player: 0, user_index: 0 }], players.update_with(&[connection(Buttons::None, wheel), None]));
+    players.leave(0);
+
+    // a wheel on user 2 reclaims player 1 by device
+    assert_eq!(vec![Event::Reconnected { player: 1, user_index: 2 }], players.update_with(&[None, None, connection(Buttons::None, wheel)]));
+    assert_eq!(Slot::Connected(2), players.slot(1));
+
+    // a new controller joins, taking the empty slot
+    players.update_with(&[None, None, connection(Buttons::None, wheel), connection(Buttons::None, pad)]);
+    assert_eq!(vec![Event::Joined { player: 0, user_index: 3 }], players.update_with(&[None, None, connection(Buttons::None, wheel), connection(Buttons::A, pad)]));
+    players.update_with(&[None, None, None, connection(Buttons::None, pad)]);
+    assert_eq!(vec![1], players.disconnected().collect::<Vec<_>>());
+
+    // an unrecognized controller pressing join reclaims a disconnected player before joining as a new one
+    assert_eq!(vec![Event::Reconnected { player: 1, user_index: 0 }], players.update_with(&[connection(Buttons::A, DevSubType::FlightStick), None, None, connection(Buttons::None, pad)]));
+}
+
+#[test] fn test_update_without_controllers() {
+    let _lock = backend::test_lock();
+    let mut players = PlayerSlots::new(4);
+    assert_eq!(Vec::<Event>::new(), players.update());
+    assert_eq!(4, players.max_players());
+}
@@ -0,0 +1,231 @@
+//! Mouse cursor emulation: thumbstick driven pointer movement, and buttons as mouse clicks.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::pointer::*;
+//! use std::time::Instant;
+//!
+//! let mut pointer = Pointer::new(ThumbStick::Left);
+//! let (mut x, mut y) = (960, 540);
+//! # for _ in 0 .. 1 {
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! for event in pointer.update(&state, Instant::now()) {
+//!     match event {
+//!         PointerEvent::Move { dx, dy }           => { x += dx; y += dy; },
+//!         PointerEvent::Down(MouseButton::Left)   => println!("click at ({x}, {y})"),
+//!         _                                       => {},
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::*;
+use crate::profile::DeadZone;
+
+use std::time::{Duration, Instant};
+
+
+
+/// An emulated mouse button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MouseButton {
+    /// The primary (usually left) mouse button.
+    Left,
+
+    /// The secondary (usually right) mouse button.
+    Right,
+
+    /// The middle mouse button.
+    Middle,
+}
+
+/// Output of [`Pointer::update`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerEvent {
+    /// Move the cursor by whole pixels (`+dy` is down, as in screen coordinates.)
+    Move {
+        /// Horizontal movement, in pixels.
+        dx: i32,
+
+        /// Vertical movement, in pixels (`+` = down.)
+        dy: i32,
+    },
+
+    /// A mouse button was pressed.
+    Down(MouseButton),
+
+    /// A mouse button was released.
+    Up(MouseButton),
+}
+
+/// Turns a thumbstick into cursor velocity, and buttons into mouse clicks.
+///
+/// Cursor speed is <code>[speed](Self::speed) × magnitude<sup>[exponent](Self::exponent)</sup></code> pixels per second (after the [dead zone](Self::deadzone)),
+/// ramping up towards <code>× [boost](Self::boost)</code> while the stick is held fully pushed for [`boost_time`](Self::boost_time),
+/// and scaled by [`precision_scale`](Self::precision_scale) while [`precision`](Self::precision) is held.
+/// Fractional pixels are accumulated between updates, so slow movement isn't lost to rounding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pointer {
+    /// The stick that moves the cursor.
+    pub stick:              ThumbStick,
+
+    /// Dead zone applied to [`stick`](Self::stick) before anything else.
+    pub deadzone:           DeadZone,
+
+    /// Pixels per second at full deflection (before [`boost`](Self::boost).)
+    pub speed:              f32,
+
+    /// Response exponent: `1.0` is linear, `> 1.0` gives finer control near the center.
+    pub exponent:           f32,
+
+    /// Speed multiplier reached after holding the stick fully pushed for [`boost_time`](Self::boost_time).  `1.0` disables boost.
+    pub boost:              f32,
+
+    /// Time to ramp up to full [`boost`](Self::boost).
+    pub boost_time:         Duration,
+
+    /// While any of these buttons are held, speed is scaled by [`precision_scale`](Self::precision_scale).
+    pub precision:          Buttons,
+
+    /// Speed multiplier while [`precision`](Self::precision) is held.
+    pub precision_scale:    f32,
+
+    /// Buttons emulating mouse buttons.
+    pub clicks:             Vec<(Buttons, MouseButton)>,
+
+    remainder:              (f32, f32),
+    prev:                   Option<Instant>,
+    pushed_since:           Option<Instant>,
+    held:                   Buttons,
+}
+
+/// Updates further apart than this are treated as this far apart, so the cursor doesn't jump after a stall.
+const MAX_DT : Duration = Duration::from_millis(100);
+
+impl Pointer {
+    /// A pointer driven by `stick` with its default dead zone, clicking with [`Buttons::A`] (left) and [`Buttons::B`] (right),
+    /// with [`Buttons::LeftShoulder`] for precision.
+    pub fn new(stick: ThumbStick) -> Self {
+        Self {
+            stick,
+            deadzone:           match stick { ThumbStick::Left => DeadZone::LEFT_THUMB, ThumbStick::Right => DeadZone::RIGHT_THUMB },
+            speed:              1200.0,
+            exponent:           2.0,
+            boost:              2.0,
+            boost_time:         Duration::from_millis(750),
+            precision:          Buttons::LeftShoulder,
+            precision_scale:    0.25,
+            clicks:             vec![(Buttons::A, MouseButton::Left), (Buttons::B, MouseButton::Right)],
+            remainder:          (0.0, 0.0),
+            prev:               None,
+            pushed_since:       None,
+            held:               Buttons::None,
+        }
+    }
+
+    /// Feed the next `gamepad` state, sampled at `timestamp`, returning cursor movement (if any) followed by mouse button events.
+    pub fn update(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Vec<PointerEvent> {
+        let mut events = Vec::new();
+        let dt = self.prev.replace(timestamp).map_or(Duration::ZERO, |prev| timestamp.saturating_duration_since(prev).min(MAX_DT));
+
+        let (x, y) = self.stick.read(gamepad);
+        let angle = Stick::from_i16(x, y).angle;
+        let (x, y) = self.deadzone.apply(x, y);
+        let magnitude = Stick::from_i16(x, y).magnitude.min(1.0);
+
+        let full = magnitude >= 0.95;
+        if !full { self.pushed_since = None }
+        let held_for = if full { timestamp.saturating_duration_since(*self.pushed_since.get_or_insert(timestamp)) } else { Duration::ZERO };
+        let ramp = if self.boost_time.is_zero() { 1.0 } else { (held_for.as_secs_f32() / self.boost_time.as_secs_f32()).min(1.0) };
+        let boost = 1.0 + (self.boost - 1.0) * ramp;
+        let precision = if gamepad.buttons.any_held(self.precision) { self.precision_scale } else { 1.0 };
+        let speed = self.speed * magnitude.powf(self.exponent) * boost * precision;
+        let (sin, cos) = angle.sin_cos();
+        let (vx, vy) = (cos * speed, -sin * speed);
+
+        if vx == 0.0 && vy == 0.0 {
+            self.remainder = (0.0, 0.0);
+        } else {
+            let (x, y) = (self.remainder.0 + vx * dt.as_secs_f32(), self.remainder.1 + vy * dt.as_secs_f32());
+            let (dx, dy) = (x.trunc(), y.trunc());
+            self.remainder = (x - dx, y - dy);
+            if dx != 0.0 || dy != 0.0 { events.push(PointerEvent::Move { dx: dx as i32, dy: dy as i32 }) }
+        }
+
+        let (prev, now) = (self.held, gamepad.buttons);
+        for &(buttons, mouse) in self.clicks.iter() {
+            match (prev.any_held(buttons), now.any_held(buttons)) {
+                (false, true)   => events.push(PointerEvent::Down(mouse)),
+                (true, false)   => events.push(PointerEvent::Up(mouse)),
+                _               => {},
+            }
+        }
+        self.held = now;
+
+        events
+    }
+
+    /// Forget all previous states, discarding any sub-pixel movement.
+    /// Buttons still held are reported as [`PointerEvent::Down`] again by the next update.
+    pub fn reset(&mut self) {
+        self.remainder = (0.0, 0.0);
+        self.prev = None;
+        self.pushed_since = None;
+        self.held = Buttons::None;
+    }
+}
+
+
+
+#[cfg(test)] fn moves(events: &[PointerEvent]) -> (i32, i32) {
+    events.iter().fold((0, 0), |(x, y), e| match e { PointerEvent::Move { dx, dy } => (x + dx, y + dy), _ => (x, y) })
+}
+
+#[test] fn test_speed_and_direction() {
+    let t0 = Instant::now();
+    let mut p = Pointer::new(ThumbStick::Right);
+    p.boost = 1.0;
+    let up = Gamepad { right_thumb_y: 32767, .. Gamepad::default() };
+    let mut total = (0, 0);
+    for frame in 0 ..= 100 {
+        let (x, y) = moves(&p.update(&up, t0 + Duration::from_millis(10 * frame)));
+        total = (total.0 + x, total.1 + y);
+    }
+    assert_eq!((0, -1200), total, "1s fully pushed up");
+
+    p.reset();
+    let slow = Gamepad { right_thumb_x: 12000, buttons: Buttons::LeftShoulder, .. Gamepad::default() };
+    let mut total = 0;
+    for frame in 0 ..= 100 {
+        let events = p.update(&slow, t0 + Duration::from_millis(10 * frame));
+        assert!(events.len() <= 1, "at most 1 pixel per frame: {events:?}");
+        total += moves(&events).0;
+    }
+    assert!((1 ..= 8).contains(&total), "sub-pixel movement accumulates: {total}");
+
+    p.reset();
+    let inside = Gamepad { right_thumb_x: 8000, .. Gamepad::default() };
+    assert_eq!(Vec::<PointerEvent>::new(), p.update(&inside, t0));
+    assert_eq!(Vec::<PointerEvent>::new(), p.update(&inside, t0 + Duration::from_secs(1)), "dead zone");
+}
+
+#[test] fn test_boost() {
+    let t0 = Instant::now();
+    let mut p = Pointer::new(ThumbStick::Left);
+    let left = Gamepad { left_thumb_x: -32768, .. Gamepad::default() };
+    let mut per_frame = Vec::new();
+    for frame in 0 ..= 100 { per_frame.push(moves(&p.update(&left, t0 + Duration::from_millis(10 * frame))).0) }
+    assert_eq!(-12, per_frame[1]);
+    assert_eq!(-24, per_frame[100], "full boost");
+    assert!((-23 ..= -13).contains(&per_frame[50]), "ramping up: {}", per_frame[50]);
+}
+
+#[test] fn test_clicks() {
+    let t0 = Instant::now();
+    let mut p = Pointer::new(ThumbStick::Left);
+    assert_eq!(vec![PointerEvent::Down(MouseButton::Left)], p.update(&Gamepad { buttons: Buttons::A, .. Gamepad::default() }, t0));
+    assert_eq!(Vec::<PointerEvent>::new(), p.update(&Gamepad { buttons: Buttons::A, .. Gamepad::default() }, t0));
+    assert_eq!(vec![PointerEvent::Up(MouseButton::Left), PointerEvent::Down(MouseButton::Right)], p.update(&Gamepad { buttons: Buttons::B | Buttons::X, .. Gamepad::default() }, t0));
+    assert_eq!(vec![PointerEvent::Up(MouseButton::Right)], p.update(&Gamepad::default(), t0));
+}
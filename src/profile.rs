@@ -0,0 +1,606 @@
+//! Per-device controller profiles: [`actions`] bindings, dead zones, trigger curves and vibration settings.
+//!
+//! With the `"toml"` feature, profiles can be saved and loaded as versioned TOML, with older versions migrated on load.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::profile::*;
+//!
+//! let mut profiles = ProfileSet::new();
+//! // ...profiles.push(Profile::from_toml(&std::fs::read_to_string("wheel.toml")?)?)...
+//!
+//! let device = xinput::get_capabilities(0, Flag::None).map(|caps| DeviceInfo::from(&caps)).unwrap_or_default();
+//! let profile = profiles.select(&device);
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! let gamepad = profile.apply(&state);
+//! ```
+
+use crate::*;
+use crate::actions::{ActionMap, Binding};
+
+use std::collections::BTreeMap;
+
+
+
+/// The current version of the TOML serialization.
+///
+/// | Version   | Changes   |
+/// | ---------:| --------- |
+/// | 1         | Initial version.  Dead zones were raw thumbstick magnitudes, vibration was a single `bool`.
+/// | 2         | `[left_stick]` / `[right_stick]` dead zones are normalized (`0.0 ..= 1.0`) with an outer dead zone, `[vibration]` has a strength.
+pub const VERSION : u32 = 2;
+
+/// A radial thumbstick dead zone, in normalized units (`1.0` = `32767`.)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeadZone {
+    /// Magnitudes below this read as centered.
+    pub inner:  f32,
+
+    /// Magnitudes above this read as fully pushed.
+    pub outer:  f32,
+}
+
+impl DeadZone {
+    /// No dead zone.
+    pub const NONE          : Self = Self { inner: 0.0, outer: 1.0 };
+
+    /// [`Gamepad::LEFT_THUMB_DEADZONE`]
+    pub const LEFT_THUMB    : Self = Self { inner: Gamepad::LEFT_THUMB_DEADZONE  as f32 / 32767.0, outer: 1.0 };
+
+    /// [`Gamepad::RIGHT_THUMB_DEADZONE`]
+    pub const RIGHT_THUMB   : Self = Self { inner: Gamepad::RIGHT_THUMB_DEADZONE as f32 / 32767.0, outer: 1.0 };
+
+    /// Apply this dead zone to raw thumbstick coordinates, rescaling `inner ..= outer` to `0.0 ..= 1.0`.
+    pub fn apply(&self, x: i16, y: i16) -> (i16, i16) {
+        let stick = Stick::from_i16(x, y);
+        if stick.magnitude <= self.inner { return (0, 0) }
+        let range = (self.outer - self.inner).max(f32::EPSILON);
+        Stick { magnitude: ((stick.magnitude - self.inner) / range).min(1.0), .. stick }.to_i16()
+    }
+}
+
+/// A response curve for an analog trigger.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriggerCurve {
+    /// Normalized values (`0.0 ..= 1.0`) below this read as released.
+    pub deadzone:   f32,
+
+    /// Normalized values (`0.0 ..= 1.0`) above this read as fully pulled.
+    pub saturation: f32,
+
+    /// Response exponent: `1.0` is linear, `> 1.0` gives more precision near the start of the pull.
+    pub exponent:   f32,
+}
+
+impl TriggerCurve {
+    /// Untouched trigger values.
+    pub const LINEAR    : Self = Self { deadzone: 0.0, saturation: 1.0, exponent: 1.0 };
+
+    /// Linear, with a dead zone of [`Gamepad::TRIGGER_THRESHOLD`].
+    pub const THRESHOLD : Self = Self { deadzone: Gamepad::TRIGGER_THRESHOLD as f32 / 255.0, saturation: 1.0, exponent: 1.0 };
+
+    /// Apply this curve to a raw trigger value.
+    pub fn apply(&self, value: u8) -> u8 {
+        let v = f32::from(value) / 255.0;
+        if v <= self.deadzone { return 0 }
+        let v = ((v - self.deadzone) / (self.saturation - self.deadzone).max(f32::EPSILON)).min(1.0);
+        (v.powf(self.exponent.max(0.0)) * 255.0).round() as u8
+    }
+}
+
+/// Vibration preferences.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VibrationSettings {
+    /// If `false`, all vibration is disabled.
+    pub enabled:    bool,
+
+    /// Multiplier for motor speeds (`0.0 ..= 1.0`.)
+    pub strength:   f32,
+}
+
+impl VibrationSettings {
+    /// Full strength vibration.
+    pub const FULL : Self = Self { enabled: true, strength: 1.0 };
+
+    /// Apply these settings to a [`Vibration`] about to be passed to [`set_state`].
+    pub fn apply(&self, vibration: Vibration) -> Vibration {
+        let scale = |speed: u16| if self.enabled { (f32::from(speed) * self.strength.clamp(0.0, 1.0)).round() as u16 } else { 0 };
+        Vibration { left_motor_speed: scale(vibration.left_motor_speed), right_motor_speed: scale(vibration.right_motor_speed) }
+    }
+}
+
+/// Identifies a connected device, for [`ProfileSet::select`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    /// The device subtype (from [`Capabilities::sub_type`].)
+    pub sub_type:   DevSubType,
+
+    /// The USB vendor ID, if known.  XInput doesn't expose this: obtain it from e.g. Raw Input or SetupAPI.
+    pub vendor_id:  Option<u16>,
+
+    /// The USB product ID, if known.  XInput doesn't expose this: obtain it from e.g. Raw Input or SetupAPI.
+    pub product_id: Option<u16>,
+}
+
+impl From<&Capabilities> for DeviceInfo {
+    fn from(caps: &Capabilities) -> Self { Self { sub_type: caps.sub_type, vendor_id: None, product_id: None } }
+}
+
+/// Which devices a [`Profile`] applies to.  [`None`] fields match anything.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DeviceMatch {
+    /// Only match devices of this subtype.
+    pub sub_type:   Option<DevSubType>,
+
+    /// Only match devices with this USB vendor ID.
+    pub vendor_id:  Option<u16>,
+
+    /// Only match devices with this USB product ID.
+    pub product_id: Option<u16>,
+}
+
+impl DeviceMatch {
+    /// Matches any device.
+    pub const ANY : Self = Self { sub_type: None, vendor_id: None, product_id: None };
+
+    /// If `device` matches, returns how specific the match is (higher is more specific.)
+    pub fn specificity(&self, device: &DeviceInfo) -> Option<u32> {
+        fn field<T: Copy + PartialEq>(want: Option<T>, have: Option<T>, weight: u32) -> Option<u32> {
+            match want {
+                None                            => Some(0),
+                Some(want) if have == Some(want) => Some(weight),
+                Some(_)                         => None,
+            }
+        }
+        Some(field(self.sub_type, Some(device.sub_type), 1)? + field(self.vendor_id, device.vendor_id, 2)? + field(self.product_id, device.product_id, 4)?)
+    }
+}
+
+/// A controller profile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    /// A user-facing name.
+    pub name:           String,
+
+    /// Which devices this profile applies to.
+    pub device:         DeviceMatch,
+
+    /// Binding overrides for named actions (see [`Profile::apply_bindings`].)
+    pub bindings:       BTreeMap<String, Vec<Binding>>,
+
+    /// Dead zone of the left thumbstick.
+    pub left_stick:     DeadZone,
+
+    /// Dead zone of the right thumbstick.
+    pub right_stick:    DeadZone,
+
+    /// Response curve of the left trigger.
+    pub left_trigger:   TriggerCurve,
+
+    /// Response curve of the right trigger.
+    pub right_trigger:  TriggerCurve,
+
+    /// Vibration preferences.
+    pub vibration:      VibrationSettings,
+}
+
+impl Default for Profile { fn default() -> Self { Self::default_for(DevSubType::Gamepad) } }
+
+impl Profile {
+    /// A sensible default profile for devices of `sub_type`, matching only that subtype.
+    ///
+    /// | Subtype                       | Sticks                    | Triggers                      |
+    /// | ----------------------------- | ------------------------- | ----------------------------- |
+    /// | [`DevSubType::Wheel`]         | [`DeadZone::NONE`]        | [`TriggerCurve::LINEAR`]      |
+    /// | [`DevSubType::FlightStick`]   | 5% dead zone              | [`TriggerCurve::LINEAR`]      |
+    /// | Everything else               | [`DeadZone::LEFT_THUMB`] / [`DeadZone::RIGHT_THUMB`] | [`TriggerCurve::THRESHOLD`] |
+    pub fn default_for(sub_type: DevSubType) -> Self {
+        let base = Self {
+            name:           sub_type_name(sub_type).unwrap_or("Unknown").into(),
+            device:         DeviceMatch { sub_type: Some(sub_type), .. DeviceMatch::ANY },
+            bindings:       BTreeMap::new(),
+            left_stick:     DeadZone::LEFT_THUMB,
+            right_stick:    DeadZone::RIGHT_THUMB,
+            left_trigger:   TriggerCurve::THRESHOLD,
+            right_trigger:  TriggerCurve::THRESHOLD,
+            vibration:      VibrationSettings::FULL,
+        };
+        match sub_type {
+            DevSubType::Wheel       => Self { left_stick: DeadZone::NONE, right_stick: DeadZone::NONE, left_trigger: TriggerCurve::LINEAR, right_trigger: TriggerCurve::LINEAR, .. base },
+            DevSubType::FlightStick => {
+                let stick = DeadZone { inner: 0.05, outer: 1.0 };
+                Self { left_stick: stick, right_stick: stick, left_trigger: TriggerCurve::LINEAR, right_trigger: TriggerCurve::LINEAR, .. base }
+            },
+            _ => base,
+        }
+    }
+
+    /// Replace the bindings of every action in `map` that this profile has bindings for.  Actions `map` doesn't declare are ignored.
+    pub fn apply_bindings(&self, map: &mut ActionMap) {
+        for (action, bindings) in self.bindings.iter() { map.rebind(action, bindings.iter().copied()); }
+    }
+
+    /// Apply dead zones and trigger curves to `gamepad`.
+    pub fn apply(&self, gamepad: &Gamepad) -> Gamepad {
+        let mut gamepad = *gamepad;
+        for (stick, deadzone) in [(ThumbStick::Left, self.left_stick), (ThumbStick::Right, self.right_stick)] {
+            let (x, y) = stick.read(&gamepad);
+            stick.write(&mut gamepad, deadzone.apply(x, y));
+        }
+        gamepad.left_trigger    = self.left_trigger .apply(gamepad.left_trigger);
+        gamepad.right_trigger   = self.right_trigger.apply(gamepad.right_trigger);
+        gamepad
+    }
+
+    /// Apply [`Profile::vibration`] settings to `vibration`.
+    pub fn apply_vibration(&self, vibration: Vibration) -> Vibration { self.vibration.apply(vibration) }
+}
+
+/// A collection of [`Profile`]s, from which the best match for a device can be selected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileSet {
+    profiles: Vec<Profile>,
+}
+
+impl ProfileSet {
+    /// An empty set.
+    pub fn new() -> Self { Self::default() }
+
+    /// Add a profile.
+    pub fn push(&mut self, profile: Profile) { self.profiles.push(profile) }
+
+    /// All profiles, in the order they were added.
+    pub fn profiles(&self) -> &[Profile] { &self.profiles }
+
+    /// Select the most specific profile matching `device` (product &gt; vendor &gt; subtype), preferring the first added on ties.
+    /// Falls back on <code>[Profile::default_for]\(device.sub_type\)</code> if no profiles match.
+    pub fn select(&self, device: &DeviceInfo) -> Profile {
+        let mut best = None;
+        for profile in self.profiles.iter() {
+            let Some(s) = profile.device.specificity(device) else { continue };
+            if best.map_or(true, |(b, _)| s > b) { best = Some((s, profile)) }
+        }
+        best.map_or_else(|| Profile::default_for(device.sub_type), |(_, p)| p.clone())
+    }
+}
+
+
+fn sub_type_name(sub_type: DevSubType) -> Option<&'static str> { DevSubType::NAMES.iter().find(|(_, s)| *s == sub_type).map(|(n, _)| *n) }
+
+
+
+/// An error loading a [`Profile`] with [`Profile::from_toml`].
+#[cfg(feature = "toml")] #[cfg_attr(doc_cfg, doc(cfg(feature = "toml")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProfileError {
+    /// The text isn't valid TOML.
+    Syntax(String),
+
+    /// The `version` key is missing, or newer than [`VERSION`].
+    UnsupportedVersion(Option<i64>),
+
+    /// A key has the wrong type or an unrecognized value.
+    Invalid {
+        /// The offending key (e.g. `"bindings.jump"`.)
+        key:        String,
+        /// What was wrong with it.
+        message:    String,
+    },
+}
+
+#[cfg(feature = "toml")] impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProfileError::Syntax(err)                   => write!(f, "invalid TOML: {err}"),
+            ProfileError::UnsupportedVersion(None)      => write!(f, "profile is missing a `version`"),
+            ProfileError::UnsupportedVersion(Some(v))   => write!(f, "unsupported profile version {v} (expected 1 ..= {VERSION})"),
+            ProfileError::Invalid { key, message }      => write!(f, "invalid `{key}`: {message}"),
+        }
+    }
+}
+
+#[cfg(feature = "toml")] impl std::error::Error for ProfileError {}
+
+#[cfg(feature = "toml")] #[cfg_attr(doc_cfg, doc(cfg(feature = "toml")))]
+impl Profile {
+    /// Serialize as TOML, at the current [`VERSION`].
+    ///
+    /// ```toml
+    /// version = 2
+    /// name = "Wheel"
+    ///
+    /// [device]
+    /// sub_type = "Wheel"
+    ///
+    /// [left_stick]
+    /// deadzone = 0.0
+    /// outer = 1.0
+    ///
+    /// # [right_stick], [left_trigger], [right_trigger], [vibration] ...
+    ///
+    /// [bindings]
+    /// jump = ["A", "LeftThumb:Up"]
+    /// fire = ["RightTrigger>30", "LeftShoulder+RightShoulder"]
+    /// pitch = [{ input = "LeftThumbY", invert = true, sensitivity = 0.5 }]
+    /// ```
+    pub fn to_toml(&self) -> String {
+        use toml::{Table, Value};
+        let table = |entries: &[(&str, Value)]| Value::Table(entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect());
+
+        let mut root = Table::new();
+        root.insert("version".into(), Value::Integer(VERSION.into()));
+        root.insert("name".into(), Value::String(self.name.clone()));
+
+        let mut device = Table::new();
+        if let Some(sub_type) = self.device.sub_type {
+            device.insert("sub_type".into(), sub_type_name(sub_type).map_or_else(|| Value::Integer(sub_type.into_inner().into()), |n| Value::String(n.into())));
+        }
+        if let Some(id) = self.device.vendor_id  { device.insert("vendor_id" .into(), Value::Integer(id.into())); }
+        if let Some(id) = self.device.product_id { device.insert("product_id".into(), Value::Integer(id.into())); }
+        root.insert("device".into(), Value::Table(device));
+
+        for (key, dz) in [("left_stick", self.left_stick), ("right_stick", self.right_stick)] {
+            root.insert(key.into(), table(&[("deadzone", float(dz.inner)), ("outer", float(dz.outer))]));
+        }
+        for (key, tc) in [("left_trigger", self.left_trigger), ("right_trigger", self.right_trigger)] {
+            root.insert(key.into(), table(&[("deadzone", float(tc.deadzone)), ("saturation", float(tc.saturation)), ("exponent", float(tc.exponent))]));
+        }
+        root.insert("vibration".into(), table(&[("enabled", Value::Boolean(self.vibration.enabled)), ("strength", float(self.vibration.strength))]));
+
+        let bindings = self.bindings.iter().map(|(action, bindings)| {
+            (action.clone(), Value::Array(bindings.iter().map(|b| {
+                let input = Value::String(input_to_string(&b.input));
+                if !b.invert && b.sensitivity == 1.0 { input } else { table(&[("input", input), ("invert", Value::Boolean(b.invert)), ("sensitivity", float(b.sensitivity))]) }
+            }).collect()))
+        }).collect();
+        root.insert("bindings".into(), Value::Table(bindings));
+
+        root.to_string()
+    }
+
+    /// Deserialize from TOML, migrating older versions.  Missing keys use the values of [`Profile::default_for`] the profile's subtype.
+    pub fn from_toml(text: &str) -> Result<Self, ProfileError> {
+        use toml::{Table, Value};
+        let mut root = text.parse::<Table>().map_err(|err| ProfileError::Syntax(err.to_string()))?;
+
+        let version = root.get("version").and_then(Value::as_integer);
+        match version {
+            Some(1)                                 => migrate_v1(&mut root)?,
+            Some(v) if v == i64::from(VERSION)      => {},
+            _                                       => return Err(ProfileError::UnsupportedVersion(version)),
+        }
+
+        let invalid = |key: &str, message: &str| ProfileError::Invalid { key: key.into(), message: message.into() };
+        let empty = Table::new();
+        let section = |key: &str| match root.get(key) {
+            None                    => Ok(&empty),
+            Some(Value::Table(t))   => Ok(t),
+            Some(_)                 => Err(invalid(key, "expected a table")),
+        };
+        let number = |t: &Table, section: &str, key: &str, default: f32| match t.get(key) {
+            None                    => Ok(default),
+            Some(Value::Float(f))   => Ok(*f as f32),
+            Some(Value::Integer(i)) => Ok(*i as f32),
+            Some(_)                 => Err(invalid(&format!("{section}.{key}"), "expected a number")),
+        };
+        let id = |t: &Table, key: &str| match t.get(key) {
+            None                    => Ok(None),
+            Some(Value::Integer(i)) => u16::try_from(*i).map(Some).map_err(|_| invalid(&format!("device.{key}"), "expected 0 ..= 65535")),
+            Some(_)                 => Err(invalid(&format!("device.{key}"), "expected an integer")),
+        };
+
+        let device = section("device")?;
+        let sub_type = match device.get("sub_type") {
+            None                    => None,
+            Some(Value::String(s))  => Some(DevSubType::NAMES.iter().find(|(n, _)| n == s).map(|(_, t)| *t).ok_or_else(|| invalid("device.sub_type", "unrecognized subtype"))?),
+            Some(Value::Integer(i)) => Some(u8::try_from(*i).map(DevSubType::from_unchecked).map_err(|_| invalid("device.sub_type", "expected 0 ..= 255"))?),
+            Some(_)                 => return Err(invalid("device.sub_type", "expected a string")),
+        };
+
+        let mut profile = Profile::default_for(sub_type.unwrap_or(DevSubType::Gamepad));
+        profile.device = DeviceMatch { sub_type, vendor_id: id(device, "vendor_id")?, product_id: id(device, "product_id")? };
+        match root.get("name") {
+            None                    => {},
+            Some(Value::String(s))  => profile.name = s.clone(),
+            Some(_)                 => return Err(invalid("name", "expected a string")),
+        }
+
+        for (key, dz) in [("left_stick", &mut profile.left_stick), ("right_stick", &mut profile.right_stick)] {
+            let t = section(key)?;
+            *dz = DeadZone { inner: number(t, key, "deadzone", dz.inner)?, outer: number(t, key, "outer", dz.outer)? };
+        }
+        for (key, tc) in [("left_trigger", &mut profile.left_trigger), ("right_trigger", &mut profile.right_trigger)] {
+            let t = section(key)?;
+            *tc = TriggerCurve { deadzone: number(t, key, "deadzone", tc.deadzone)?, saturation: number(t, key, "saturation", tc.saturation)?, exponent: number(t, key, "exponent", tc.exponent)? };
+        }
+        let vibration = section("vibration")?;
+        profile.vibration = VibrationSettings {
+            enabled:    match vibration.get("enabled") { None => profile.vibration.enabled, Some(Value::Boolean(b)) => *b, Some(_) => return Err(invalid("vibration.enabled", "expected a boolean")) },
+            strength:   number(vibration, "vibration", "strength", profile.vibration.strength)?,
+        };
+
+        for (action, bindings) in section("bindings")?.iter() {
+            let key = format!("bindings.{action}");
+            let Value::Array(bindings) = bindings else { return Err(invalid(&key, "expected an array")) };
+            let bindings = bindings.iter().map(|b| match b {
+                Value::String(s) => input_from_str(s).map(Binding::from).ok_or_else(|| invalid(&key, &format!("unrecognized input {s:?}"))),
+                Value::Table(t) => {
+                    let Some(Value::String(s)) = t.get("input") else { return Err(invalid(&key, "expected an `input` string")) };
+                    let input = input_from_str(s).ok_or_else(|| invalid(&key, &format!("unrecognized input {s:?}")))?;
+                    let invert = match t.get("invert") { None => false, Some(Value::Boolean(b)) => *b, Some(_) => return Err(invalid(&key, "expected `invert` to be a boolean")) };
+                    Ok(Binding { input, invert, sensitivity: number(t, &key, "sensitivity", 1.0)? })
+                },
+                _ => Err(invalid(&key, "expected a string or table")),
+            }).collect::<Result<Vec<_>, _>>()?;
+            profile.bindings.insert(action.clone(), bindings);
+        }
+
+        Ok(profile)
+    }
+}
+
+/// Version 1 &rarr; 2: normalize raw dead zones into `[left_stick]` / `[right_stick]`, and expand `vibration = bool` into a `[vibration]` table.
+#[cfg(feature = "toml")] fn migrate_v1(root: &mut toml::Table) -> Result<(), ProfileError> {
+    use toml::{Table, Value};
+    for (old, new) in [("left_deadzone", "left_stick"), ("right_deadzone", "right_stick")] {
+        let Some(raw) = root.remove(old) else { continue };
+        let raw = raw.as_integer().ok_or_else(|| ProfileError::Invalid { key: old.into(), message: "expected an integer".into() })? as f32;
+        root.insert(new.into(), Value::Table(Table::from_iter([("deadzone".to_string(), float(raw / 32767.0)), ("outer".to_string(), float(1.0))])));
+    }
+    if let Some(Value::Boolean(enabled)) = root.get("vibration").cloned() {
+        root.insert("vibration".into(), Value::Table(Table::from_iter([("enabled".to_string(), Value::Boolean(enabled)), ("strength".to_string(), float(1.0))])));
+    }
+    root.insert("version".into(), Value::Integer(2));
+    Ok(())
+}
+
+/// Avoid `0.23953670263290405`-style noise from widening `f32`s.
+#[cfg(feature = "toml")] fn float(v: f32) -> toml::Value { toml::Value::Float(v.to_string().parse().unwrap_or(v.into())) }
+
+#[cfg(feature = "toml")] fn named_buttons() -> impl Iterator<Item = (&'static str, Buttons)> { Buttons::NAMES.iter().copied().filter(|(_, b)| *b != Buttons::None) }
+
+/// e.g. `"A"`, `"LeftShoulder+RightShoulder"`, `"RightTrigger>30"`, `"LeftThumbY"`, `"LeftThumb:UpLeft"`
+///
+/// Lossless: no buttons are written as `"None"`, and unnamed button bits as hex (e.g. `"A+0x0800"`.)
+#[cfg(feature = "toml")] fn input_to_string(input: &actions::Input) -> String {
+    use actions::Input;
+    match *input {
+        Input::Buttons(Buttons::None)   => "None".into(),
+        Input::Buttons(buttons)         => {
+            let mut names = named_buttons().filter(|(_, b)| buttons.all_held(*b)).map(|(n, _)| n.to_string()).collect::<Vec<_>>();
+            let unnamed = named_buttons().fold(buttons.into_inner(), |bits, (_, b)| bits & !b.into_inner());
+            if unnamed != 0 { names.push(format!("0x{unnamed:04X}")) }
+            names.join("+")
+        },
+        Input::Trigger(axis, threshold) => format!("{axis:?}>{threshold}"),
+        Input::Axis(axis)               => format!("{axis:?}"),
+        Input::Direction(stick, dir)    => format!("{stick:?}Thumb:{dir:?}"),
+    }
+}
+
+#[cfg(feature = "toml")] fn input_from_str(s: &str) -> Option<actions::Input> {
+    use actions::Input;
+    let axis = |s: &str| Axis::ALL.into_iter().find(|a| format!("{a:?}") == s);
+    if let Some((stick, dir)) = s.split_once(':') {
+        let stick = ThumbStick::ALL.into_iter().find(|t| format!("{t:?}Thumb") == stick)?;
+        let dir = Direction8::ALL.into_iter().find(|d| format!("{d:?}") == dir)?;
+        Some(Input::Direction(stick, dir))
+    } else if let Some((a, threshold)) = s.split_once('>') {
+        Some(Input::Trigger(axis(a)?, threshold.trim().parse().ok()?))
+    } else if let Some(a) = axis(s) {
+        Some(Input::Axis(a))
+    } else if s.trim() == "None" {
+        Some(Input::Buttons(Buttons::None))
+    } else {
+        let mut buttons = Buttons::None;
+        for name in s.split('+').map(str::trim) {
+            buttons |= match name.strip_prefix("0x") {
+                Some(hex)   => Buttons::from_unchecked(u16::from_str_radix(hex, 16).ok()?),
+                None        => named_buttons().find(|(n, _)| *n == name)?.1,
+            };
+        }
+        Some(Input::Buttons(buttons))
+    }
+}
+
+
+
+#[test] fn test_apply() {
+    let profile = Profile::default();
+    let gamepad = profile.apply(&Gamepad { left_thumb_x: 7000, right_thumb_y: 32767, left_trigger: 20, right_trigger: 255, .. Gamepad::default() });
+    assert_eq!((gamepad.left_thumb_x, gamepad.right_thumb_y, gamepad.left_trigger, gamepad.right_trigger), (0, 32767, 0, 255));
+
+    let wheel = Profile::default_for(DevSubType::Wheel);
+    let gamepad = wheel.apply(&Gamepad { left_thumb_x: 700, left_trigger: 20, .. Gamepad::default() });
+    assert_eq!((gamepad.left_thumb_x, gamepad.left_trigger), (700, 20));
+
+    let curve = TriggerCurve { deadzone: 0.0, saturation: 0.5, exponent: 2.0 };
+    assert_eq!((0, 64, 255, 255), (curve.apply(0), curve.apply(64), curve.apply(128), curve.apply(255)));
+
+    let quiet = VibrationSettings { enabled: true, strength: 0.5 };
+    assert_eq!((32768, 0), { let v = quiet.apply(Vibration::from((65535, 0))); (v.left_motor_speed, v.right_motor_speed) });
+    let off = Profile { vibration: VibrationSettings { enabled: false, strength: 1.0 }, .. Profile::default() };
+    assert_eq!(0, off.apply_vibration(Vibration::from((65535, 65535))).left_motor_speed);
+}
+
+#[test] fn test_select() {
+    let mut set = ProfileSet::new();
+    set.push(Profile { name: "any".into(),     device: DeviceMatch::ANY, .. Profile::default() });
+    set.push(Profile { name: "vendor".into(),  device: DeviceMatch { vendor_id: Some(0x045E), .. DeviceMatch::ANY }, .. Profile::default() });
+    set.push(Profile { name: "product".into(), device: DeviceMatch { vendor_id: Some(0x045E), product_id: Some(0x028E), .. DeviceMatch::ANY }, .. Profile::default() });
+    set.push(Profile::default_for(DevSubType::Wheel));
+
+    let device = |sub_type, vendor_id, product_id| DeviceInfo { sub_type, vendor_id, product_id };
+    assert_eq!("any",     set.select(&device(DevSubType::Gamepad, None,         None        )).name);
+    assert_eq!("Wheel",   set.select(&device(DevSubType::Wheel,   None,         None        )).name);
+    assert_eq!("vendor",  set.select(&device(DevSubType::Wheel,   Some(0x045E), None        )).name);
+    assert_eq!("product", set.select(&device(DevSubType::Gamepad, Some(0x045E), Some(0x028E))).name);
+    assert_eq!("Wheel",   ProfileSet::new().select(&device(DevSubType::Wheel, None, None)).name);
+}
+
+#[test] fn test_apply_bindings() {
+    let mut map = ActionMap::new();
+    map.digital("jump", [Buttons::A]).digital("fire", [Buttons::X]);
+    let mut profile = Profile::default();
+    profile.bindings.insert("jump".into(), vec![Buttons::B.into()]);
+    profile.bindings.insert("undeclared".into(), vec![Buttons::Y.into()]);
+    profile.apply_bindings(&mut map);
+    assert_eq!(map.bindings("jump"), [Binding::from(Buttons::B)]);
+    assert_eq!(map.bindings("fire"), [Binding::from(Buttons::X)]);
+    assert_eq!(None, map.kind("undeclared"));
+}
+
+#[cfg(feature = "toml")] #[test] fn test_toml_round_trip() {
+    use actions::Input;
+    let mut profile = Profile::default_for(DevSubType::FlightStick);
+    profile.device.vendor_id = Some(0x044F);
+    profile.right_trigger = TriggerCurve { deadzone: 0.1, saturation: 0.9, exponent: 2.5 };
+    profile.vibration.strength = 0.25;
+    profile.bindings.insert("jump".into(), vec![Buttons::A.into(), Input::Direction(ThumbStick::Left, Direction8::UpLeft).into()]);
+    profile.bindings.insert("fire".into(), vec![Input::Trigger(Axis::RightTrigger, 30).into(), (Buttons::LeftShoulder | Buttons::RightShoulder).into()]);
+    profile.bindings.insert("pitch".into(), vec![Binding::from(Axis::LeftThumbY).inverted().with_sensitivity(0.5)]);
+
+    let text = profile.to_toml();
+    assert!(text.contains("version = 2"), "{text}");
+    assert!(text.contains(r#""LeftShoulder+RightShoulder""#), "{text}");
+    assert_eq!(Ok(&profile), Profile::from_toml(&text).as_ref(), "{text}");
+
+    // bindings that never fire are still preserved
+    let mut odd = Profile::default();
+    odd.bindings.insert("odd".into(), vec![
+        Buttons::None.into(),
+        (Buttons::A | Buttons::from_unchecked(0x0800)).into(),
+        Buttons::from_unchecked(0x0800).into(),
+        Input::Direction(ThumbStick::Right, Direction8::Neutral).into(),
+        Input::Trigger(Axis::LeftThumbX, 30).into(),
+    ]);
+    let text = odd.to_toml();
+    assert!(text.contains(r#""A+0x0800""#), "{text}");
+    assert_eq!(Ok(&odd), Profile::from_toml(&text).as_ref(), "{text}");
+
+    for sub_type in DevSubType::NAMES.iter().map(|(_, s)| *s).chain(Some(DevSubType::from_unchecked(0x42))) {
+        let profile = Profile::default_for(sub_type);
+        assert_eq!(Ok(&profile), Profile::from_toml(&profile.to_toml()).as_ref());
+    }
+}
+
+#[cfg(feature = "toml")] #[test] fn test_toml_migrate_and_errors() {
+    let v1 = "version = 1\nname = \"Old\"\nleft_deadzone = 16384\nvibration = false\n[device]\nsub_type = \"Wheel\"\n[bindings]\njump = [\"A\"]\n";
+    let profile = Profile::from_toml(v1).unwrap();
+    assert_eq!(profile.name, "Old");
+    assert_eq!(profile.device.sub_type, Some(DevSubType::Wheel));
+    assert!((profile.left_stick.inner - 0.5).abs() < 1e-4);
+    assert_eq!(profile.right_stick, DeadZone::NONE, "wheel default");
+    assert_eq!(profile.vibration, VibrationSettings { enabled: false, strength: 1.0 });
+    assert_eq!(profile.bindings["jump"], [Binding::from(Buttons::A)]);
+
+    assert_eq!(Err(ProfileError::UnsupportedVersion(None)),    Profile::from_toml("name = \"x\""));
+    assert_eq!(Err(ProfileError::UnsupportedVersion(Some(3))), Profile::from_toml("version = 3"));
+    assert!(matches!(Profile::from_toml("version = "), Err(ProfileError::Syntax(_))));
+    assert!(matches!(Profile::from_toml("version = 2\n[bindings]\njump = [\"Q\"]"), Err(ProfileError::Invalid { key, .. }) if key == "bindings.jump"));
+    assert!(matches!(Profile::from_toml("version = 2\n[device]\nvendor_id = 70000"), Err(ProfileError::Invalid { key, .. }) if key == "device.vendor_id"));
+    assert!(matches!(Profile::from_toml("version = 2\nleft_stick = 5"), Err(ProfileError::Invalid { key, .. }) if key == "left_stick"));
+    assert!(matches!(Profile::from_toml("version = 1\nleft_deadzone = 0.25"), Err(ProfileError::Invalid { key, .. }) if key == "left_deadzone"), "not silently a zero dead zone");
+    assert_eq!(Ok(Profile { name: "Gamepad".into(), device: DeviceMatch::ANY, .. Profile::default() }), Profile::from_toml("version = 2\nname = \"Gamepad\""));
+}
@@ -0,0 +1,252 @@
+//! Data-driven button and axis remapping, including one-handed layout presets.
+//!
+//! A [`Remap`] is a table of [`Rule`]s, applied to a [`Gamepad`] before game logic sees it.
+//! Each active rule *consumes* its source (and modifier), and drives its target instead.
+//! Axes targeted by active rules are overwritten with the sum of those rules' values.
+//! Buttons and axes not consumed or targeted by any active rule pass through unmodified.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::remap::*;
+//!
+//! // left hand only: hold LB to steer the camera with the left stick, and use the D-pad as face buttons
+//! let mut remap = Remap::one_handed_left(Buttons::LeftShoulder);
+//! remap.rules.push(Rule::new(Source::Button(Buttons::Back), Target::Button(Buttons::Start)));
+//!
+//! # for _ in 0 .. 1 {
+//! let state = xinput::get_state(0).unwrap_or_default();
+//! let gamepad = remap.apply(&state);
+//! # }
+//! ```
+
+use crate::*;
+use crate::transform::Transform;
+
+use std::time::Instant;
+
+
+
+/// What a [`Rule`] reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// `1.0` if any of these buttons are held, `0.0` otherwise.
+    Button(Buttons),
+
+    /// The normalized value of an axis (see [`Axis::read`].)
+    Axis(Axis),
+}
+
+impl Source {
+    /// Read the value of this source from `gamepad`.
+    pub fn value(self, gamepad: &Gamepad) -> f32 {
+        match self {
+            Source::Button(buttons) => if gamepad.buttons.any_held(buttons) { 1.0 } else { 0.0 },
+            Source::Axis(axis)      => axis.read(gamepad),
+        }
+    }
+}
+
+/// What a [`Rule`] writes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    /// Nothing: the source is consumed (i.e. unbound.)
+    None,
+
+    /// Hold these buttons while the source's value is at least `0.5`.
+    Button(Buttons),
+
+    /// Set an axis to the source's value, multiplied by a scale (summed with any other active rules targeting the same axis.)
+    /// Use a scale of `1.0` for axis to axis remaps, or e.g. `-1.0` to map a button to the negative end of a thumbstick axis.
+    Axis(Axis, f32),
+}
+
+/// A single remapping: while [`modifier`](Self::modifier) is held, [`source`](Self::source) drives [`target`](Self::target) instead of itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    /// What to read.
+    pub source:     Source,
+
+    /// What to write.
+    pub target:     Target,
+
+    /// All of these buttons must be held for this rule to apply.  [`Buttons::None`] applies the rule unconditionally.
+    pub modifier:   Buttons,
+}
+
+impl Rule {
+    /// An unconditional rule.
+    pub fn new(source: Source, target: Target) -> Self { Self { source, target, modifier: Buttons::None } }
+
+    /// Only apply this rule while `modifier` is held.
+    pub fn when(self, modifier: Buttons) -> Self { Self { modifier, .. self } }
+
+    /// Remap button(s) `from` to button(s) `to`.
+    pub fn button(from: Buttons, to: Buttons) -> Self { Self::new(Source::Button(from), Target::Button(to)) }
+
+    /// Remap axis `from` to axis `to`.
+    pub fn axis(from: Axis, to: Axis) -> Self { Self::new(Source::Axis(from), Target::Axis(to, 1.0)) }
+
+    /// Remap button(s) `from` to push axis `to` to `value` (normalized, see [`Axis::write`].)
+    pub fn button_to_axis(from: Buttons, to: Axis, value: f32) -> Self { Self::new(Source::Button(from), Target::Axis(to, value)) }
+}
+
+/// A table of [`Rule`]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Remap {
+    /// The rules, applied in order (although order only matters for readability: every rule reads the unmodified input.)
+    pub rules: Vec<Rule>,
+}
+
+/// A button and the button in the same position of the opposite cluster, for mirroring the face buttons onto the D-pad.
+const FACE_DPAD : [(Buttons, Buttons); 4] = [
+    (Buttons::A, Buttons::DPadDown),
+    (Buttons::B, Buttons::DPadRight),
+    (Buttons::X, Buttons::DPadLeft),
+    (Buttons::Y, Buttons::DPadUp),
+];
+
+/// Thumbstick axes and their counterpart on the other thumbstick.
+const STICKS : [(Axis, Axis); 2] = [
+    (Axis::LeftThumbX, Axis::RightThumbX),
+    (Axis::LeftThumbY, Axis::RightThumbY),
+];
+
+impl Remap {
+    /// A table of `rules`.
+    pub fn new(rules: impl Into<Vec<Rule>>) -> Self { Self { rules: rules.into() } }
+
+    /// Swap the face buttons with the D-pad ([`Buttons::A`] &harr; [`Buttons::DPadDown`], [`Buttons::Y`] &harr; [`Buttons::DPadUp`], etc.)
+    pub fn swap_face_dpad() -> Self {
+        Self::new(FACE_DPAD.iter().flat_map(|&(face, dpad)| [Rule::button(face, dpad), Rule::button(dpad, face)]).collect::<Vec<_>>())
+    }
+
+    /// Swap the left and right thumbsticks (and thumbstick clicks.)
+    pub fn swap_sticks() -> Self {
+        let mut rules = STICKS.iter().flat_map(|&(l, r)| [Rule::axis(l, r), Rule::axis(r, l)]).collect::<Vec<_>>();
+        rules.push(Rule::button(Buttons::LeftThumb, Buttons::RightThumb));
+        rules.push(Rule::button(Buttons::RightThumb, Buttons::LeftThumb));
+        Self::new(rules)
+    }
+
+    /// For playing with the left hand only: while `modifier` is held, the left stick drives the right stick, and the D-pad drives the face buttons.
+    /// The modifier itself is consumed.
+    pub fn one_handed_left(modifier: Buttons) -> Self {
+        let mut rules = STICKS.iter().map(|&(l, r)| Rule::axis(l, r).when(modifier)).collect::<Vec<_>>();
+        rules.extend(FACE_DPAD.iter().map(|&(face, dpad)| Rule::button(dpad, face).when(modifier)));
+        rules.push(Rule::button(Buttons::LeftThumb, Buttons::RightThumb).when(modifier));
+        Self::new(rules)
+    }
+
+    /// For playing with the right hand only: while `modifier` is held, the right stick drives the left stick, and the face buttons drive the D-pad.
+    /// The modifier itself is consumed.
+    pub fn one_handed_right(modifier: Buttons) -> Self {
+        let mut rules = STICKS.iter().map(|&(l, r)| Rule::axis(r, l).when(modifier)).collect::<Vec<_>>();
+        rules.extend(FACE_DPAD.iter().map(|&(face, dpad)| Rule::button(face, dpad).when(modifier)));
+        rules.push(Rule::button(Buttons::RightThumb, Buttons::LeftThumb).when(modifier));
+        Self::new(rules)
+    }
+
+    /// Apply all rules whose modifiers are held to `gamepad`.
+    pub fn apply(&self, gamepad: &Gamepad) -> Gamepad {
+        let active = || self.rules.iter().filter(|r| gamepad.buttons.all_held(r.modifier));
+
+        let mut consumed = 0u16;
+        let mut out = *gamepad;
+        for rule in active() {
+            consumed |= rule.modifier.into_inner();
+            match rule.source {
+                Source::Button(buttons) => consumed |= buttons.into_inner(),
+                Source::Axis(axis)      => axis.write_raw(&mut out, 0),
+            }
+        }
+        out.buttons = Buttons::from_unchecked(out.buttons.into_inner() & !consumed);
+
+        let mut axes = [None::<f32>; 6];
+        for rule in active() {
+            let value = rule.source.value(gamepad);
+            match rule.target {
+                Target::None                            => {},
+                Target::Button(b) if value >= 0.5       => out.buttons |= b,
+                Target::Button(_)                       => {},
+                Target::Axis(axis, scale)               => *axes[axis as usize].get_or_insert(0.0) += value * scale,
+            }
+        }
+        for (axis, value) in Axis::ALL.into_iter().zip(axes) {
+            if let Some(value) = value { axis.write(&mut out, value) }
+        }
+        out
+    }
+}
+
+impl Transform for Remap {
+    fn transform(&mut self, gamepad: &Gamepad, _timestamp: Instant) -> Gamepad { self.apply(gamepad) }
+    fn reset(&mut self) {}
+}
+
+
+
+#[cfg(test)] fn every_button() -> impl Iterator<Item = Buttons> { (0 .. 16).map(|bit| Buttons::from_unchecked(1 << bit)) }
+
+#[cfg(test)] fn press(buttons: Buttons) -> Gamepad { Gamepad { buttons, .. Gamepad::default() } }
+
+#[test] fn test_every_button_to_every_button() {
+    assert_eq!(Remap::default().apply(&press(Buttons::from_unchecked(!0))).buttons, Buttons::from_unchecked(!0));
+    for from in every_button() {
+        assert_eq!(from, Remap::default().apply(&press(from)).buttons, "identity");
+        for to in every_button() {
+            let remap = Remap::new([Rule::button(from, to)]);
+            assert_eq!(to, remap.apply(&press(from)).buttons, "{from:?} => {to:?}");
+            for other in every_button().filter(|&b| b != from) {
+                assert_eq!(other, remap.apply(&press(other)).buttons, "{from:?} => {to:?} shouldn't affect {other:?}");
+            }
+        }
+        assert_eq!(Buttons::None, Remap::new([Rule::new(Source::Button(from), Target::None)]).apply(&press(from)).buttons, "unbind {from:?}");
+    }
+}
+
+#[test] fn test_presets_for_every_button() {
+    let swap = Remap::swap_face_dpad();
+    let sticks = Remap::swap_sticks();
+    let lb = Buttons::LeftShoulder;
+    let left = Remap::one_handed_left(lb);
+    let right = Remap::one_handed_right(lb);
+    for b in every_button() {
+        let swapped = FACE_DPAD.iter().find_map(|&(f, d)| if f == b { Some(d) } else if d == b { Some(f) } else { None }).unwrap_or(b);
+        let sticked = if b == Buttons::LeftThumb { Buttons::RightThumb } else if b == Buttons::RightThumb { Buttons::LeftThumb } else { b };
+        assert_eq!(swapped, swap.apply(&press(b)).buttons, "swap_face_dpad {b:?}");
+        assert_eq!(sticked, sticks.apply(&press(b)).buttons, "swap_sticks {b:?}");
+
+        if b == lb { continue }
+        assert_eq!(b, left.apply(&press(b)).buttons, "one_handed_left without modifier {b:?}");
+        assert_eq!(b, right.apply(&press(b)).buttons, "one_handed_right without modifier {b:?}");
+        let l = FACE_DPAD.iter().find(|(_, d)| *d == b).map_or(if b == Buttons::LeftThumb { Buttons::RightThumb } else { b }, |(f, _)| *f);
+        let r = FACE_DPAD.iter().find(|(f, _)| *f == b).map_or(if b == Buttons::RightThumb { Buttons::LeftThumb } else { b }, |(_, d)| *d);
+        assert_eq!(l, left.apply(&press(b | lb)).buttons, "one_handed_left {b:?}");
+        assert_eq!(r, right.apply(&press(b | lb)).buttons, "one_handed_right {b:?}");
+    }
+}
+
+#[cfg(test)] fn axes(g: Gamepad) -> (i16, i16, i16, i16, u8, u8) { (g.left_thumb_x, g.left_thumb_y, g.right_thumb_x, g.right_thumb_y, g.left_trigger, g.right_trigger) }
+
+#[test] fn test_axes() {
+    let gamepad = Gamepad { left_thumb_x: 1000, left_thumb_y: -2000, right_thumb_x: 3000, right_thumb_y: 4000, left_trigger: 5, right_trigger: 6, .. Gamepad::default() };
+    let swapped = Remap::swap_sticks().apply(&gamepad);
+    assert_eq!((3000, 4000, 1000, -2000, 5, 6), axes(swapped));
+
+    let left = Remap::one_handed_left(Buttons::LeftShoulder);
+    assert_eq!(axes(gamepad), axes(left.apply(&gamepad)));
+    let moved = left.apply(&Gamepad { buttons: Buttons::LeftShoulder, .. gamepad });
+    assert_eq!((0, 0, 1000, -2000, Buttons::None), (moved.left_thumb_x, moved.left_thumb_y, moved.right_thumb_x, moved.right_thumb_y, moved.buttons));
+
+    let dpad = Remap::new([
+        Rule::button_to_axis(Buttons::DPadLeft,  Axis::LeftThumbX, -1.0),
+        Rule::button_to_axis(Buttons::DPadRight, Axis::LeftThumbX,  1.0),
+        Rule::button_to_axis(Buttons::A,         Axis::RightTrigger, 1.0),
+        Rule::new(Source::Axis(Axis::LeftTrigger), Target::Button(Buttons::B)),
+    ]);
+    let out = dpad.apply(&press(Buttons::DPadLeft | Buttons::A));
+    assert_eq!((-32768, 255, Buttons::None), (out.left_thumb_x, out.right_trigger, out.buttons));
+    let out = dpad.apply(&Gamepad { buttons: Buttons::DPadLeft | Buttons::DPadRight, left_trigger: 200, .. Gamepad::default() });
+    assert_eq!((0, 0, Buttons::B), (out.left_thumb_x, out.left_trigger, out.buttons));
+}
@@ -0,0 +1,632 @@
+//! Remote controllers: stream [`State`]s from one process to another over TCP or UDP, and [`Vibration`]s back.
+//!
+//! A [`Client`] (e.g. on a developer's machine) [`forward`](Client::forward)s its local controllers to a [`Server`] (e.g. on a test rig.)
+//! Once [`register`](Server::register)ed, the server's remote controllers appear through the regular [`get_state`] / [`set_state`]
+//! under the same `user_index`es they had on the client, so the code under test doesn't need to know about them.
+//!
+//! ### Platforms
+//! Like the rest of this crate, this module only builds for Windows.
+//! The wire protocol is simple and fully specified below, so a non-Windows peer can implement it independently.
+//!
+//! ### Wire protocol
+//! Messages are self-delimiting, so they can be streamed back to back over TCP, or sent one per UDP datagram.
+//! Multi-byte integers are little endian.
+//!
+//! | Message                       | Bytes | Layout |
+//! | ----------------------------- | ----- | ------ |
+//! | [`Message::State`]            | 18    | `0x01`, `user_index: u8`, `packet_number: u32`, [`Codec::LOSSLESS`](codec::Codec::LOSSLESS) encoded [`Gamepad`] (12 bytes)
+//! | [`Message::Disconnected`]     | 2     | `0x02`, `user_index: u8`
+//! | [`Message::Vibration`]        | 6     | `0x03`, `user_index: u8`, `left_motor_speed: u16`, `right_motor_speed: u16`
+//!
+//! ### Example
+//! ```rust,no_run
+//! # use xinput::*;
+//! use xinput::remote::*;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! # let developer_machine = true;
+//! if developer_machine {
+//!     let mut client = Client::connect_tcp("testrig.local:7777")?;
+//!     loop {
+//!         client.forward()?; // send local controllers' states, and apply any vibration sent back
+//!         std::thread::sleep(std::time::Duration::from_millis(4));
+//!     }
+//! } else {
+//!     let mut server = Server::bind_tcp("0.0.0.0:7777")?;
+//!     let _registration = server.register();
+//!     loop {
+//!         server.poll()?;
+//!         // ...existing game code, unaware of `server`...
+//!         if let Ok(state) = xinput::get_state(0) {
+//!             let rumble = if state.buttons.any_held(Buttons::A) { 0xFFFF } else { 0 };
+//!             let _ = xinput::set_state(0, Vibration { left_motor_speed: rumble, right_motor_speed: 0 });
+//!         }
+//!         std::thread::sleep(std::time::Duration::from_millis(4));
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::*;
+use crate::backend::{self, Backend, Registration};
+use crate::codec::{Codec, DecodeError};
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+
+
+/// A message of the [wire protocol](self#wire-protocol).
+#[derive(Clone, Copy, Debug)]
+pub enum Message {
+    /// [`Client`] → [`Server`]: the current state of a controller.
+    State {
+        /// The controller's `user_index`.
+        user_index: u8,
+
+        /// The controller's state.
+        state:      State,
+    },
+
+    /// [`Client`] → [`Server`]: a controller disconnected.
+    Disconnected {
+        /// The controller's `user_index`.
+        user_index: u8,
+    },
+
+    /// [`Server`] → [`Client`]: set a controller's vibration.
+    Vibration {
+        /// The controller's `user_index`.
+        user_index: u8,
+
+        /// The vibration to set.
+        vibration:  Vibration,
+    },
+}
+
+impl Message {
+    /// Append the encoded message to `out`.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match *self {
+            Message::State { user_index, state } => {
+                out.extend_from_slice(&[0x01, user_index]);
+                out.extend_from_slice(&state.packet_number.to_le_bytes());
+                out.extend_from_slice(&Codec::LOSSLESS.encode(&state.gamepad));
+            },
+            Message::Disconnected { user_index } => out.extend_from_slice(&[0x02, user_index]),
+            Message::Vibration { user_index, vibration } => {
+                out.extend_from_slice(&[0x03, user_index]);
+                out.extend_from_slice(&vibration.left_motor_speed.to_le_bytes());
+                out.extend_from_slice(&vibration.right_motor_speed.to_le_bytes());
+            },
+        }
+    }
+
+    /// Decode the message at the start of `bytes`, returning it and its length in bytes.
+    ///
+    /// ### Errors
+    /// *   [`DecodeError::Truncated`]  - `bytes` ends before the message does (wait for more data)
+    /// *   [`DecodeError::Invalid`]    - `bytes` doesn't start with a valid message
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let len = match bytes.first() { None => return Err(DecodeError::Truncated), Some(0x01) => 18, Some(0x02) => 2, Some(0x03) => 6, Some(_) => return Err(DecodeError::Invalid) };
+        let bytes = bytes.get(.. len).ok_or(DecodeError::Truncated)?;
+        let user_index = bytes[1];
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i+1]]);
+        let message = match bytes[0] {
+            0x01 => Message::State { user_index, state: State {
+                packet_number:  u32::from_le_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]),
+                gamepad:        Codec::LOSSLESS.decode(&bytes[6 ..]).map_err(|_| DecodeError::Invalid)?,
+            }},
+            0x02 => Message::Disconnected { user_index },
+            _    => Message::Vibration { user_index, vibration: Vibration { left_motor_speed: u16_at(2), right_motor_speed: u16_at(4) } },
+        };
+        Ok((message, len))
+    }
+}
+
+
+
+/// A nonblocking TCP connection with buffered, message-framed I/O.
+#[derive(Debug)]
+struct Connection {
+    stream: TcpStream,
+    inbox:  Vec<u8>,
+    outbox: Vec<u8>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self { stream, inbox: Vec::new(), outbox: Vec::new() })
+    }
+
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        message.encode(&mut self.outbox);
+        self.flush()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.outbox.is_empty() {
+            match self.stream.write(&self.outbox) {
+                Ok(0)                                                   => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n)                                                   => { self.outbox.drain(.. n); },
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock     => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted    => continue,
+                Err(err)                                                => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Append all complete messages received so far to `messages`.
+    /// Returns an error (after appending any messages received before it) if the connection was closed or sent invalid data.
+    fn receive(&mut self, messages: &mut Vec<Message>) -> io::Result<()> {
+        let mut closed = false;
+        let mut buf = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0)                                                   => { closed = true; break },
+                Ok(n)                                                   => self.inbox.extend_from_slice(&buf[.. n]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock     => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted    => continue,
+                Err(err)                                                => return Err(err),
+            }
+        }
+
+        let mut used = 0;
+        let result = loop {
+            match Message::decode(&self.inbox[used ..]) {
+                Ok((message, len))              => { messages.push(message); used += len; },
+                Err(DecodeError::Truncated)     => break Ok(()),
+                Err(_)                          => break Err(io::Error::new(io::ErrorKind::InvalidData, "invalid remote controller message")),
+            }
+        };
+        self.inbox.drain(.. used);
+        if closed { return Err(io::ErrorKind::UnexpectedEof.into()) }
+        result
+    }
+}
+
+/// Decode a UDP datagram containing exactly one message.
+fn decode_datagram(datagram: &[u8]) -> Option<Message> {
+    Message::decode(datagram).ok().filter(|(_, len)| *len == datagram.len()).map(|(message, _)| message)
+}
+
+#[derive(Debug)]
+enum Transport {
+    Tcp(Connection),
+    Udp(UdpSocket),
+}
+
+
+
+/// Sends local controllers to a [`Server`], and applies vibration sent back.
+#[derive(Debug)]
+pub struct Client {
+    transport:  Transport,
+    connected:  [bool; xuser::MAX_COUNT as usize],
+}
+
+impl Client {
+    /// Connect to a [`Server::bind_tcp`] server.
+    pub fn connect_tcp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::new(Transport::Tcp(Connection::new(TcpStream::connect(addr)?)?)))
+    }
+
+    /// Send to a [`Server::bind_udp`] server (from an ephemeral local port.)
+    pub fn connect_udp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let addr = addr.to_socket_addrs()?.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect to"))?;
+        let local : SocketAddr = if addr.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self::new(Transport::Udp(socket)))
+    }
+
+    fn new(transport: Transport) -> Self { Self { transport, connected: [false; xuser::MAX_COUNT as usize] } }
+
+    /// Send a single message.
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        match &mut self.transport {
+            Transport::Tcp(connection)  => connection.send(message),
+            Transport::Udp(socket)      => {
+                let mut datagram = Vec::new();
+                message.encode(&mut datagram);
+                match socket.send(&datagram) {
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(()), // dropped, like any other lost datagram
+                    result                                              => result.map(|_| ()),
+                }
+            },
+        }
+    }
+
+    /// Receive all messages that have arrived, without blocking.
+    pub fn receive(&mut self) -> io::Result<Vec<Message>> {
+        let mut messages = Vec::new();
+        match &mut self.transport {
+            Transport::Tcp(connection)  => { connection.flush()?; connection.receive(&mut messages)?; },
+            Transport::Udp(socket)      => {
+                let mut buf = [0u8; 64];
+                loop {
+                    match socket.recv(&mut buf) {
+                        Ok(n)                                                   => messages.extend(decode_datagram(&buf[.. n])),
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock     => break,
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted    => continue,
+                        Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => break, // ICMP port unreachable: the server isn't up (yet?)
+                        Err(err)                                                => return Err(err),
+                    }
+                }
+            },
+        }
+        Ok(messages)
+    }
+
+    /// [`get_state`] every local controller and [`send`](Self::send) it (or [`Message::Disconnected`] once, when it disconnects),
+    /// then [`set_state`] any vibration [`receive`](Self::receive)d.  Call this at your desired polling rate.
+    ///
+    /// Registered [`backend`]s (including a [`Server`] in this process) are bypassed, so remote controllers are never forwarded back out.
+    pub fn forward(&mut self) -> io::Result<()> {
+        for user in xuser::iter() {
+            let user_index = user as u8;
+            match crate::xinput_get_state(user) {
+                Ok(state) => {
+                    self.connected[user as usize] = true;
+                    self.send(&Message::State { user_index, state })?;
+                },
+                Err(_) if self.connected[user as usize] => {
+                    self.connected[user as usize] = false;
+                    self.send(&Message::Disconnected { user_index })?;
+                },
+                Err(_) => {},
+            }
+        }
+        for message in self.receive()? {
+            if let Message::Vibration { user_index, vibration } = message { let _ = crate::xinput_set_state(u32::from(user_index), vibration); }
+        }
+        Ok(())
+    }
+}
+
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Peer {
+    Tcp(u64),
+    Udp(SocketAddr),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Remote {
+    state:      State,
+    peer:       Peer,
+    expires:    Option<Instant>, // UDP only
+}
+
+/// Remote controllers by `user_index`, shared between a [`Server`] and its [`Backend`] registration.
+#[derive(Debug, Default)]
+struct Slots {
+    remotes:    [Option<Remote>; xuser::MAX_COUNT as usize],
+    vibrations: [Option<Vibration>; xuser::MAX_COUNT as usize], // to send on the next poll
+}
+
+impl Slots {
+    fn remote(&self, user_index: u32) -> Option<&Remote> {
+        self.remotes.get(user_index as usize)?.as_ref().filter(|r| r.expires.map_or(true, |e| Instant::now() <= e))
+    }
+}
+
+#[derive(Debug, Default)]
+struct SharedSlots(Mutex<Slots>);
+
+impl SharedSlots {
+    fn lock(&self) -> MutexGuard<Slots> { self.0.lock().unwrap_or_else(PoisonError::into_inner) }
+}
+
+impl Backend for SharedSlots {
+    fn get_state(&self, user_index: u32) -> Option<State> { self.lock().remote(user_index).map(|r| r.state) }
+
+    fn set_state(&self, user_index: u32, vibration: Vibration) -> bool {
+        let mut slots = self.lock();
+        if slots.remote(user_index).is_none() { return false }
+        slots.vibrations[user_index as usize] = Some(vibration);
+        true
+    }
+}
+
+/// Receives controllers from [`Client`]s, presenting them as `user_index`es.
+///
+/// A remote controller appears under the same `user_index` it had on the [`Client`] (the most recent sender wins, if several clients send the same one.)
+/// It disconnects when the client sends [`Message::Disconnected`], closes its TCP connection, or (over UDP) sends nothing for [`timeout`](Self::timeout).
+///
+/// Remote controllers are available via [`Server::get_state`] / [`Server::set_state`], or - once [`register`](Self::register)ed -
+/// via the regular [`get_state`] / [`set_state`], so existing game code sees them like any local controller.
+#[derive(Debug)]
+pub struct Server {
+    /// How long a UDP client can go without sending a [`Message::State`] before its controller is considered disconnected.
+    pub timeout:    Duration,
+
+    listener:       Option<TcpListener>,
+    udp:            Option<UdpSocket>,
+    connections:    Vec<(u64, Connection)>,
+    next_id:        u64,
+    slots:          Arc<SharedSlots>,
+}
+
+impl Server {
+    /// Listen for [`Client::connect_tcp`] clients.
+    pub fn bind_tcp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(Some(listener), None))
+    }
+
+    /// Receive from [`Client::connect_udp`] clients.
+    pub fn bind_udp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self::new(None, Some(socket)))
+    }
+
+    fn new(listener: Option<TcpListener>, udp: Option<UdpSocket>) -> Self {
+        Self { timeout: Duration::from_secs(1), listener, udp, connections: Vec::new(), next_id: 0, slots: Default::default() }
+    }
+
+    /// Make remote controllers available through the regular [`get_state`] / [`set_state`] (see [`backend`]), until the returned [`Registration`] is dropped.
+    ///
+    /// Remote controllers take precedence over local controllers with the same `user_index`.
+    /// Vibration set through [`set_state`] is sent on the next [`poll`](Self::poll).
+    pub fn register(&self) -> Registration { backend::register(self.slots.clone()) }
+
+    /// The address the server is listening on (e.g. to find the port after binding to port `0`.)
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match (&self.listener, &self.udp) {
+            (Some(listener), _) => listener.local_addr(),
+            (_, Some(socket))   => socket.local_addr(),
+            (None, None)        => Err(io::ErrorKind::NotConnected.into()),
+        }
+    }
+
+    /// Accept new connections, process all messages that have arrived, and send pending vibration, without blocking.
+    /// Errors from individual clients disconnect that client; errors from the listening socket itself are returned.
+    pub fn poll(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+
+        if let Some(listener) = self.listener.as_ref() {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => if let Ok(connection) = Connection::new(stream) {
+                        self.connections.push((self.next_id, connection));
+                        self.next_id += 1;
+                    },
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock     => break,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted    => continue,
+                    Err(err)                                                => return Err(err),
+                }
+            }
+        }
+
+        let mut received = Vec::new();
+        let mut closed = Vec::new();
+        for (id, connection) in self.connections.iter_mut() {
+            let mut messages = Vec::new();
+            let result = connection.flush().and_then(|()| connection.receive(&mut messages));
+            received.extend(messages.into_iter().map(|m| (Peer::Tcp(*id), m)));
+            if result.is_err() { closed.push(*id) }
+        }
+
+        if let Some(socket) = self.udp.as_ref() {
+            let mut buf = [0u8; 64];
+            loop {
+                match socket.recv_from(&mut buf) {
+                    Ok((n, addr))                                           => received.extend(decode_datagram(&buf[.. n]).map(|m| (Peer::Udp(addr), m))),
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock     => break,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted    => continue,
+                    Err(err) if err.kind() == io::ErrorKind::ConnectionReset => continue, // Windows: an earlier send_to a client that's gone
+                    Err(err)                                                => return Err(err),
+                }
+            }
+        }
+
+        let slots = self.slots.clone();
+        let mut slots = slots.lock();
+        for (peer, message) in received {
+            match message {
+                Message::State { user_index, state } => if let Some(remote) = slots.remotes.get_mut(usize::from(user_index)) {
+                    let expires = matches!(peer, Peer::Udp(_)).then(|| now + self.timeout);
+                    *remote = Some(Remote { state, peer, expires });
+                },
+                Message::Disconnected { user_index } => if let Some(remote) = slots.remotes.get_mut(usize::from(user_index)) {
+                    if remote.map_or(false, |r| r.peer == peer) { *remote = None }
+                },
+                Message::Vibration { .. } => {}, // wrong direction
+            }
+        }
+
+        self.connections.retain(|(id, _)| !closed.contains(id));
+        for remote in slots.remotes.iter_mut() {
+            let gone = remote.map_or(false, |r| match r.peer {
+                Peer::Tcp(id)   => closed.contains(&id),
+                Peer::Udp(_)    => r.expires.map_or(false, |e| now > e),
+            });
+            if gone { *remote = None }
+        }
+
+        for user_index in xuser::iter() {
+            let Some(vibration) = slots.vibrations[user_index as usize].take() else { continue };
+            let Some(remote) = slots.remote(user_index).copied() else { continue };
+            self.send_vibration(remote.peer, user_index, vibration);
+        }
+
+        Ok(())
+    }
+
+    /// `true` if a remote controller is connected as `user_index`.
+    pub fn is_connected(&self, user_index: u32) -> bool { self.slots.lock().remote(user_index).is_some() }
+
+    /// The last [`State`] received for `user_index` (as of the last [`poll`](Self::poll).)
+    ///
+    /// ### Errors
+    /// *   [error::BAD_ARGUMENTS]          - Invalid `user_index` (expected <code>0 .. [xuser::MAX_COUNT]</code>)
+    /// *   [error::DEVICE_NOT_CONNECTED]   - No remote controller connected as `user_index`.
+    pub fn get_state(&self, user_index: impl TryInto<u32>) -> Result<State, Error> {
+        fn_context!(xinput::remote::Server::get_state);
+        let user_index = user_index.try_into().ok().filter(|&u| u < xuser::MAX_COUNT).ok_or_else(|| fn_param_error!(user_index, error::BAD_ARGUMENTS))?;
+        self.slots.lock().remote(user_index).map(|r| r.state).ok_or(Error(&_THINDX_FN_CONTEXT, error::DEVICE_NOT_CONNECTED))
+    }
+
+    /// Send `vibration` to the [`Client`] that owns `user_index`.
+    ///
+    /// ### Errors
+    /// *   [error::BAD_ARGUMENTS]          - Invalid `user_index` (expected <code>0 .. [xuser::MAX_COUNT]</code>)
+    /// *   [error::DEVICE_NOT_CONNECTED]   - No remote controller connected as `user_index`.
+    pub fn set_state(&mut self, user_index: impl TryInto<u32>, vibration: impl Into<Vibration>) -> Result<(), Error> {
+        fn_context!(xinput::remote::Server::set_state);
+        let user_index = user_index.try_into().ok().filter(|&u| u < xuser::MAX_COUNT).ok_or_else(|| fn_param_error!(user_index, error::BAD_ARGUMENTS))?;
+        let remote = self.slots.lock().remote(user_index).copied().ok_or(Error(&_THINDX_FN_CONTEXT, error::DEVICE_NOT_CONNECTED))?;
+        self.send_vibration(remote.peer, user_index, vibration.into());
+        Ok(())
+    }
+
+    /// Send errors are ignored: a broken connection is cleaned up by the next poll, and UDP is lossy anyways.
+    fn send_vibration(&mut self, peer: Peer, user_index: u32, vibration: Vibration) {
+        let message = Message::Vibration { user_index: user_index as u8, vibration };
+        match peer {
+            Peer::Tcp(id)   => if let Some((_, connection)) = self.connections.iter_mut().find(|(i, _)| *i == id) { let _ = connection.send(&message); },
+            Peer::Udp(addr) => if let Some(socket) = self.udp.as_ref() {
+                let mut datagram = Vec::new();
+                message.encode(&mut datagram);
+                let _ = socket.send_to(&datagram, addr);
+            },
+        }
+    }
+}
+
+
+
+/// Poll `server` (and `client`) until `done`, or panic after a few seconds.
+#[cfg(test)] fn poll_until(server: &mut Server, client: &mut Client, mut done: impl FnMut(&Server, Vec<Message>) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        server.poll().unwrap();
+        let messages = client.receive().unwrap();
+        if done(server, messages) { return }
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[cfg(test)] fn test_transport(mut server: Server, mut client: Client) {
+    let state = State { packet_number: 42, gamepad: Gamepad { buttons: Buttons::A | Buttons::Back, left_trigger: 255, left_thumb_x: -32768, right_thumb_y: 32767, .. Gamepad::default() } };
+    assert_eq!(error::DEVICE_NOT_CONNECTED, server.get_state(1).unwrap_err().kind());
+    assert_eq!(error::BAD_ARGUMENTS, server.get_state(4).unwrap_err().kind());
+    assert_eq!(error::BAD_ARGUMENTS, server.set_state(-1, Vibration::default()).unwrap_err().kind());
+
+    client.send(&Message::State { user_index: 1, state }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| server.is_connected(1));
+    let received = server.get_state(1u32).unwrap();
+    assert_eq!(42, received.packet_number);
+    assert_eq!(bytemuck::bytes_of(&state.gamepad), bytemuck::bytes_of(&received.gamepad));
+    assert!(!server.is_connected(0));
+
+    server.set_state(1, Vibration { left_motor_speed: 1000, right_motor_speed: 2000 }).unwrap();
+    assert_eq!(error::DEVICE_NOT_CONNECTED, server.set_state(2, Vibration::default()).unwrap_err().kind());
+    let mut vibration = None;
+    poll_until(&mut server, &mut client, |_, messages| {
+        for m in messages { if let Message::Vibration { user_index, vibration: v } = m { vibration = Some((user_index, v.left_motor_speed, v.right_motor_speed)) } }
+        vibration.is_some()
+    });
+    assert_eq!(Some((1, 1000, 2000)), vibration);
+
+    client.send(&Message::Disconnected { user_index: 1 }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| !server.is_connected(1));
+    client.send(&Message::State { user_index: 3, state }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| server.is_connected(3));
+}
+
+#[test] fn test_tcp() {
+    let server = Server::bind_tcp("127.0.0.1:0").unwrap();
+    let client = Client::connect_tcp(server.local_addr().unwrap()).unwrap();
+    test_transport(server, client);
+
+    // closing the connection disconnects its controllers
+    let mut server = Server::bind_tcp("127.0.0.1:0").unwrap();
+    let mut client = Client::connect_tcp(server.local_addr().unwrap()).unwrap();
+    client.send(&Message::State { user_index: 0, state: State::default() }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| server.is_connected(0));
+    drop(client);
+    let mut client = Client::connect_tcp(server.local_addr().unwrap()).unwrap();
+    poll_until(&mut server, &mut client, |server, _| !server.is_connected(0));
+
+    // as does sending garbage
+    client.send(&Message::State { user_index: 2, state: State::default() }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| server.is_connected(2));
+    if let Transport::Tcp(connection) = &mut client.transport { connection.stream.write_all(&[0xFF]).unwrap() }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while server.is_connected(2) {
+        server.poll().unwrap();
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(client.receive().is_err(), "server should have closed the connection");
+}
+
+#[test] fn test_udp() {
+    let server = Server::bind_udp("127.0.0.1:0").unwrap();
+    let client = Client::connect_udp(server.local_addr().unwrap()).unwrap();
+    test_transport(server, client);
+
+    let mut server = Server::bind_udp("127.0.0.1:0").unwrap();
+    let mut client = Client::connect_udp(server.local_addr().unwrap()).unwrap();
+    server.timeout = Duration::from_millis(50);
+    client.send(&Message::State { user_index: 0, state: State::default() }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| server.is_connected(0));
+    poll_until(&mut server, &mut client, |server, _| !server.is_connected(0)); // timed out
+}
+
+#[test] fn test_register() {
+    let _lock = backend::test_lock();
+    let mut server = Server::bind_tcp("127.0.0.1:0").unwrap();
+    let mut client = Client::connect_tcp(server.local_addr().unwrap()).unwrap();
+    let registration = server.register();
+    client.send(&Message::State { user_index: 3, state: State { packet_number: 0x5EB, .. State::default() } }).unwrap();
+    poll_until(&mut server, &mut client, |server, _| server.is_connected(3));
+    assert_eq!(0x5EB, crate::get_state(3u32).unwrap().packet_number);
+
+    crate::set_state(3u32, Vibration { left_motor_speed: 3, right_motor_speed: 4 }).unwrap();
+    let mut vibration = None;
+    poll_until(&mut server, &mut client, |_, messages| {
+        for m in messages { if let Message::Vibration { user_index, vibration: v } = m { vibration = Some((user_index, v.left_motor_speed, v.right_motor_speed)) } }
+        vibration.is_some()
+    });
+    assert_eq!(Some((3, 3, 4)), vibration);
+
+    drop(registration);
+    assert!(crate::get_state(3u32).map_or(true, |s| s.packet_number != 0x5EB));
+}
+
+#[test] fn test_message_encoding() {
+    let messages = [
+        Message::State { user_index: 3, state: State { packet_number: 0x12345678, gamepad: Gamepad { buttons: Buttons::Y, right_trigger: 7, left_thumb_y: -2, .. Gamepad::default() } } },
+        Message::Disconnected { user_index: 0 },
+        Message::Vibration { user_index: 2, vibration: Vibration { left_motor_speed: 0x1234, right_motor_speed: 0xFFFF } },
+    ];
+    let mut bytes = Vec::new();
+    for m in messages.iter() { m.encode(&mut bytes) }
+    assert_eq!(18 + 2 + 6, bytes.len());
+    assert_eq!([0x03, 2, 0x34, 0x12, 0xFF, 0xFF], bytes[20 ..]);
+
+    let mut rest = &bytes[..];
+    for m in messages.iter() {
+        let (decoded, len) = Message::decode(rest).unwrap();
+        assert_eq!(format!("{m:?}"), format!("{decoded:?}"));
+        rest = &rest[len ..];
+    }
+    assert_eq!(Err(DecodeError::Truncated), Message::decode(rest).map(|_| ()));
+    assert_eq!(Err(DecodeError::Truncated), Message::decode(&bytes[.. 17]).map(|_| ()));
+    assert_eq!(Err(DecodeError::Invalid), Message::decode(&[0x04, 0]).map(|_| ()));
+    assert!(decode_datagram(&bytes[.. 18]).is_some());
+    assert!(decode_datagram(&bytes[.. 19]).is_none(), "trailing data");
+}
@@ -0,0 +1,217 @@
+//! Rollback netcode: per-frame [`Gamepad`] inputs for several players, predicting inputs that haven't arrived yet.
+//!
+//! Each simulated frame reads every player's [`input`](InputQueue::input): confirmed if known, predicted otherwise.
+//! When a remote input is [`confirm`](InputQueue::confirm)ed and differs from what was predicted for that frame,
+//! the game should roll back to that frame and resimulate (predictions for later frames are redone using the new input.)
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::rollback::*;
+//!
+//! let (local, remote) = (0, 1);
+//! let mut inputs = InputQueue::new(2);
+//! # let mut frame = 0;
+//! # let received : Vec<(u32, Gamepad)> = Vec::new();
+//! # for _ in 0 .. 1 {
+//! inputs.confirm(local, frame, xinput::get_state(0).unwrap_or_default().gamepad);
+//!
+//! let mut rollback = None::<u32>;
+//! for (remote_frame, gamepad) in received.iter().copied() {
+//!     if let Some(f) = inputs.confirm(remote, remote_frame, gamepad) { rollback = Some(rollback.map_or(f, |r| r.min(f))) }
+//! }
+//! for f in rollback.unwrap_or(frame) ..= frame {
+//!     // (restore the game state saved for frame `f`, if rolling back)
+//!     let (p1, p2) = (inputs.input(local, f), inputs.input(remote, f));
+//!     // ...simulate frame `f` with p1 and p2, and save the resulting game state...
+//! }
+//!
+//! inputs.trim(inputs.confirmed_frame().unwrap_or(0)); // frames before this will never be rolled back to
+//! frame += 1;
+//! # }
+//! ```
+
+use crate::*;
+
+use std::collections::BTreeMap;
+
+
+
+/// How [`InputQueue`] predicts inputs that haven't been confirmed yet.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Prediction {
+    /// Repeat the most recent confirmed input (before the predicted frame.)  Usually right, since inputs rarely change every frame.
+    #[default] RepeatLast,
+
+    /// Predict an idle [`Gamepad`] (nothing held.)
+    Idle,
+
+    /// Predict from the most recent confirmed input (or an idle [`Gamepad`] if there isn't one.)
+    /// E.g. hold the thumbsticks but release the buttons.
+    Custom(fn(&Gamepad) -> Gamepad),
+}
+
+#[derive(Clone, Debug, Default)]
+struct History {
+    confirmed:      BTreeMap<u32, Gamepad>,
+    predicted:      BTreeMap<u32, Gamepad>,
+    trimmed:        Option<Gamepad>, // most recent confirmed input before `InputQueue::start`
+    confirmed_to:   Option<u32>,     // all frames `start ..= confirmed_to` are confirmed
+}
+
+/// Confirmed and predicted [`Gamepad`] inputs, by player and frame number.  See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct InputQueue {
+    /// How to predict unconfirmed inputs.
+    pub prediction: Prediction,
+
+    players:        Vec<History>,
+    start:          u32,
+}
+
+impl InputQueue {
+    /// Track inputs for `players` players, starting at frame 0, predicting with [`Prediction::RepeatLast`].
+    pub fn new(players: usize) -> Self { Self { prediction: Prediction::RepeatLast, players: vec![History::default(); players], start: 0 } }
+
+    /// The number of players.
+    pub fn players(&self) -> usize { self.players.len() }
+
+    /// The first frame still tracked (see [`trim`](Self::trim).)
+    pub fn start(&self) -> u32 { self.start }
+
+    /// Record `player`'s actual input for `frame`.
+    ///
+    /// Returns `Some(frame)` if this contradicts the input previously returned by [`input`](Self::input) for that frame
+    /// (a misprediction, or a conflicting re-confirmation), meaning the game should roll back to `frame`.
+    /// Confirmations of frames before [`start`](Self::start) are ignored.
+    ///
+    /// ### Panics
+    /// If `player` is out of range.
+    pub fn confirm(&mut self, player: usize, frame: u32, gamepad: Gamepad) -> Option<u32> {
+        if frame < self.start { return None }
+        let start = self.start;
+        let history = &mut self.players[player];
+        let previous = history.predicted.remove(&frame).or_else(|| history.confirmed.get(&frame).copied());
+        history.confirmed.insert(frame, gamepad);
+
+        let mut next = history.confirmed_to.map_or(start, |f| f + 1);
+        while history.confirmed.contains_key(&next) {
+            history.confirmed_to = Some(next);
+            next += 1;
+        }
+
+        previous.filter(|previous| bytemuck::bytes_of(previous) != bytemuck::bytes_of(&gamepad)).map(|_| frame)
+    }
+
+    /// `true` if `player`'s input for `frame` has been [`confirm`](Self::confirm)ed.
+    pub fn is_confirmed(&self, player: usize, frame: u32) -> bool {
+        self.players.get(player).map_or(false, |h| h.confirmed.contains_key(&frame))
+    }
+
+    /// `player`'s input for `frame`: the confirmed input if known, otherwise a [prediction](Self::prediction).
+    /// Predictions are remembered, so a later [`confirm`](Self::confirm)ation can report a misprediction.
+    ///
+    /// ### Panics
+    /// If `player` is out of range.
+    pub fn input(&mut self, player: usize, frame: u32) -> Gamepad {
+        let history = &mut self.players[player];
+        if let Some(confirmed) = history.confirmed.get(&frame) { return *confirmed }
+        let last = history.confirmed.range(.. frame).next_back().map(|(_, g)| *g).or(history.trimmed);
+        let predicted = match self.prediction {
+            Prediction::RepeatLast  => last.unwrap_or_default(),
+            Prediction::Idle        => Gamepad::default(),
+            Prediction::Custom(f)   => f(&last.unwrap_or_default()),
+        };
+        if frame >= self.start { history.predicted.insert(frame, predicted); }
+        predicted
+    }
+
+    /// The last frame for which every player's input (from [`start`](Self::start) onwards) is confirmed, if any.
+    /// No confirmation can cause a rollback to this frame or earlier, so it's safe to [`trim`](Self::trim) up to it.
+    pub fn confirmed_frame(&self) -> Option<u32> {
+        self.players.iter().map(|h| h.confirmed_to).min().flatten()
+    }
+
+    /// The last frame for which `player`'s input (from [`start`](Self::start) onwards) is confirmed, if any.
+    pub fn player_confirmed_frame(&self, player: usize) -> Option<u32> { self.players.get(player).and_then(|h| h.confirmed_to) }
+
+    /// Forget inputs for frames before `frame`.  The most recent confirmed input is kept for prediction.
+    pub fn trim(&mut self, frame: u32) {
+        if frame <= self.start { return }
+        self.start = frame;
+        for history in self.players.iter_mut() {
+            let kept = history.confirmed.split_off(&frame);
+            if let Some((_, last)) = history.confirmed.iter().next_back() { history.trimmed = Some(*last) }
+            history.confirmed = kept;
+            history.predicted = history.predicted.split_off(&frame);
+            history.confirmed_to = history.confirmed_to.filter(|&f| f >= frame);
+        }
+    }
+}
+
+
+
+#[cfg(test)] fn pad(buttons: Buttons) -> Gamepad { Gamepad { buttons, .. Gamepad::default() } }
+
+#[test] fn test_predict_and_rollback() {
+    let mut q = InputQueue::new(2);
+    for frame in 0 .. 3 { assert_eq!(None, q.confirm(0, frame, pad(Buttons::A))) }
+    assert_eq!(None, q.confirm(1, 0, pad(Buttons::B)));
+    assert_eq!((Some(2), Some(0), Some(0)), (q.player_confirmed_frame(0), q.player_confirmed_frame(1), q.confirmed_frame()));
+
+    // frames 1 ..= 2 predicted by repeating frame 0
+    assert_eq!(Buttons::A, q.input(0, 1).buttons);
+    assert_eq!(Buttons::B, q.input(1, 1).buttons);
+    assert_eq!(Buttons::B, q.input(1, 2).buttons);
+    assert_eq!(Buttons::A, q.input(0, 5).buttons, "predicted from frame 2");
+
+    assert_eq!(None, q.confirm(1, 1, pad(Buttons::B)), "correct prediction");
+    assert_eq!(Some(2), q.confirm(1, 2, pad(Buttons::X)), "misprediction");
+    assert!(q.is_confirmed(1, 2));
+    assert_eq!(Buttons::X, q.input(1, 2).buttons);
+    assert_eq!(Some(2), q.confirmed_frame());
+
+    // out of order: frame 4 arrives before frame 3, predictions redone after the rollback
+    assert_eq!(Buttons::X, q.input(1, 3).buttons);
+    assert_eq!(Buttons::X, q.input(1, 4).buttons);
+    assert_eq!(Some(4), q.confirm(1, 4, pad(Buttons::Y)));
+    assert_eq!(Some(2), q.player_confirmed_frame(1));
+    assert_eq!(Buttons::X, q.input(1, 3).buttons);
+    assert_eq!(None, q.confirm(1, 3, pad(Buttons::X)));
+    assert_eq!(Some(4), q.player_confirmed_frame(1));
+
+    assert_eq!(None, q.confirm(1, 5, pad(Buttons::Y)), "never predicted");
+    assert_eq!(Some(5), q.confirm(1, 5, pad(Buttons::A)), "conflicting re-confirmation");
+}
+
+#[test] fn test_prediction_modes() {
+    let mut q = InputQueue::new(1);
+    assert_eq!(Buttons::None, q.input(0, 3).buttons, "nothing confirmed yet");
+    q.confirm(0, 0, Gamepad { buttons: Buttons::A, left_thumb_x: 1000, .. Gamepad::default() });
+    q.prediction = Prediction::Idle;
+    assert_eq!((Buttons::None, 0), (q.input(0, 1).buttons, q.input(0, 1).left_thumb_x));
+    q.prediction = Prediction::Custom(|g| Gamepad { buttons: Buttons::None, .. *g });
+    assert_eq!((Buttons::None, 1000), (q.input(0, 1).buttons, q.input(0, 1).left_thumb_x));
+    assert_eq!(Some(1), q.confirm(0, 1, pad(Buttons::None)), "thumbstick released");
+}
+
+#[test] fn test_trim() {
+    let mut q = InputQueue::new(2);
+    for frame in 0 .. 10 { q.confirm(0, frame, pad(Buttons::A)); }
+    for frame in 0 .. 6 { q.confirm(1, frame, pad(if frame < 5 { Buttons::B } else { Buttons::X })); }
+    q.input(1, 7);
+    assert_eq!(Some(5), q.confirmed_frame());
+
+    q.trim(6);
+    assert_eq!(6, q.start());
+    assert_eq!((Some(9), None, None), (q.player_confirmed_frame(0), q.player_confirmed_frame(1), q.confirmed_frame()));
+    assert!(!q.is_confirmed(1, 5));
+    assert_eq!(Buttons::X, q.input(1, 6).buttons, "predicted from the trimmed frame 5");
+    assert_eq!(None, q.confirm(1, 5, pad(Buttons::Y)), "before start");
+    assert_eq!(Some(7), q.confirm(1, 7, pad(Buttons::Y)), "prediction from before the trim still counts");
+    assert_eq!(None, q.confirm(1, 6, pad(Buttons::X)));
+    assert_eq!(Some(7), q.confirmed_frame());
+
+    q.trim(3);
+    assert_eq!(6, q.start(), "can't un-trim");
+}
@@ -0,0 +1,138 @@
+use crate::*;
+#[cfg(test)] use bytemuck::Zeroable;
+
+
+
+/// A [`Gamepad`] read as an arcade stick ([`DevSubType::ArcadeStick`] or [`DevSubType::ArcadePad`].)
+///
+/// XInput arcade sticks report their lever as the D-pad, and their attack buttons as face buttons, bumpers, and triggers.
+/// This type uses the conventional (Mad Catz / Hori / Razer "Xbox layout") mapping for 6 and 8 button sticks:
+///
+/// | Row       | 1st                           | 2nd                               | 3rd                               | 4th (8 button sticks)         |
+/// | --------- | ----------------------------- | --------------------------------- | --------------------------------- | ----------------------------- |
+/// | Top       | `X` → [`light_punch`]         | `Y` → [`medium_punch`]            | `RB` → [`heavy_punch`]            | `LB` → [`extra_punch`]        |
+/// | Bottom    | `A` → [`light_kick`]          | `B` → [`medium_kick`]             | `RT` → [`heavy_kick`]             | `LT` → [`extra_kick`]         |
+///
+/// [`light_punch`]:    Self::light_punch
+/// [`medium_punch`]:   Self::medium_punch
+/// [`heavy_punch`]:    Self::heavy_punch
+/// [`extra_punch`]:    Self::extra_punch
+/// [`light_kick`]:     Self::light_kick
+/// [`medium_kick`]:    Self::medium_kick
+/// [`heavy_kick`]:     Self::heavy_kick
+/// [`extra_kick`]:     Self::extra_kick
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let gamepad = 0;
+/// let caps  = xinput::get_capabilities(gamepad, Flag::None).ok();
+/// let state = xinput::get_state(gamepad).unwrap_or_default();
+/// if let Some(stick) = caps.and_then(|caps| ArcadeStickState::from_capabilities(&caps, &state)) {
+///     if stick.lever == Direction8::DownRight && stick.heavy_punch {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Default)]
+pub struct ArcadeStickState {
+    /// The 8-way lever, read from the D-pad with opposing directions cancelling out.
+    pub lever:          Direction8,
+
+    /// `LP` &mdash; [`Buttons::X`]
+    pub light_punch:    bool,
+
+    /// `MP` &mdash; [`Buttons::Y`]
+    pub medium_punch:   bool,
+
+    /// `HP` &mdash; [`Buttons::RightShoulder`]
+    pub heavy_punch:    bool,
+
+    /// 4th top row button on 8 button sticks &mdash; [`Buttons::LeftShoulder`]
+    pub extra_punch:    bool,
+
+    /// `LK` &mdash; [`Buttons::A`]
+    pub light_kick:     bool,
+
+    /// `MK` &mdash; [`Buttons::B`]
+    pub medium_kick:    bool,
+
+    /// `HK` &mdash; [`Gamepad::right_trigger`]
+    pub heavy_kick:     bool,
+
+    /// 4th bottom row button on 8 button sticks &mdash; [`Gamepad::left_trigger`]
+    pub extra_kick:     bool,
+
+    /// [`Buttons::Start`]
+    pub start:          bool,
+
+    /// [`Buttons::Back`]
+    pub back:           bool,
+}
+
+impl ArcadeStickState {
+    /// [`DevSubType`]s that [`ArcadeStickState::from_capabilities`] will accept.
+    pub const SUB_TYPES : [DevSubType; 2] = [DevSubType::ArcadeStick, DevSubType::ArcadePad];
+
+    /// Read `gamepad` as an arcade stick, regardless of what kind of device it actually is.
+    ///
+    /// Triggers are considered held past [`Gamepad::TRIGGER_THRESHOLD`], although arcade sticks generally only ever report `0` or `255`.
+    pub fn from_gamepad(gamepad: &Gamepad) -> Self {
+        let b = gamepad.buttons;
+        Self {
+            lever:          Direction8::from_dpad(b),
+            light_punch:    b.any_held(Buttons::X),
+            medium_punch:   b.any_held(Buttons::Y),
+            heavy_punch:    b.any_held(Buttons::RightShoulder),
+            extra_punch:    b.any_held(Buttons::LeftShoulder),
+            light_kick:     b.any_held(Buttons::A),
+            medium_kick:    b.any_held(Buttons::B),
+            heavy_kick:     gamepad.right_trigger > Gamepad::TRIGGER_THRESHOLD,
+            extra_kick:     gamepad.left_trigger  > Gamepad::TRIGGER_THRESHOLD,
+            start:          b.any_held(Buttons::Start),
+            back:           b.any_held(Buttons::Back),
+        }
+    }
+
+    /// Read `gamepad` as an arcade stick, if <code>caps.[sub_type](Capabilities::sub_type)</code> is one of [`ArcadeStickState::SUB_TYPES`].
+    pub fn from_capabilities(caps: &Capabilities, gamepad: &Gamepad) -> Option<Self> {
+        Self::SUB_TYPES.contains(&caps.sub_type).then(|| Self::from_gamepad(gamepad))
+    }
+
+    /// Returns `true` if any punch button is held.
+    pub fn any_punch(&self) -> bool { self.light_punch || self.medium_punch || self.heavy_punch || self.extra_punch }
+
+    /// Returns `true` if any kick button is held.
+    pub fn any_kick(&self) -> bool { self.light_kick || self.medium_kick || self.heavy_kick || self.extra_kick }
+}
+
+impl From<Gamepad> for ArcadeStickState { fn from(gamepad: Gamepad) -> Self { Self::from_gamepad(&gamepad) } }
+
+#[test] fn test_from_gamepad() {
+    let stick = ArcadeStickState::from_gamepad(&Gamepad {
+        buttons:        Buttons::DPadDown | Buttons::DPadRight | Buttons::X | Buttons::B,
+        right_trigger:  255,
+        .. Gamepad::default()
+    });
+    assert_eq!(stick.lever, Direction8::DownRight);
+    assert!( stick.light_punch);
+    assert!(!stick.medium_punch);
+    assert!( stick.medium_kick);
+    assert!( stick.heavy_kick);
+    assert!(!stick.extra_kick);
+    assert!(stick.any_punch() && stick.any_kick());
+}
+
+#[test] fn test_from_capabilities() {
+    let mut caps = Capabilities::zeroed();
+    let gamepad = Gamepad::default();
+    for sub_type in [DevSubType::ArcadeStick, DevSubType::ArcadePad] {
+        caps.sub_type = sub_type;
+        assert_eq!(Some(ArcadeStickState::default()), ArcadeStickState::from_capabilities(&caps, &gamepad));
+    }
+    for sub_type in [DevSubType::Unknown, DevSubType::Gamepad, DevSubType::FlightStick, DevSubType::Guitar] {
+        caps.sub_type = sub_type;
+        assert_eq!(None, ArcadeStickState::from_capabilities(&caps, &gamepad));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::*;
+
+use std::f32::consts::TAU;
+
+
+
+/// The current phase of a [`Calibrator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CalibrationPhase {
+    /// Waiting for the user to leave the stick centered.  Prompt e.g. "Let go of the left stick."
+    Center,
+
+    /// Waiting for the user to rotate the stick against the edge of its gate.  Prompt e.g. "Slowly rotate the left stick in a full circle."
+    Rotate,
+
+    /// Calibration is complete: see [`Calibrator::calibration`].
+    Done,
+}
+
+/// Walks a user through calibrating a single thumbstick, producing a [`StickCalibration`].
+///
+/// 1.  [`CalibrationPhase::Center`]:  collects [`center_samples`](Self::center_samples) samples of the resting stick, to find its center and jitter.
+///     If the stick moves more than [`center_tolerance`](Self::center_tolerance) from where this phase started, the phase restarts.
+/// 2.  [`CalibrationPhase::Rotate`]:  collects the extents of the stick's range of motion, until the stick has been pushed at least
+///     [`rotate_threshold`](Self::rotate_threshold) from center in every direction.
+/// 3.  [`CalibrationPhase::Done`]:  [`Calibrator::calibration`] returns the result.
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let mut calibrator = Calibrator::new(ThumbStick::Left);
+/// # for _ in 0 .. 1 {
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// match calibrator.sample(&state) {
+///     CalibrationPhase::Center    => println!("Let go of the left stick..."),
+///     CalibrationPhase::Rotate    => println!("Rotate the left stick in a full circle... {:.0}%", calibrator.progress() * 100.0),
+///     CalibrationPhase::Done      => println!("Calibrated: {:?}", calibrator.calibration()),
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Calibrator {
+    /// The stick being calibrated.
+    pub stick:              ThumbStick,
+
+    /// How many samples [`CalibrationPhase::Center`] collects.
+    pub center_samples:     usize,
+
+    /// How far (in raw units) the stick may move during [`CalibrationPhase::Center`] before the phase restarts.
+    pub center_tolerance:   i16,
+
+    /// How far (in raw units) from center the stick must be pushed in every direction during [`CalibrationPhase::Rotate`].
+    pub rotate_threshold:   i16,
+
+    phase:                  CalibrationPhase,
+    centered:               Vec<(i16, i16)>,
+    center:                 (i16, i16),
+    jitter:                 f32,
+    min:                    (i16, i16),
+    max:                    (i16, i16),
+    sectors:                u16,
+}
+
+impl Calibrator {
+    const SECTORS : u32 = 16;
+
+    /// Start calibrating `stick`, with default settings.
+    pub fn new(stick: ThumbStick) -> Self {
+        Self {
+            stick,
+            center_samples:     60,
+            center_tolerance:   4096,
+            rotate_threshold:   22000,
+            phase:              CalibrationPhase::Center,
+            centered:           Vec::new(),
+            center:             (0, 0),
+            jitter:             0.0,
+            min:                (0, 0),
+            max:                (0, 0),
+            sectors:            0,
+        }
+    }
+
+    /// The current phase.
+    pub fn phase(&self) -> CalibrationPhase { self.phase }
+
+    /// Progress through the current phase, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f32 {
+        match self.phase {
+            CalibrationPhase::Center    => self.centered.len() as f32 / self.center_samples.max(1) as f32,
+            CalibrationPhase::Rotate    => self.sectors.count_ones() as f32 / Self::SECTORS as f32,
+            CalibrationPhase::Done      => 1.0,
+        }
+    }
+
+    /// Discard all samples and restart from [`CalibrationPhase::Center`].
+    pub fn restart(&mut self) { *self = Self { center_samples: self.center_samples, center_tolerance: self.center_tolerance, rotate_threshold: self.rotate_threshold, .. Self::new(self.stick) } }
+
+    /// Feed the next `gamepad` sample, returning the (possibly new) phase.
+    pub fn sample(&mut self, gamepad: &Gamepad) -> CalibrationPhase {
+        let (x, y) = self.stick.read(gamepad);
+        match self.phase {
+            CalibrationPhase::Center => {
+                if let Some(&(x0, y0)) = self.centered.first() {
+                    if distance((x0, y0), (x, y)) > f32::from(self.center_tolerance) { self.centered.clear() }
+                }
+                self.centered.push((x, y));
+                if self.centered.len() >= self.center_samples.max(1) { self.finish_center() }
+            },
+            CalibrationPhase::Rotate => {
+                self.min = (self.min.0.min(x), self.min.1.min(y));
+                self.max = (self.max.0.max(x), self.max.1.max(y));
+                if distance(self.center, (x, y)) >= f32::from(self.rotate_threshold) {
+                    let angle = (f32::from(y) - f32::from(self.center.1)).atan2(f32::from(x) - f32::from(self.center.0));
+                    let sector = (angle.rem_euclid(TAU) / TAU * Self::SECTORS as f32) as u32 % Self::SECTORS;
+                    self.sectors |= 1 << sector;
+                }
+                if self.sectors.count_ones() == Self::SECTORS { self.phase = CalibrationPhase::Done }
+            },
+            CalibrationPhase::Done => {},
+        }
+        self.phase
+    }
+
+    /// The resulting calibration, once [`CalibrationPhase::Done`].
+    ///
+    /// The recommended [`StickCalibration::deadzone`] is 1.5&times; the jitter observed while centered, plus a small margin.
+    pub fn calibration(&self) -> Option<StickCalibration> {
+        if self.phase != CalibrationPhase::Done { return None }
+        let deadzone = (self.jitter * 1.5 + 512.0).min(16384.0) as i16;
+        let c = StickCalibration { center_x: self.center.0, center_y: self.center.1, min_x: self.min.0, max_x: self.max.0, min_y: self.min.1, max_y: self.max.1, deadzone };
+        c.is_valid().then_some(c)
+    }
+
+    fn finish_center(&mut self) {
+        let n = self.centered.len() as f32;
+        let cx = self.centered.iter().map(|&(x, _)| f32::from(x)).sum::<f32>() / n;
+        let cy = self.centered.iter().map(|&(_, y)| f32::from(y)).sum::<f32>() / n;
+        self.center = (cx.round() as i16, cy.round() as i16);
+        self.jitter = self.centered.iter().map(|&p| distance(self.center, p)).fold(0.0, f32::max);
+        self.min    = self.center;
+        self.max    = self.center;
+        self.phase  = CalibrationPhase::Rotate;
+    }
+}
+
+fn distance(a: (i16, i16), b: (i16, i16)) -> f32 { (f32::from(a.0) - f32::from(b.0)).hypot(f32::from(a.1) - f32::from(b.1)) }
+
+
+
+#[test] fn test_calibrate() {
+    let left = |x: i16, y: i16| Gamepad { left_thumb_x: x, left_thumb_y: y, right_thumb_x: 12345, .. Gamepad::default() };
+    let mut c = Calibrator::new(ThumbStick::Left);
+    c.center_samples = 10;
+
+    // centered, with jitter, but then bumped: restarts
+    for i in 0 .. 5 { assert_eq!(CalibrationPhase::Center, c.sample(&left(3000 + i * 10, -2000))); }
+    assert_eq!(0.5, c.progress());
+    c.sample(&left(20000, 0));
+    assert_eq!(0.1, c.progress());
+
+    for i in 0 .. 10 { c.sample(&left(3000 + if i % 2 == 0 { 100 } else { -100 }, -2000)); }
+    assert_eq!(CalibrationPhase::Rotate, c.phase());
+    assert_eq!(None, c.calibration());
+
+    // asymmetric circular gate around (3000, -2000)
+    for deg in (0 .. 360).step_by(5) {
+        let r = (deg as f32).to_radians();
+        let (x, y) = (r.cos() * if r.cos() > 0.0 { 27000.0 } else { 29000.0 }, r.sin() * 28000.0);
+        c.sample(&left(3000 + x as i16, -2000 + y as i16));
+    }
+    assert_eq!(CalibrationPhase::Done, c.phase());
+    assert_eq!(1.0, c.progress());
+
+    let cal = c.calibration().unwrap();
+    assert_eq!((cal.center_x, cal.center_y), (3000, -2000));
+    assert_eq!((cal.min_x, cal.max_x, cal.min_y, cal.max_y), (-26000, 30000, -30000, 26000));
+    assert_eq!(cal.deadzone, 662);
+    assert_eq!((0, 0), cal.apply_xy(3100, -2000));
+    assert_eq!((32767, 0), cal.apply_xy(30000, -2000));
+
+    c.restart();
+    assert_eq!((CalibrationPhase::Center, 10), (c.phase(), c.center_samples));
+}
+
+#[test] fn test_partial_rotation() {
+    let mut c = Calibrator::new(ThumbStick::Right);
+    c.center_samples = 1;
+    c.sample(&Gamepad::default());
+    for x in [32767, -32768] { c.sample(&Gamepad { right_thumb_x: x, .. Gamepad::default() }); }
+    assert_eq!(CalibrationPhase::Rotate, c.phase());
+    assert_eq!(2.0 / 16.0, c.progress());
+}
@@ -0,0 +1,155 @@
+use crate::*;
+
+use std::f32::consts::{PI, TAU};
+
+
+
+/// Quantizes thumbstick coordinates into a [`Direction4`] or [`Direction8`], with hysteresis to avoid flickering.
+///
+/// *   A direction is only entered once the stick is pushed past [`activation_radius`](Self::activation_radius).
+/// *   A direction is only released once the stick falls back within [`release_radius`](Self::release_radius).
+/// *   While held, the current direction is kept until the stick angle leaves the direction's sector by more than [`hysteresis`](Self::hysteresis).
+///
+/// The quantizer itself is stateless: pass in the previously returned direction each frame.
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let quantizer = DirectionQuantizer::MENU;
+/// let mut dir = Direction4::Neutral;
+/// # for _ in 0 .. 1 {
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// let prev = dir;
+/// dir = quantizer.quantize4(dir, state.left_thumb_x, state.left_thumb_y);
+/// if dir != prev && dir.is_held() {
+///     // move menu focus
+/// }
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirectionQuantizer {
+    /// Normalized magnitude (`0.0 ..= 1.0`) the stick must exceed to start holding a direction.
+    pub activation_radius:  f32,
+
+    /// Normalized magnitude (`0.0 ..= 1.0`) the stick must fall below to release a held direction.
+    /// Should be less than or equal to [`activation_radius`](Self::activation_radius).
+    pub release_radius:     f32,
+
+    /// Extra angle (in radians) the stick may stray past the edge of a held direction's sector before switching directions.
+    pub hysteresis:         f32,
+}
+
+impl Default for DirectionQuantizer { fn default() -> Self { Self::LEFT_THUMB } }
+
+impl DirectionQuantizer {
+    /// Activates at [`Gamepad::LEFT_THUMB_DEADZONE`], with a small amount of radial and angular hysteresis.
+    pub const LEFT_THUMB : Self = Self {
+        activation_radius:  Gamepad::LEFT_THUMB_DEADZONE as f32 / 32767.0,
+        release_radius:     Gamepad::LEFT_THUMB_DEADZONE as f32 / 32767.0 * 0.8,
+        hysteresis:         5.0 * PI / 180.0,
+    };
+
+    /// Activates at [`Gamepad::RIGHT_THUMB_DEADZONE`], with a small amount of radial and angular hysteresis.
+    pub const RIGHT_THUMB : Self = Self {
+        activation_radius:  Gamepad::RIGHT_THUMB_DEADZONE as f32 / 32767.0,
+        release_radius:     Gamepad::RIGHT_THUMB_DEADZONE as f32 / 32767.0 * 0.8,
+        hysteresis:         5.0 * PI / 180.0,
+    };
+
+    /// Requires a deliberate push (half way) to activate, and generous hysteresis, for UI navigation.
+    pub const MENU : Self = Self {
+        activation_radius:  0.5,
+        release_radius:     0.35,
+        hysteresis:         10.0 * PI / 180.0,
+    };
+
+    /// Quantize `(x, y)` into an 8-way direction, given the `previous` result.
+    pub fn quantize8(&self, previous: Direction8, x: i16, y: i16) -> Direction8 {
+        self.quantize(previous.angle(), x, y, 8).map_or(Direction8::Neutral, |sector| SECTORS8[sector])
+    }
+
+    /// Quantize `(x, y)` into a 4-way direction, given the `previous` result.
+    pub fn quantize4(&self, previous: Direction4, x: i16, y: i16) -> Direction4 {
+        self.quantize(previous.angle(), x, y, 4).map_or(Direction4::Neutral, |sector| SECTORS4[sector])
+    }
+
+    /// Quantize `(x, y)` into one of `sectors` sectors (sector `0` centered on +X, counterclockwise), or [`None`] if neutral.
+    fn quantize(&self, previous: Option<f32>, x: i16, y: i16, sectors: usize) -> Option<usize> {
+        let (x, y) = (f32::from(x) / 32767.0, f32::from(y) / 32767.0);
+        let magnitude = x.hypot(y).min(1.0);
+        let angle = y.atan2(x);
+        let sector_size = TAU / sectors as f32;
+
+        match previous {
+            None if magnitude <= self.activation_radius     => None,
+            Some(_) if magnitude <= self.release_radius     => None,
+            Some(prev) if angle_between(prev, angle) <= sector_size / 2.0 + self.hysteresis => {
+                Some(((prev / sector_size).round() as isize).rem_euclid(sectors as isize) as usize)
+            },
+            _ => Some(((angle / sector_size).round() as isize).rem_euclid(sectors as isize) as usize),
+        }
+    }
+}
+
+/// Absolute difference between two angles, in radians (`0 ..= π`.)
+fn angle_between(a: f32, b: f32) -> f32 { ((a - b + PI).rem_euclid(TAU) - PI).abs() }
+
+const SECTORS8 : [Direction8; 8] = [Direction8::Right, Direction8::UpRight, Direction8::Up, Direction8::UpLeft, Direction8::Left, Direction8::DownLeft, Direction8::Down, Direction8::DownRight];
+const SECTORS4 : [Direction4; 4] = [Direction4::Right, Direction4::Up, Direction4::Left, Direction4::Down];
+
+
+
+#[cfg(test)] fn polar(degrees: f32, magnitude: f32) -> (i16, i16) {
+    let r = degrees.to_radians();
+    ((r.cos() * magnitude * 32767.0) as i16, (r.sin() * magnitude * 32767.0) as i16)
+}
+
+#[test] fn test_quantize8_sectors() {
+    let q = DirectionQuantizer { activation_radius: 0.5, release_radius: 0.5, hysteresis: 0.0 };
+    for (i, &dir) in SECTORS8.iter().enumerate() {
+        for offset in [-20.0, 0.0, 20.0] {
+            let (x, y) = polar(i as f32 * 45.0 + offset, 0.9);
+            assert_eq!(dir, q.quantize8(Direction8::Neutral, x, y), "angle {}", i as f32 * 45.0 + offset);
+        }
+    }
+    assert_eq!(Direction8::DownLeft, q.quantize8(Direction8::Neutral, i16::MIN, i16::MIN));
+    assert_eq!(Direction8::Neutral,  q.quantize8(Direction8::Neutral, 0, 0));
+}
+
+#[test] fn test_radial_hysteresis() {
+    let q = DirectionQuantizer { activation_radius: 0.5, release_radius: 0.3, hysteresis: 0.0 };
+    let (x, y) = polar(90.0, 0.4);
+    assert_eq!(Direction4::Neutral, q.quantize4(Direction4::Neutral, x, y), "below activation radius");
+    assert_eq!(Direction4::Up,      q.quantize4(Direction4::Up,      x, y), "above release radius");
+    let (x, y) = polar(90.0, 0.2);
+    assert_eq!(Direction4::Neutral, q.quantize4(Direction4::Up,      x, y), "below release radius");
+}
+
+#[test] fn test_angular_hysteresis() {
+    let q = DirectionQuantizer { activation_radius: 0.5, release_radius: 0.5, hysteresis: 10f32.to_radians() };
+    // 4-way boundary between right and up is at 45°
+    let (x, y) = polar(50.0, 1.0);
+    assert_eq!(Direction4::Up,      q.quantize4(Direction4::Neutral, x, y));
+    assert_eq!(Direction4::Right,   q.quantize4(Direction4::Right,   x, y), "within hysteresis of right");
+    let (x, y) = polar(60.0, 1.0);
+    assert_eq!(Direction4::Up,      q.quantize4(Direction4::Right,   x, y), "beyond hysteresis of right");
+
+    // down-right (-45°) held, stick just past the -22.5° boundary into right
+    let (x, y) = polar(-17.0, 1.0);
+    assert_eq!(Direction8::DownRight, q.quantize8(Direction8::DownRight, x, y));
+    assert_eq!(Direction8::Right,     q.quantize8(Direction8::Neutral,   x, y));
+}
+
+#[test] fn test_no_flicker() {
+    // Jitter back and forth across the 8-way boundary at 22.5°: with hysteresis, the direction should only change once (out of neutral.)
+    let q = DirectionQuantizer::LEFT_THUMB;
+    let mut dir = Direction8::Neutral;
+    let mut changes = 0;
+    for i in 0 .. 100 {
+        let (x, y) = polar(22.5 + if i % 2 == 0 { 2.0 } else { -2.0 }, 0.9);
+        let next = q.quantize8(dir, x, y);
+        if next != dir { changes += 1 }
+        dir = next;
+    }
+    assert_eq!(changes, 1);
+}
@@ -0,0 +1,182 @@
+use crate::*;
+
+use std::time::{Duration, Instant};
+
+
+
+/// A thumbstick that appears to rest outside its dead zone, as reported by [`DriftDetector::drift`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drift {
+    /// The drifting stick.
+    pub stick:      ThumbStick,
+
+    /// Estimated raw X-coordinate the stick rests at.
+    pub offset_x:   i16,
+
+    /// Estimated raw Y-coordinate the stick rests at.
+    pub offset_y:   i16,
+
+    /// How confident the detector is that this is drift, and not a player deliberately holding the stick (`0.0 ..= 1.0`.)
+    pub confidence: f32,
+}
+
+impl Drift {
+    /// Distance of [`offset_x`](Self::offset_x), [`offset_y`](Self::offset_y) from center, in raw units.
+    pub fn magnitude(&self) -> f32 { f32::from(self.offset_x).hypot(f32::from(self.offset_y)) }
+}
+
+/// Watches idle thumbsticks for drift, so you can proactively suggest recalibration (see [`Calibrator`].)
+///
+/// A stick is considered idle while no [`Gamepad::buttons`] change, and the stick stays within [`stillness`](Self::stillness) of where it came to rest.
+/// Players rarely hold a stick perfectly still while not pressing anything for long, so the longer a stick idles outside of
+/// [`deadzone`](Self::deadzone), the more confident the detector becomes that the stick is drifting.
+/// Drift is small, so a stick held beyond [`max_offset`](Self::max_offset) (e.g. pushed fully to one side) is never considered idle.
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// # use std::time::Instant;
+/// let mut detector = DriftDetector::default();
+/// # for _ in 0 .. 1 {
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// detector.sample(&state, Instant::now());
+/// for stick in ThumbStick::ALL {
+///     if let Some(drift) = detector.drift(stick).filter(|d| d.confidence >= 0.9) {
+///         println!("{stick:?} stick appears to be drifting: ({}, {}) - recalibrate?", drift.offset_x, drift.offset_y);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DriftDetector {
+    /// Dead zone magnitude of each stick, indexed by <code>[ThumbStick] as usize</code>.  Defaults to [`ThumbStick::default_deadzone`].
+    pub deadzone:       [i16; 2],
+
+    /// Largest rest offset of each stick that may still be drift, indexed by <code>[ThumbStick] as usize</code>.  Defaults to twice [`deadzone`](Self::deadzone).
+    pub max_offset:     [i16; 2],
+
+    /// How far (in raw units) a stick may wander from where it came to rest while still being considered idle.
+    pub stillness:      i16,
+
+    /// How long a stick must idle outside its dead zone before [`Drift::confidence`] reaches `1.0`.
+    pub settle_time:    Duration,
+
+    buttons:            Option<Buttons>,
+    idle:               [Option<Idle>; 2],
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Idle {
+    start:      Instant,
+    last:       Instant,
+    rest:       (i16, i16),
+    sum:        (i64, i64),
+    count:      u32,
+    outside:    u32,
+}
+
+impl Default for DriftDetector { fn default() -> Self { Self::new() } }
+
+impl DriftDetector {
+    /// Create a detector with default settings.
+    pub fn new() -> Self {
+        let deadzone = [ThumbStick::Left.default_deadzone(), ThumbStick::Right.default_deadzone()];
+        Self {
+            deadzone,
+            max_offset:     deadzone.map(|d| d.saturating_mul(2)),
+            stillness:      2048,
+            settle_time:    Duration::from_secs(5),
+            buttons:        None,
+            idle:           [None; 2],
+        }
+    }
+
+    /// Feed the next `gamepad` sample, taken at `timestamp`.
+    pub fn sample(&mut self, gamepad: &Gamepad, timestamp: Instant) {
+        if self.buttons.replace(gamepad.buttons) != Some(gamepad.buttons) { self.idle = [None; 2] }
+
+        for stick in ThumbStick::ALL {
+            let (x, y) = stick.read(gamepad);
+            let deadzone = f32::from(self.deadzone[stick as usize]);
+            let magnitude = f32::from(x).hypot(f32::from(y));
+            let outside = magnitude > deadzone;
+            let idle = &mut self.idle[stick as usize];
+            if magnitude > f32::from(self.max_offset[stick as usize]) { *idle = None; continue }
+
+            match idle {
+                Some(i) if (f32::from(x) - f32::from(i.rest.0)).hypot(f32::from(y) - f32::from(i.rest.1)) <= f32::from(self.stillness) => {
+                    i.last      = timestamp;
+                    i.sum       = (i.sum.0 + i64::from(x), i.sum.1 + i64::from(y));
+                    i.count     += 1;
+                    i.outside   += u32::from(outside);
+                },
+                _ => *idle = Some(Idle { start: timestamp, last: timestamp, rest: (x, y), sum: (x.into(), y.into()), count: 1, outside: u32::from(outside) }),
+            }
+        }
+    }
+
+    /// Returns the estimated drift of `stick`, if it's currently idling outside of its dead zone.
+    pub fn drift(&self, stick: ThumbStick) -> Option<Drift> {
+        let idle = self.idle[stick as usize]?;
+        let n = i64::from(idle.count);
+        let drift = Drift { stick, offset_x: (idle.sum.0 / n) as i16, offset_y: (idle.sum.1 / n) as i16, confidence: 0.0 };
+        if drift.magnitude() <= f32::from(self.deadzone[stick as usize]) { return None }
+
+        let settle = self.settle_time.as_secs_f32();
+        let time = if settle <= 0.0 { 1.0 } else { (idle.last.saturating_duration_since(idle.start).as_secs_f32() / settle).min(1.0) };
+        let consistency = idle.outside as f32 / idle.count as f32;
+        Some(Drift { confidence: time * consistency, .. drift })
+    }
+
+    /// Forget all samples (e.g. after the controller reconnects, or is recalibrated.)
+    pub fn reset(&mut self) {
+        self.buttons = None;
+        self.idle = [None; 2];
+    }
+}
+
+
+
+#[test] fn test_drift() {
+    let t0 = Instant::now();
+    let ms = |ms| t0 + Duration::from_millis(ms);
+    let pad = |x: i16, y: i16| Gamepad { left_thumb_x: x, left_thumb_y: y, right_thumb_x: 100, .. Gamepad::default() };
+
+    let mut d = DriftDetector::new();
+    for i in 0 ..= 50 { d.sample(&pad(9000 + (i % 3) as i16 * 50, -1000), ms(i * 100)); }
+    let drift = d.drift(ThumbStick::Left).unwrap();
+    assert_eq!((drift.offset_x, drift.offset_y), (9050, -1000));
+    assert_eq!(drift.confidence, 1.0);
+    assert_eq!(None, d.drift(ThumbStick::Right), "within dead zone");
+
+    // pressing a button restarts the idle window
+    d.sample(&Gamepad { buttons: Buttons::A, .. pad(9000, -1000) }, ms(5100));
+    let drift = d.drift(ThumbStick::Left).unwrap();
+    assert_eq!(drift.confidence, 0.0);
+    d.sample(&Gamepad { buttons: Buttons::A, .. pad(9000, -1000) }, ms(6350));
+    assert_eq!(d.drift(ThumbStick::Left).unwrap().confidence, 0.25);
+
+    // so does moving the stick
+    d.sample(&Gamepad { buttons: Buttons::A, .. pad(0, 0) }, ms(6400));
+    assert_eq!(None, d.drift(ThumbStick::Left));
+
+    d.reset();
+    assert_eq!(None, d.drift(ThumbStick::Left));
+}
+
+#[test] fn test_held_not_drift() {
+    // A player sweeping the stick around never idles long enough to build confidence.
+    let t0 = Instant::now();
+    let mut d = DriftDetector::new();
+    for i in 0 .. 100u64 {
+        let r = (i as f32 * 0.3).sin();
+        d.sample(&Gamepad { left_thumb_x: (r * 30000.0) as i16, .. Gamepad::default() }, t0 + Duration::from_millis(i * 16));
+    }
+    assert!(d.drift(ThumbStick::Left).map_or(true, |d| d.confidence < 0.1));
+
+    // A player holding the stick fully to one side isn't drift, no matter how long they hold it.
+    let mut d = DriftDetector::new();
+    for i in 0 ..= 100u64 { d.sample(&Gamepad { left_thumb_x: 32767, right_thumb_y: -25000, .. Gamepad::default() }, t0 + Duration::from_millis(i * 100)); }
+    assert_eq!(None, d.drift(ThumbStick::Left));
+    assert_eq!(None, d.drift(ThumbStick::Right));
+}
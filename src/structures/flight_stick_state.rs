@@ -0,0 +1,110 @@
+use crate::*;
+#[cfg(test)] use bytemuck::Zeroable;
+
+
+
+/// A [`Gamepad`] read as a flight stick ([`DevSubType::FlightStick`].)
+///
+/// | Axis              | Source                        | Range                 |
+/// | ----------------- | ----------------------------- | --------------------- |
+/// | [`roll`]          | [`Gamepad::left_thumb_x`]     | `-1.0` (left) ..= `+1.0` (right)
+/// | [`pitch`]         | [`Gamepad::left_thumb_y`]     | `-1.0` (stick pulled back) ..= `+1.0` (stick pushed forward)
+/// | [`yaw`]           | [`Gamepad::right_thumb_x`]    | `-1.0` (left rudder) ..= `+1.0` (right rudder)
+/// | [`throttle`]      | [`Gamepad::right_thumb_y`]    | `0.0` (idle) ..= `1.0` (full)
+/// | [`hat`]           | D-pad                         | [`Direction8`]
+///
+/// [`roll`]:       Self::roll
+/// [`pitch`]:      Self::pitch
+/// [`yaw`]:        Self::yaw
+/// [`throttle`]:   Self::throttle
+/// [`hat`]:        Self::hat
+///
+/// No dead zone is applied &mdash; flight sticks generally have much tighter centering than thumbsticks,
+/// and flight sims generally want every last bit of precision.
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let gamepad = 0;
+/// let caps  = xinput::get_capabilities(gamepad, Flag::None).ok();
+/// let state = xinput::get_state(gamepad).unwrap_or_default();
+/// if let Some(stick) = caps.and_then(|caps| FlightStickState::from_capabilities(&caps, &state)) {
+///     println!("pitch={:+.2} roll={:+.2} throttle={:.0}%", stick.pitch, stick.roll, stick.throttle * 100.0);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Default)]
+pub struct FlightStickState {
+    /// Normalized pitch (`-1.0 ..= +1.0`, pushed forward is positive.)
+    pub pitch:      f32,
+
+    /// Normalized roll (`-1.0 ..= +1.0`, right is positive.)
+    pub roll:       f32,
+
+    /// Normalized yaw / rudder (`-1.0 ..= +1.0`, right is positive.)
+    pub yaw:        f32,
+
+    /// Normalized throttle (`0.0 ..= 1.0`.)
+    pub throttle:   f32,
+
+    /// The hat switch, read from the D-pad.
+    pub hat:        Direction8,
+
+    /// All other buttons (fire, etc.) as reported by XInput.
+    pub buttons:    Buttons,
+}
+
+impl FlightStickState {
+    /// [`DevSubType`]s that [`FlightStickState::from_capabilities`] will accept.
+    pub const SUB_TYPES : [DevSubType; 1] = [DevSubType::FlightStick];
+
+    /// Read `gamepad` as a flight stick, regardless of what kind of device it actually is.
+    pub fn from_gamepad(gamepad: &Gamepad) -> Self {
+        Self {
+            pitch:      axis(gamepad.left_thumb_y),
+            roll:       axis(gamepad.left_thumb_x),
+            yaw:        axis(gamepad.right_thumb_x),
+            throttle:   (f32::from(gamepad.right_thumb_y) + 32768.0) / 65535.0,
+            hat:        Direction8::from_dpad(gamepad.buttons),
+            buttons:    gamepad.buttons,
+        }
+    }
+
+    /// Read `gamepad` as a flight stick, if <code>caps.[sub_type](Capabilities::sub_type)</code> is one of [`FlightStickState::SUB_TYPES`].
+    pub fn from_capabilities(caps: &Capabilities, gamepad: &Gamepad) -> Option<Self> {
+        Self::SUB_TYPES.contains(&caps.sub_type).then(|| Self::from_gamepad(gamepad))
+    }
+}
+
+impl From<Gamepad> for FlightStickState { fn from(gamepad: Gamepad) -> Self { Self::from_gamepad(&gamepad) } }
+
+/// Map `-32768 ..= 32767` to `-1.0 ..= 1.0` (`-32768` and `-32767` both map to `-1.0`.)
+fn axis(value: i16) -> f32 { (f32::from(value) / 32767.0).max(-1.0) }
+
+#[test] fn test_from_gamepad() {
+    let stick = FlightStickState::from_gamepad(&Gamepad {
+        buttons:        Buttons::DPadUp | Buttons::A,
+        left_thumb_x:   i16::MIN,
+        left_thumb_y:   i16::MAX,
+        right_thumb_x:  0,
+        right_thumb_y:  i16::MIN,
+        .. Gamepad::default()
+    });
+    assert_eq!(stick.roll,      -1.0);
+    assert_eq!(stick.pitch,     1.0);
+    assert_eq!(stick.yaw,       0.0);
+    assert_eq!(stick.throttle,  0.0);
+    assert_eq!(stick.hat,       Direction8::Up);
+    assert!(stick.buttons.any_held(Buttons::A));
+
+    let stick = FlightStickState::from_gamepad(&Gamepad { right_thumb_y: i16::MAX, .. Gamepad::default() });
+    assert_eq!(stick.throttle, 1.0);
+}
+
+#[test] fn test_from_capabilities() {
+    let mut caps = Capabilities::zeroed();
+    caps.sub_type = DevSubType::FlightStick;
+    assert!(FlightStickState::from_capabilities(&caps, &Gamepad::default()).is_some());
+    caps.sub_type = DevSubType::ArcadeStick;
+    assert!(FlightStickState::from_capabilities(&caps, &Gamepad::default()).is_none());
+}
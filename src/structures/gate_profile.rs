@@ -0,0 +1,192 @@
+use crate::*;
+
+use std::f32::consts::TAU;
+
+
+
+const SECTORS : usize = 32;
+
+/// The reach of a thumbstick's gate in every direction, used to remap its coordinates onto a unit circle (and back.)
+///
+/// Build one from a known [`GateShape`] with [`GateProfile::from_shape`], or from recorded samples with a [`GateAnalyzer`].
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let profile = GateProfile::from_shape(GateShape::Square);
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// let (x, y) = profile.to_circle(state.left_thumb_x, state.left_thumb_y);
+/// // diagonal movement is now no faster than cardinal movement
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GateProfile {
+    reach: [f32; SECTORS],
+}
+
+impl Default for GateProfile { fn default() -> Self { Self::from_shape(GateShape::Circle) } }
+
+impl GateProfile {
+    /// The profile of an ideal `shape`.
+    pub fn from_shape(shape: GateShape) -> Self {
+        Self { reach: std::array::from_fn(|i| shape.reach(sector_angle(i))) }
+    }
+
+    /// Build a profile from `(angle, reach)` points measured along the edge of the gate (angle in radians, reach normalized such that `32767` = `1.0`.)
+    /// Reach between points is linearly interpolated.  Returns [`None`] if `points` is empty.
+    pub fn from_points(points: impl IntoIterator<Item = (f32, f32)>) -> Option<Self> {
+        let mut points = points.into_iter().map(|(a, r)| (a.rem_euclid(TAU), r)).collect::<Vec<_>>();
+        if points.is_empty() { return None }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Some(Self { reach: std::array::from_fn(|i| {
+            let angle = sector_angle(i);
+            let next = points.iter().position(|&(a, _)| a >= angle).unwrap_or(points.len());
+            let (a0, r0) = if next == 0 { let (a, r) = points[points.len()-1]; (a - TAU, r) } else { points[next-1] };
+            let (a1, r1) = if next == points.len() { let (a, r) = points[0]; (a + TAU, r) } else { points[next] };
+            if a1 - a0 <= f32::EPSILON { r1 } else { r0 + (r1 - r0) * (angle - a0) / (a1 - a0) }
+        })})
+    }
+
+    /// Normalized distance from center to the edge of the gate, in the direction of `angle` (radians, counterclockwise from right.)
+    pub fn reach(&self, angle: f32) -> f32 {
+        let s = angle.rem_euclid(TAU) / TAU * SECTORS as f32;
+        let i = (s as usize).min(SECTORS - 1);
+        let t = s - i as f32;
+        self.reach[i] * (1.0 - t) + self.reach[(i + 1) % SECTORS] * t
+    }
+
+    /// The [`GateShape`] this profile most closely resembles.
+    pub fn shape(&self) -> GateShape {
+        let error = |shape: GateShape| (0 .. SECTORS).map(|i| (self.reach[i] - shape.reach(sector_angle(i))).powi(2)).sum::<f32>();
+        GateShape::ALL.into_iter().min_by(|a, b| error(*a).total_cmp(&error(*b))).unwrap_or(GateShape::Circle)
+    }
+
+    /// Remap raw coordinates within this gate onto a unit circle (radius `32767`.)
+    pub fn to_circle(&self, x: i16, y: i16) -> (i16, i16) {
+        let stick = Stick::from_i16(x, y);
+        let reach = self.reach(stick.angle);
+        let magnitude = if reach <= 0.0 { 0.0 } else { (stick.magnitude / reach).min(1.0) };
+        Stick { magnitude, .. stick }.to_i16()
+    }
+
+    /// Remap unit circle coordinates back onto this gate (the inverse of [`GateProfile::to_circle`].)
+    pub fn from_circle(&self, x: i16, y: i16) -> (i16, i16) {
+        let stick = Stick::from_i16(x, y);
+        Stick { magnitude: stick.magnitude.min(1.0) * self.reach(stick.angle), .. stick }.to_i16()
+    }
+
+    /// Remap `stick` of `gamepad` onto a unit circle, leaving the other stick, triggers, and buttons untouched.
+    pub fn apply(&self, stick: ThumbStick, gamepad: &Gamepad) -> Gamepad {
+        let mut gamepad = *gamepad;
+        let (x, y) = stick.read(&gamepad);
+        stick.write(&mut gamepad, self.to_circle(x, y));
+        gamepad
+    }
+}
+
+fn sector_angle(i: usize) -> f32 { i as f32 * TAU / SECTORS as f32 }
+
+
+
+/// Estimates a thumbstick's [`GateProfile`] from recorded [`Gamepad`] samples, as the user rotates the stick against its gate.
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let mut analyzer = GateAnalyzer::new(ThumbStick::Left);
+/// # for _ in 0 .. 1 {
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// analyzer.sample(&state);
+/// if let Some(profile) = analyzer.profile() {
+///     println!("left stick gate: {:?}", profile.shape());
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GateAnalyzer {
+    /// The stick being analyzed.
+    pub stick:          ThumbStick,
+
+    /// Minimum normalized magnitude a sample must have to count towards [`GateAnalyzer::coverage`].
+    pub min_magnitude:  f32,
+
+    edge:               [Option<Stick>; BINS],
+}
+
+/// [`GateAnalyzer`] records the furthest sample in each of these (finer than [`SECTORS`]) bins, to minimize bias towards the edges of each sector.
+const BINS : usize = SECTORS * 4;
+
+impl GateAnalyzer {
+    /// Start analyzing `stick`.
+    pub fn new(stick: ThumbStick) -> Self { Self { stick, min_magnitude: 0.5, edge: [None; BINS] } }
+
+    /// Record a sample.
+    pub fn sample(&mut self, gamepad: &Gamepad) {
+        let stick = Stick::from_gamepad(gamepad, self.stick);
+        if stick.magnitude < self.min_magnitude { return }
+        let i = ((stick.angle_positive() / TAU * BINS as f32).round() as usize) % BINS;
+        let edge = &mut self.edge[i];
+        if edge.map_or(true, |e| stick.magnitude > e.magnitude) { *edge = Some(stick) }
+    }
+
+    /// Fraction of directions (`0.0 ..= 1.0`) the stick has been pushed towards so far.
+    pub fn coverage(&self) -> f32 {
+        let bins_per_sector = (BINS / SECTORS) as f32;
+        let sectors = self.edge.iter().enumerate().filter(|(_, e)| e.is_some()).fold(0u32, |mask, (bin, _)| mask | 1 << ((bin as f32 / bins_per_sector).round() as usize % SECTORS));
+        sectors.count_ones() as f32 / SECTORS as f32
+    }
+
+    /// The estimated profile, once [`GateAnalyzer::coverage`] reaches `1.0`.
+    pub fn profile(&self) -> Option<GateProfile> {
+        if self.coverage() < 1.0 { return None }
+        GateProfile::from_points(self.edge.iter().flatten().map(|e| (e.angle, e.magnitude)))
+    }
+
+    /// Discard all samples.
+    pub fn reset(&mut self) { self.edge = [None; BINS] }
+}
+
+
+
+#[test] fn test_to_circle() {
+    let square = GateProfile::from_shape(GateShape::Square);
+    assert_eq!(GateShape::Square, square.shape());
+    let (x, y) = square.to_circle(32767, 32767);
+    assert!((i32::from(x) - 23170).abs() <= 2 && (i32::from(y) - 23170).abs() <= 2, "({x}, {y})");
+    assert_eq!((32767, 0), square.to_circle(32767, 0));
+    assert_eq!((0, 0), square.to_circle(0, 0));
+
+    for shape in GateShape::ALL {
+        let profile = GateProfile::from_shape(shape);
+        for (x, y) in [(32767, 0), (20000, 20000), (-12345, 23456), (-30000, -5000), (1000, -200)] {
+            let (cx, cy) = profile.to_circle(x, y);
+            if Stick::from_i16(x, y).magnitude > profile.reach(Stick::from_i16(x, y).angle) { continue }
+            let (rx, ry) = profile.from_circle(cx, cy);
+            assert!((i32::from(rx) - i32::from(x)).abs() <= 8 && (i32::from(ry) - i32::from(y)).abs() <= 8, "{shape:?}: ({x}, {y}) => ({cx}, {cy}) => ({rx}, {ry})");
+        }
+    }
+
+    let gamepad = square.apply(ThumbStick::Right, &Gamepad { left_thumb_x: 32767, left_thumb_y: 32767, right_thumb_x: 32767, right_thumb_y: 32767, .. Gamepad::default() });
+    assert_eq!((gamepad.left_thumb_x, gamepad.left_thumb_y), (32767, 32767));
+    assert!(gamepad.right_thumb_x < 23200);
+}
+
+#[test] fn test_analyzer() {
+    for shape in GateShape::ALL {
+        let mut analyzer = GateAnalyzer::new(ThumbStick::Left);
+        for deg in 0 .. 360 {
+            if deg < 340 { assert_eq!(None, analyzer.profile()) }
+            let angle = (deg as f32 + 0.3).to_radians();
+            let (x, y) = Stick { magnitude: shape.reach(angle), angle }.to_i16();
+            analyzer.sample(&Gamepad { left_thumb_x: x, left_thumb_y: y, .. Gamepad::default() });
+            analyzer.sample(&Gamepad { left_thumb_x: x / 3, left_thumb_y: y / 3, .. Gamepad::default() });
+        }
+        assert_eq!(1.0, analyzer.coverage());
+        let profile = analyzer.profile().unwrap();
+        assert_eq!(shape, profile.shape());
+        let (x, y) = profile.to_circle(32767, 0);
+        assert!(x > 32600 && y.abs() < 100, "{shape:?}: ({x}, {y})");
+        analyzer.reset();
+        assert_eq!(0.0, analyzer.coverage());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::*;
+
+use std::f32::consts::{PI, TAU};
+
+
+
+/// A thumbstick position in polar coordinates.
+///
+/// Coordinates are normalized such that `±32767` maps to `±1.0`.
+/// Note that [`magnitude`](Self::magnitude) may exceed `1.0` on controllers with square or octagonal gates
+/// (a stick pushed fully into a corner may report `(32767, 32767)`, for a magnitude of `≈1.414`.)
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// let camera_yaw = 0.5; // radians
+/// let stick = Stick::from_gamepad_left(&state);
+/// if stick.magnitude > 0.25 {
+///     let world = stick.rotate(camera_yaw);
+///     let (dx, dy) = world.to_xy();
+///     // move player by (dx, dy) * speed...
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stick {
+    /// Distance from center (`0.0` = centered, `1.0` = fully pushed in a cardinal direction.)
+    pub magnitude:  f32,
+
+    /// Angle in radians, counterclockwise from right (`0` = right, `π/2` = up, `±π` = left, `-π/2` = down.)
+    pub angle:      f32,
+}
+
+impl Stick {
+    /// Read the left thumbstick of `gamepad`.
+    pub fn from_gamepad_left(gamepad: &Gamepad) -> Self { Self::from_gamepad(gamepad, ThumbStick::Left) }
+
+    /// Read the right thumbstick of `gamepad`.
+    pub fn from_gamepad_right(gamepad: &Gamepad) -> Self { Self::from_gamepad(gamepad, ThumbStick::Right) }
+
+    /// Read `stick` of `gamepad`.
+    pub fn from_gamepad(gamepad: &Gamepad, stick: ThumbStick) -> Self {
+        let (x, y) = stick.read(gamepad);
+        Self::from_i16(x, y)
+    }
+
+    /// Convert raw thumbstick coordinates (`-32768 ..= 32767`) into polar coordinates.
+    pub fn from_i16(x: i16, y: i16) -> Self {
+        Self::from_xy(f32::from(x) / 32767.0, f32::from(y) / 32767.0)
+    }
+
+    /// Convert normalized cartesian coordinates into polar coordinates.
+    pub fn from_xy(x: f32, y: f32) -> Self {
+        let magnitude = x.hypot(y);
+        let angle = if magnitude == 0.0 { 0.0 } else { y.atan2(x) };
+        Self { magnitude, angle }
+    }
+
+    /// Convert to normalized cartesian coordinates.
+    pub fn to_xy(self) -> (f32, f32) {
+        let (sin, cos) = self.angle.sin_cos();
+        (cos * self.magnitude, sin * self.magnitude)
+    }
+
+    /// Convert to raw thumbstick coordinates, clamping each axis to `-32768 ..= 32767`.
+    pub fn to_i16(self) -> (i16, i16) {
+        let (x, y) = self.to_xy();
+        let clamp = |v: f32| (v * 32767.0).round().clamp(-32768.0, 32767.0) as i16;
+        (clamp(x), clamp(y))
+    }
+
+    /// Write this position into `stick` of `gamepad` (see [`Stick::to_i16`].)
+    pub fn write(self, gamepad: &mut Gamepad, stick: ThumbStick) { stick.write(gamepad, self.to_i16()) }
+
+    /// [`angle`](Self::angle), in degrees.
+    pub fn angle_degrees(self) -> f32 { self.angle.to_degrees() }
+
+    /// Angle in radians, normalized to `0 ..= 2π` instead of `-π ..= π`.
+    pub fn angle_positive(self) -> f32 { self.angle.rem_euclid(TAU) }
+
+    /// Snap the angle to the nearest multiple of `step` radians (e.g. `π/4` for 8-way movement.)
+    pub fn snap_angle(self, step: f32) -> Self {
+        if step <= 0.0 { return self }
+        Self { angle: (self.angle / step).round() * step, .. self }
+    }
+
+    /// Snap the angle to the nearest of `directions` evenly spaced directions, starting from right (e.g. `8` for 8-way movement.)
+    pub fn snap_directions(self, directions: u32) -> Self {
+        if directions == 0 { return self }
+        self.snap_angle(TAU / directions as f32)
+    }
+
+    /// Rotate counterclockwise by `radians`.
+    ///
+    /// For camera-relative movement, rotate by the camera's yaw (counterclockwise, from the world's +X axis) to get a world space direction.
+    pub fn rotate(self, radians: f32) -> Self {
+        let angle = (self.angle + radians + PI).rem_euclid(TAU) - PI;
+        Self { angle, .. self }
+    }
+
+    /// Rotate counterclockwise by `degrees`.
+    pub fn rotate_degrees(self, degrees: f32) -> Self { self.rotate(degrees.to_radians()) }
+
+    /// Clamp [`magnitude`](Self::magnitude) to at most `1.0` (e.g. to avoid faster diagonal movement on square gated controllers.)
+    pub fn clamp_magnitude(self) -> Self { Self { magnitude: self.magnitude.min(1.0), .. self } }
+}
+
+#[test] fn test_round_trip() {
+    for (x, y) in [(0, 0), (32767, 0), (0, -32767), (12345, -23456), (-32768, 32767), (i16::MIN, i16::MIN)] {
+        let stick = Stick::from_i16(x, y);
+        let (rx, ry) = stick.to_i16();
+        assert!((i32::from(rx) - i32::from(x)).abs() <= 1 && (i32::from(ry) - i32::from(y)).abs() <= 1, "({x}, {y}) => ({rx}, {ry})");
+    }
+}
+
+#[test] fn test_polar() {
+    let up = Stick::from_gamepad_left(&Gamepad { left_thumb_y: 32767, .. Gamepad::default() });
+    assert_eq!(up.magnitude, 1.0);
+    assert!((up.angle_degrees() - 90.0).abs() < 1e-4);
+
+    let corner = Stick::from_gamepad_right(&Gamepad { right_thumb_x: 32767, right_thumb_y: 32767, .. Gamepad::default() });
+    assert!((corner.magnitude - std::f32::consts::SQRT_2).abs() < 1e-4);
+    assert!((corner.clamp_magnitude().magnitude - 1.0).abs() < 1e-6);
+    assert_eq!((32767, 32767), corner.to_i16());
+
+    let down = Stick::from_i16(0, -32767);
+    assert!((down.angle_positive().to_degrees() - 270.0).abs() < 1e-3);
+    assert_eq!(Stick::from_i16(0, 0), Stick::default());
+}
+
+#[test] fn test_snap_and_rotate() {
+    let stick = Stick { magnitude: 1.0, angle: 50f32.to_radians() };
+    assert!((stick.snap_directions(8).angle_degrees() - 45.0).abs() < 1e-4);
+    assert!((stick.snap_directions(4).angle_degrees() - 90.0).abs() < 1e-4);
+    assert_eq!(stick.snap_directions(0), stick);
+
+    let rotated = stick.rotate_degrees(180.0);
+    assert!((rotated.angle_degrees() - -130.0).abs() < 1e-3);
+    assert_eq!(rotated.magnitude, 1.0);
+
+    let (x, y) = Stick { magnitude: 1.0, angle: 0.0 }.rotate(std::f32::consts::FRAC_PI_2).to_i16();
+    assert_eq!((0, 32767), (x, y));
+
+    let mut gamepad = Gamepad::default();
+    Stick { magnitude: 2.0, angle: 0.0 }.write(&mut gamepad, ThumbStick::Left);
+    assert_eq!(gamepad.left_thumb_x, 32767, "should clamp");
+}
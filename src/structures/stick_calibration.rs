@@ -0,0 +1,144 @@
+use crate::*;
+use bytemuck::{Pod, Zeroable};
+
+
+
+/// Calibration data for a single thumbstick, as produced by a [`Calibrator`].
+///
+/// Corrects an off-center resting position and asymmetric range of motion, then applies a scaled radial dead zone.
+/// This is a plain `#[repr(C)]` [`Pod`] struct: persist it with [`StickCalibration::to_bytes`] / [`StickCalibration::from_bytes`].
+///
+/// ### Example
+/// ```rust
+/// # use xinput::*;
+/// # let saved = StickCalibration::IDENTITY.to_bytes();
+/// let calibration = StickCalibration::from_bytes(&saved).unwrap_or_default(); // e.g. loaded from disk
+/// let state = xinput::get_state(0).unwrap_or_default();
+/// let gamepad = calibration.apply(ThumbStick::Left, &state);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Pod, Zeroable)]
+#[repr(C)] pub struct StickCalibration {
+    /// Raw X-coordinate the stick rests at when left alone.
+    pub center_x:   i16,
+
+    /// Raw Y-coordinate the stick rests at when left alone.
+    pub center_y:   i16,
+
+    /// Smallest raw X-coordinate the stick reaches (fully left.)
+    pub min_x:      i16,
+
+    /// Largest raw X-coordinate the stick reaches (fully right.)
+    pub max_x:      i16,
+
+    /// Smallest raw Y-coordinate the stick reaches (fully down.)
+    pub min_y:      i16,
+
+    /// Largest raw Y-coordinate the stick reaches (fully up.)
+    pub max_y:      i16,
+
+    /// Radial dead zone, applied after recentering and rescaling.
+    ///
+    /// | Value     | Description   |
+    /// | ---------:| ------------- |
+    /// |         0 | No dead zone
+    /// |      7849 | [`Gamepad::LEFT_THUMB_DEADZONE`]
+    /// |      8689 | [`Gamepad::RIGHT_THUMB_DEADZONE`]
+    pub deadzone:   i16,
+}
+
+impl Default for StickCalibration { fn default() -> Self { Self::IDENTITY } }
+
+impl StickCalibration {
+    /// Leaves coordinates untouched.
+    pub const IDENTITY : Self = Self { center_x: 0, center_y: 0, min_x: i16::MIN, max_x: i16::MAX, min_y: i16::MIN, max_y: i16::MAX, deadzone: 0 };
+
+    /// Size of [`StickCalibration::to_bytes`].
+    pub const BYTES : usize = 14;
+
+    /// Serialize as little endian `i16`s, in field order.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0u8; Self::BYTES];
+        let fields = [self.center_x, self.center_y, self.min_x, self.max_x, self.min_y, self.max_y, self.deadzone];
+        for (chunk, field) in bytes.chunks_exact_mut(2).zip(fields) { chunk.copy_from_slice(&field.to_le_bytes()) }
+        bytes
+    }
+
+    /// Deserialize from [`StickCalibration::to_bytes`].
+    /// Returns [`None`] if `bytes` is the wrong length, or describes an invalid calibration (see [`StickCalibration::is_valid`].)
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES { return None }
+        let f = |i: usize| i16::from_le_bytes([bytes[2*i], bytes[2*i+1]]);
+        let c = Self { center_x: f(0), center_y: f(1), min_x: f(2), max_x: f(3), min_y: f(4), max_y: f(5), deadzone: f(6) };
+        c.is_valid().then_some(c)
+    }
+
+    /// Returns `true` if the center lies strictly within the min/max range of each axis, and the dead zone isn't negative.
+    pub fn is_valid(&self) -> bool {
+        self.min_x < self.center_x && self.center_x < self.max_x &&
+        self.min_y < self.center_y && self.center_y < self.max_y &&
+        self.deadzone >= 0
+    }
+
+    /// Calibrate raw thumbstick coordinates.
+    pub fn apply_xy(&self, x: i16, y: i16) -> (i16, i16) {
+        let x = rescale(x, self.center_x, self.min_x, self.max_x);
+        let y = rescale(y, self.center_y, self.min_y, self.max_y);
+
+        let magnitude = x.hypot(y);
+        let deadzone = f32::from(self.deadzone.max(0));
+        if magnitude <= deadzone { return (0, 0) }
+        let scale = ((magnitude - deadzone) / (32767.0 - deadzone).max(1.0)) * 32767.0 / magnitude;
+        let clamp = |v: f32| (v * scale).round().clamp(-32768.0, 32767.0) as i16;
+        (clamp(x), clamp(y))
+    }
+
+    /// Calibrate `stick` of `gamepad`, leaving the other stick, triggers, and buttons untouched.
+    pub fn apply(&self, stick: ThumbStick, gamepad: &Gamepad) -> Gamepad {
+        let mut gamepad = *gamepad;
+        let (x, y) = stick.read(&gamepad);
+        stick.write(&mut gamepad, self.apply_xy(x, y));
+        gamepad
+    }
+}
+
+/// Recenter `v` around `center`, and rescale `min ..= max` to roughly `-32767 ..= 32767`.
+fn rescale(v: i16, center: i16, min: i16, max: i16) -> f32 {
+    let d = f32::from(v) - f32::from(center);
+    let span = if d >= 0.0 { f32::from(max) - f32::from(center) } else { f32::from(center) - f32::from(min) };
+    if span <= 0.0 { 0.0 } else { d * 32767.0 / span }
+}
+
+
+
+#[test] fn test_identity() {
+    let c = StickCalibration::default();
+    for (x, y) in [(0, 0), (32767, 0), (-32768, 0), (1234, -4321), (32767, 32767)] {
+        let (rx, ry) = c.apply_xy(x, y);
+        assert!((i32::from(rx) - i32::from(x)).abs() <= 1 && (i32::from(ry) - i32::from(y)).abs() <= 1, "({x}, {y}) => ({rx}, {ry})");
+    }
+}
+
+#[test] fn test_apply() {
+    let c = StickCalibration { center_x: 3000, center_y: -2000, min_x: -26000, max_x: 30000, min_y: -31000, max_y: 28000, deadzone: 2000 };
+    assert!(c.is_valid());
+    assert_eq!((0, 0),      c.apply_xy(3000, -2000));
+    assert_eq!((0, 0),      c.apply_xy(4000, -1500), "within dead zone");
+    assert_eq!((32767, 0),  c.apply_xy(30000, -2000));
+    assert_eq!((-32767, 0), c.apply_xy(-26000, -2000));
+    assert_eq!((0, 32767),  c.apply_xy(3000, 28000));
+    assert_eq!((0, -32767), c.apply_xy(3000, -31000));
+
+    let gamepad = Gamepad { right_thumb_x: 3000, right_thumb_y: -2000, left_thumb_x: 3000, .. Gamepad::default() };
+    let gamepad = c.apply(ThumbStick::Right, &gamepad);
+    assert_eq!((gamepad.left_thumb_x, gamepad.right_thumb_x, gamepad.right_thumb_y), (3000, 0, 0));
+}
+
+#[test] fn test_bytes() {
+    let c = StickCalibration { center_x: 3000, center_y: -2000, min_x: -26000, max_x: 30000, min_y: -31000, max_y: 28000, deadzone: 2000 };
+    assert_eq!(Some(c), StickCalibration::from_bytes(&c.to_bytes()));
+    assert_eq!(None,    StickCalibration::from_bytes(&c.to_bytes()[1..]));
+    assert_eq!(None,    StickCalibration::from_bytes(&StickCalibration { center_x: 31000, .. c }.to_bytes()));
+    assert_eq!(None,    StickCalibration::from_bytes(&[0; StickCalibration::BYTES]));
+    assert_eq!(bytemuck::bytes_of(&StickCalibration::zeroed()), &[0; StickCalibration::BYTES][..]);
+}
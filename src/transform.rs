@@ -0,0 +1,287 @@
+//! Accessibility transforms (turbo, toggle-to-hold, sticky modifiers, hold extenders) that rewrite [`Gamepad`] states between [`get_state`] and game logic.
+//!
+//! ### Example
+//! ```rust
+//! # use xinput::*;
+//! use xinput::transform::*;
+//! use std::time::Duration;
+//!
+//! let mut pipeline = Pipeline::new();
+//! pipeline.push(Turbo::new(Buttons::A, 10.0));
+//! pipeline.push(ToggleHold::new(Buttons::RightShoulder));
+//! pipeline.push(StickyModifiers::new(Buttons::LeftShoulder));
+//! pipeline.push(HoldExtender::new(Buttons::X, Duration::from_millis(500)));
+//!
+//! # for _ in 0 .. 1 {
+//! // instead of xinput::get_state(0):
+//! let state = pipeline.get_state(0).unwrap_or_default();
+//! # }
+//! ```
+
+use crate::*;
+use crate::filter::{Filter, GamepadFilter};
+
+use std::time::{Duration, Instant};
+
+
+
+/// Rewrites a stream of timestamped [`Gamepad`] states.
+pub trait Transform {
+    /// Transform the next `gamepad` state, sampled at `timestamp`.
+    fn transform(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Gamepad;
+
+    /// Forget all previous states (e.g. releasing any latched buttons.)
+    fn reset(&mut self);
+}
+
+impl<S: Filter, T: Filter> Transform for GamepadFilter<S, T> {
+    fn transform(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Gamepad { self.filter(gamepad, timestamp) }
+    fn reset(&mut self) { GamepadFilter::reset(self) }
+}
+
+fn bits(buttons: Buttons) -> impl Iterator<Item = (usize, u16)> {
+    let buttons = buttons.into_inner();
+    (0 .. 16).map(|bit| (bit, 1u16 << bit)).filter(move |(_, mask)| buttons & mask != 0)
+}
+
+fn with_buttons(gamepad: &Gamepad, buttons: u16) -> Gamepad { Gamepad { buttons: Buttons::from_unchecked(buttons), .. *gamepad } }
+
+
+
+/// Auto-repeat held [`buttons`](Self::buttons) at [`rate`](Self::rate) presses per second.
+///
+/// Each held button is pressed for the first half of each period, starting when it's physically pressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Turbo {
+    /// Buttons to auto-repeat.
+    pub buttons:    Buttons,
+
+    /// Presses per second.  `0.0` or less disables turbo.
+    pub rate:       f32,
+
+    pressed_at:     [Option<Instant>; 16],
+}
+
+impl Turbo {
+    /// Auto-repeat `buttons` at `rate` presses per second.
+    pub fn new(buttons: Buttons, rate: f32) -> Self { Self { buttons, rate, pressed_at: [None; 16] } }
+}
+
+impl Transform for Turbo {
+    fn transform(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Gamepad {
+        let mut out = gamepad.buttons.into_inner();
+        for (bit, mask) in bits(self.buttons) {
+            if out & mask == 0 { self.pressed_at[bit] = None; continue }
+            let pressed_at = *self.pressed_at[bit].get_or_insert(timestamp);
+            let phase = timestamp.saturating_duration_since(pressed_at).as_secs_f32() * self.rate.max(0.0);
+            if phase.fract() >= 0.5 { out &= !mask }
+        }
+        with_buttons(gamepad, out)
+    }
+
+    fn reset(&mut self) { self.pressed_at = [None; 16] }
+}
+
+
+
+/// Press [`buttons`](Self::buttons) once to hold them, and again to release them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToggleHold {
+    /// Buttons to toggle.
+    pub buttons:    Buttons,
+
+    prev:           u16,
+    latched:        u16,
+}
+
+impl ToggleHold {
+    /// Toggle `buttons`.
+    pub fn new(buttons: Buttons) -> Self { Self { buttons, prev: 0, latched: 0 } }
+
+    /// Buttons currently held by this transform.
+    pub fn latched(&self) -> Buttons { Buttons::from_unchecked(self.latched) }
+}
+
+impl Transform for ToggleHold {
+    fn transform(&mut self, gamepad: &Gamepad, _timestamp: Instant) -> Gamepad {
+        let mask = self.buttons.into_inner();
+        let now = gamepad.buttons.into_inner();
+        self.latched = (self.latched ^ (now & !self.prev)) & mask;
+        self.prev = now;
+        with_buttons(gamepad, (now & !mask) | self.latched)
+    }
+
+    fn reset(&mut self) { self.prev = 0; self.latched = 0 }
+}
+
+
+
+/// Sticky keys for gamepads: tap a [modifier](Self::modifiers) to keep it held until the next other button is pressed and released.
+///
+/// Tapping a latched modifier again releases it.  Holding a modifier while pressing another button works as usual, without latching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StickyModifiers {
+    /// Buttons that latch when tapped.
+    pub modifiers:  Buttons,
+
+    prev:           u16,
+    latched:        u16,
+    chorded:        bool,
+    consumed:       bool,
+}
+
+impl StickyModifiers {
+    /// Make `modifiers` sticky.
+    pub fn new(modifiers: Buttons) -> Self { Self { modifiers, prev: 0, latched: 0, chorded: false, consumed: false } }
+
+    /// Modifiers currently held by this transform.
+    pub fn latched(&self) -> Buttons { Buttons::from_unchecked(self.latched) }
+}
+
+impl Transform for StickyModifiers {
+    fn transform(&mut self, gamepad: &Gamepad, _timestamp: Instant) -> Gamepad {
+        let mods = self.modifiers.into_inner();
+        let now = gamepad.buttons.into_inner();
+        let (pressed, released) = (now & !self.prev, self.prev & !now);
+        if pressed & !mods != 0 {
+            if now & mods != 0 { self.chorded = true }
+            if self.latched != 0 { self.consumed = true }
+        }
+        if pressed & mods & self.latched != 0 { self.latched &= !(pressed & mods); self.chorded = true }
+        if released & mods != 0 && !self.chorded { self.latched |= released & mods }
+        if now & mods == 0 { self.chorded = false }
+        if self.consumed && now & !mods == 0 {
+            self.latched = 0;
+            self.consumed = false;
+        }
+
+        self.prev = now;
+        with_buttons(gamepad, now | self.latched)
+    }
+
+    fn reset(&mut self) { *self = Self::new(self.modifiers) }
+}
+
+
+
+/// Keep [`buttons`](Self::buttons) held for at least [`duration`](Self::duration) after being pressed, for players who can't hold buttons down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HoldExtender {
+    /// Buttons to extend.
+    pub buttons:    Buttons,
+
+    /// Minimum time each press is held for.
+    pub duration:   Duration,
+
+    prev:           u16,
+    until:          [Option<Instant>; 16],
+}
+
+impl HoldExtender {
+    /// Hold `buttons` for at least `duration` per press.
+    pub fn new(buttons: Buttons, duration: Duration) -> Self { Self { buttons, duration, prev: 0, until: [None; 16] } }
+}
+
+impl Transform for HoldExtender {
+    fn transform(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Gamepad {
+        let now = gamepad.buttons.into_inner();
+        let mut out = now;
+        for (bit, mask) in bits(self.buttons) {
+            if now & mask != 0 && self.prev & mask == 0 { self.until[bit] = Some(timestamp + self.duration) }
+            match self.until[bit] {
+                Some(until) if timestamp < until    => out |= mask,
+                _                                   => self.until[bit] = None,
+            }
+        }
+        self.prev = now;
+        with_buttons(gamepad, out)
+    }
+
+    fn reset(&mut self) { self.prev = 0; self.until = [None; 16] }
+}
+
+
+
+/// A sequence of [`Transform`]s, applied in order.
+#[derive(Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    /// An empty pipeline (which passes states through unmodified.)
+    pub fn new() -> Self { Self::default() }
+
+    /// Append a transform to the end of the pipeline.
+    pub fn push(&mut self, transform: impl Transform + 'static) { self.transforms.push(Box::new(transform)) }
+
+    /// Number of transforms in the pipeline.
+    pub fn len(&self) -> usize { self.transforms.len() }
+
+    /// Returns `true` if the pipeline has no transforms.
+    pub fn is_empty(&self) -> bool { self.transforms.is_empty() }
+
+    /// [`get_state`] for `user_index`, transformed as of [`Instant::now`].
+    /// The [`State::packet_number`] is passed through unmodified.
+    pub fn get_state(&mut self, user_index: impl TryInto<u32>) -> Result<State, Error> {
+        let mut state = crate::get_state(user_index)?;
+        state.gamepad = self.transform(&state.gamepad, Instant::now());
+        Ok(state)
+    }
+}
+
+impl Transform for Pipeline {
+    fn transform(&mut self, gamepad: &Gamepad, timestamp: Instant) -> Gamepad {
+        self.transforms.iter_mut().fold(*gamepad, |gamepad, t| t.transform(&gamepad, timestamp))
+    }
+
+    fn reset(&mut self) { self.transforms.iter_mut().for_each(|t| t.reset()) }
+}
+
+
+
+#[cfg(test)] fn script(t: &mut impl Transform, steps: &[(u64, Buttons)]) -> Vec<Buttons> {
+    let t0 = Instant::now();
+    steps.iter().map(|&(ms, buttons)| t.transform(&Gamepad { buttons, .. Gamepad::default() }, t0 + Duration::from_millis(ms)).buttons).collect()
+}
+
+#[test] fn test_turbo() {
+    let (a, b, none) = (Buttons::A, Buttons::B, Buttons::None);
+    let mut turbo = Turbo::new(Buttons::A, 10.0);
+    assert_eq!(vec![a|b, a|b, b, a|b, b, none, a], script(&mut turbo, &[(0, a|b), (40, a|b), (60, a|b), (110, a|b), (250, a|b), (300, none), (310, a)]));
+    turbo.rate = 0.0;
+    assert_eq!(vec![a, a], script(&mut turbo, &[(0, a), (60, a)]));
+}
+
+#[test] fn test_toggle_hold() {
+    let (rb, x, none) = (Buttons::RightShoulder, Buttons::X, Buttons::None);
+    let mut toggle = ToggleHold::new(rb);
+    assert_eq!(vec![rb, rb, rb|x, rb, none, none, rb], script(&mut toggle, &[(0, rb), (10, none), (20, x), (30, none), (40, rb), (50, none), (60, rb)]));
+    toggle.reset();
+    assert_eq!(Buttons::None, toggle.latched());
+}
+
+#[test] fn test_sticky_modifiers() {
+    let (lb, a, none) = (Buttons::LeftShoulder, Buttons::A, Buttons::None);
+    let mut sticky = StickyModifiers::new(lb);
+    // tap modifier, then press another button
+    assert_eq!(vec![lb, lb, lb|a, none, none], script(&mut sticky, &[(0, lb), (10, none), (20, a), (30, none), (40, none)]));
+    // chording works as usual
+    assert_eq!(vec![lb, lb|a, lb, none], script(&mut sticky, &[(0, lb), (10, lb|a), (20, lb), (30, none)]));
+    // tap twice to cancel
+    assert_eq!(vec![lb, lb, lb, none, none], script(&mut sticky, &[(0, lb), (10, none), (20, lb), (30, none), (40, none)]));
+    assert_eq!(Buttons::None, sticky.latched());
+}
+
+#[test] fn test_hold_extender_and_pipeline() {
+    let (x, y, none) = (Buttons::X, Buttons::Y, Buttons::None);
+    let mut extend = HoldExtender::new(x, Duration::from_millis(100));
+    assert_eq!(vec![x, x, x, none, x|y, y], script(&mut extend, &[(0, x), (10, none), (99, none), (100, none), (200, x|y), (300, y)]));
+
+    let mut pipeline = Pipeline::new();
+    assert!(pipeline.is_empty());
+    pipeline.push(ToggleHold::new(x));
+    pipeline.push(Turbo::new(x, 10.0));
+    assert_eq!(2, pipeline.len());
+    assert_eq!(vec![x, x, none, x, none], script(&mut pipeline, &[(0, x), (10, none), (60, none), (110, none), (120, x)]));
+}